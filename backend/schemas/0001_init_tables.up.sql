@@ -8,3 +8,6 @@ CREATE TABLE IF NOT EXISTS todos
     updated_at TIMESTAMP   NOT NULL,
     PRIMARY KEY (namespace, id)
 );
+
+CREATE INDEX IF NOT EXISTS todos_content_search
+    ON todos USING GIN (to_tsvector('simple', content));
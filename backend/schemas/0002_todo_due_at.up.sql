@@ -0,0 +1,4 @@
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS due_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS todos_due_at
+    ON todos (namespace, due_at) WHERE due_at IS NOT NULL;
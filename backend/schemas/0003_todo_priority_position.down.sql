@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS todos_position;
+DROP INDEX IF EXISTS todos_priority;
+
+ALTER TABLE todos DROP COLUMN IF EXISTS position;
+ALTER TABLE todos DROP COLUMN IF EXISTS priority;
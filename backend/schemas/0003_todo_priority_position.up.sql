@@ -0,0 +1,10 @@
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS priority SMALLINT NOT NULL DEFAULT 1;
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS position BIGINT NOT NULL DEFAULT 0;
+
+-- keep the newest first as the initial manual order
+UPDATE todos SET position = -id * 1024;
+
+CREATE INDEX IF NOT EXISTS todos_priority
+    ON todos (namespace, priority, id);
+CREATE INDEX IF NOT EXISTS todos_position
+    ON todos (namespace, position, id);
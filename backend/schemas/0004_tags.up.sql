@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS tags
+(
+    namespace  VARCHAR(36) NOT NULL,
+    id         SERIAL      NOT NULL,
+    name       VARCHAR(64) NOT NULL,
+    created_at TIMESTAMP   NOT NULL,
+    PRIMARY KEY (namespace, id),
+    UNIQUE (namespace, name)
+);
+
+CREATE TABLE IF NOT EXISTS todo_tags
+(
+    namespace VARCHAR(36) NOT NULL,
+    todo_id   INT         NOT NULL,
+    tag_id    INT         NOT NULL,
+    PRIMARY KEY (namespace, todo_id, tag_id),
+    FOREIGN KEY (namespace, todo_id) REFERENCES todos (namespace, id) ON DELETE CASCADE,
+    FOREIGN KEY (namespace, tag_id) REFERENCES tags (namespace, id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS todo_tags_tag
+    ON todo_tags (namespace, tag_id);
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS todos_parent;
+
+ALTER TABLE todos DROP CONSTRAINT IF EXISTS todos_parent;
+ALTER TABLE todos DROP COLUMN IF EXISTS parent_id;
@@ -0,0 +1,8 @@
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS parent_id INT;
+
+-- subtasks live in the namespace of their parent and go with it
+ALTER TABLE todos ADD CONSTRAINT todos_parent
+    FOREIGN KEY (namespace, parent_id) REFERENCES todos (namespace, id) ON DELETE CASCADE;
+
+CREATE INDEX IF NOT EXISTS todos_parent
+    ON todos (namespace, parent_id) WHERE parent_id IS NOT NULL;
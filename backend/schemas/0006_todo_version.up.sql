@@ -0,0 +1,2 @@
+-- counts the writes of the row, see `If-Match`
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS version INT NOT NULL DEFAULT 1;
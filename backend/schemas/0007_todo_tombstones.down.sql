@@ -0,0 +1,5 @@
+DROP TRIGGER IF EXISTS todos_tombstone ON todos;
+DROP FUNCTION IF EXISTS todo_tombstone();
+
+DROP INDEX IF EXISTS todos_updated_at;
+DROP TABLE IF EXISTS todo_tombstones;
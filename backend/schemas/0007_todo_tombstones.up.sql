@@ -0,0 +1,28 @@
+-- the hard-deleted todos, subtasks included, see GET /todos/changes
+CREATE TABLE IF NOT EXISTS todo_tombstones
+(
+    namespace  VARCHAR(36) NOT NULL,
+    id         INT         NOT NULL,
+    deleted_at TIMESTAMP   NOT NULL,
+    PRIMARY KEY (namespace, id)
+);
+
+CREATE INDEX IF NOT EXISTS todo_tombstones_deleted_at
+    ON todo_tombstones (namespace, deleted_at);
+
+CREATE INDEX IF NOT EXISTS todos_updated_at
+    ON todos (namespace, updated_at);
+
+CREATE OR REPLACE FUNCTION todo_tombstone() RETURNS TRIGGER AS
+$$
+BEGIN
+    INSERT INTO todo_tombstones (namespace, id, deleted_at)
+    VALUES (OLD.namespace, OLD.id, now() AT TIME ZONE 'UTC')
+    ON CONFLICT (namespace, id) DO UPDATE SET deleted_at = EXCLUDED.deleted_at;
+    RETURN OLD;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE TRIGGER todos_tombstone
+    AFTER DELETE ON todos
+    FOR EACH ROW EXECUTE FUNCTION todo_tombstone();
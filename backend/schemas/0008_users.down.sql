@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS namespace_owners;
+DROP TABLE IF EXISTS sessions;
+DROP TABLE IF EXISTS users;
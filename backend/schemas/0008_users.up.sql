@@ -0,0 +1,31 @@
+CREATE TABLE IF NOT EXISTS users
+(
+    id            SERIAL PRIMARY KEY,
+    username      VARCHAR(32) NOT NULL UNIQUE,
+    -- PHC string of scrypt
+    password_hash TEXT        NOT NULL,
+    created_at    TIMESTAMP   NOT NULL
+);
+
+-- the bearer tokens of POST /auth/login, by their sha-256
+CREATE TABLE IF NOT EXISTS sessions
+(
+    token_hash CHAR(64)  PRIMARY KEY,
+    user_id    INT       NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL,
+    expires_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS sessions_expires_at
+    ON sessions (expires_at);
+
+-- a namespace without an owner is open to anyone
+CREATE TABLE IF NOT EXISTS namespace_owners
+(
+    namespace  VARCHAR(36) PRIMARY KEY,
+    user_id    INT         NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    created_at TIMESTAMP   NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS namespace_owners_user_id
+    ON namespace_owners (user_id);
@@ -0,0 +1,20 @@
+DROP TABLE IF EXISTS namespace_invitations;
+
+CREATE TABLE IF NOT EXISTS namespace_owners
+(
+    namespace  VARCHAR(36) PRIMARY KEY,
+    user_id    INT         NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    created_at TIMESTAMP   NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS namespace_owners_user_id
+    ON namespace_owners (user_id);
+
+-- the earliest owner keeps the namespace, the other members lose it
+INSERT INTO namespace_owners (namespace, user_id, created_at)
+SELECT DISTINCT ON (namespace) namespace, user_id, created_at
+FROM namespace_members
+WHERE role = 2
+ORDER BY namespace, created_at, user_id;
+
+DROP TABLE IF EXISTS namespace_members;
@@ -0,0 +1,30 @@
+-- the owners become members, a namespace without members is open to anyone
+CREATE TABLE IF NOT EXISTS namespace_members
+(
+    namespace  VARCHAR(36) NOT NULL,
+    user_id    INT         NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    -- 0 viewer, 1 editor, 2 owner
+    role       SMALLINT    NOT NULL CHECK (role BETWEEN 0 AND 2),
+    created_at TIMESTAMP   NOT NULL,
+    PRIMARY KEY (namespace, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS namespace_members_user_id
+    ON namespace_members (user_id);
+
+INSERT INTO namespace_members (namespace, user_id, role, created_at)
+SELECT namespace, user_id, 2, created_at
+FROM namespace_owners;
+
+DROP TABLE IF EXISTS namespace_owners;
+
+-- the single-use tokens of POST /members/invitations, by their sha-256
+CREATE TABLE IF NOT EXISTS namespace_invitations
+(
+    token_hash CHAR(64)    PRIMARY KEY,
+    namespace  VARCHAR(36) NOT NULL,
+    role       SMALLINT    NOT NULL CHECK (role BETWEEN 0 AND 2),
+    created_by INT         NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    created_at TIMESTAMP   NOT NULL,
+    expires_at TIMESTAMP   NOT NULL
+);
@@ -0,0 +1,16 @@
+-- the keys of POST /keys, by their sha-256, a key acts with no more than the role of its creator
+CREATE TABLE IF NOT EXISTS api_keys
+(
+    id           SERIAL PRIMARY KEY,
+    namespace    VARCHAR(36) NOT NULL,
+    name         VARCHAR(64) NOT NULL,
+    key_hash     CHAR(64)    NOT NULL UNIQUE,
+    -- 0 viewer, 1 editor
+    role         SMALLINT    NOT NULL CHECK (role BETWEEN 0 AND 1),
+    created_by   INT         NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    created_at   TIMESTAMP   NOT NULL,
+    last_used_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS api_keys_namespace
+    ON api_keys (namespace);
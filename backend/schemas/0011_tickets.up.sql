@@ -0,0 +1,11 @@
+-- the single-use tokens of POST /auth/tickets opening a stream, by their sha-256
+CREATE TABLE IF NOT EXISTS tickets
+(
+    token_hash CHAR(64)  PRIMARY KEY,
+    user_id    INT       NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL,
+    expires_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS tickets_expires_at
+    ON tickets (expires_at);
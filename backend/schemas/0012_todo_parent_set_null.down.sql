@@ -0,0 +1,3 @@
+ALTER TABLE todos DROP CONSTRAINT IF EXISTS todos_parent;
+ALTER TABLE todos ADD CONSTRAINT todos_parent
+    FOREIGN KEY (namespace, parent_id) REFERENCES todos (namespace, id) ON DELETE CASCADE;
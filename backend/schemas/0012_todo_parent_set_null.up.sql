@@ -0,0 +1,4 @@
+-- a hard-deleted parent leaves its subtasks as top-level todos, the domain closes the open ones before
+ALTER TABLE todos DROP CONSTRAINT IF EXISTS todos_parent;
+ALTER TABLE todos ADD CONSTRAINT todos_parent
+    FOREIGN KEY (namespace, parent_id) REFERENCES todos (namespace, id) ON DELETE SET NULL (parent_id);
@@ -0,0 +1,6 @@
+-- the latest `deleted_at` of the purged tombstones, the changes since before it miss deleted ids
+CREATE TABLE IF NOT EXISTS todo_tombstone_horizons
+(
+    namespace    VARCHAR(36) NOT NULL PRIMARY KEY,
+    purged_until TIMESTAMP   NOT NULL
+);
@@ -0,0 +1,627 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use rand::rngs::OsRng;
+use scrypt::Scrypt;
+use scrypt::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use serde::{Deserialize, Deserializer};
+use serde::de::Error as _;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use common::model::NamespaceRole;
+
+use crate::domains::auth_repository::AuthRepository;
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct AuthConfig {
+    /// anyone may use the namespaces without an owner, else the users only
+    pub anonymous: bool,
+    /// how long a token of `POST /auth/login` is valid
+    pub session_days: i64,
+    /// how long a token of `POST /members/invitations` may be accepted
+    pub invitation_days: i64,
+    /// how long a ticket of `POST /auth/tickets` may open a stream
+    pub ticket_secs: i64,
+    /// a user may claim a namespace which already holds todos, locking out the anonymous users of them
+    pub claim_used: bool,
+    /// human-readable names of the namespaces, `t-ns: Team` stands for the namespace `Team` maps to
+    #[serde(deserialize_with = "distinct_aliases")]
+    pub aliases: HashMap<String, String>,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            anonymous: true,
+            session_days: 30,
+            invitation_days: 7,
+            ticket_secs: 60,
+            claim_used: false,
+            aliases: HashMap::new(),
+        }
+    }
+}
+
+/// the aliases are matched regardless of the case, two of them differing only in case would be ambiguous
+fn distinct_aliases<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HashMap<String, String>, D::Error> {
+    let aliases = HashMap::<String, String>::deserialize(deserializer)?;
+
+    let mut keys = HashMap::new();
+    for alias in aliases.keys() {
+        if let Some(other) = keys.insert(alias.trim().to_lowercase(), alias) {
+            return Err(D::Error::custom(format!("aliases [{}] and [{}] differ only in case", other, alias)));
+        }
+    }
+
+    Ok(aliases)
+}
+
+impl AuthConfig {
+    /// the namespace of a `t-ns` header or `?ns=`, trimmed, an alias for what it maps to regardless of the case
+    pub fn resolve_namespace(&self, namespace: &str) -> Result<String, AuthError> {
+        let namespace = namespace.trim();
+        let namespace = self.aliases.iter()
+            .find(|(alias, _)| alias.trim().to_lowercase() == namespace.to_lowercase())
+            .map_or(namespace, |(_, it)| it.as_str());
+
+        check_namespace_id(namespace)?;
+        Ok(namespace.to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct User {
+    pub id: i32,
+    pub username: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl User {
+    /// owned since the registration, used without a `t-ns` header
+    pub fn namespace(&self) -> String {
+        format!("{}{}", PERSONAL_PREFIX, self.username)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    pub user_id: i32,
+    pub username: String,
+    pub role: NamespaceRole,
+    pub created_at: DateTime<Utc>,
+}
+
+/// the access of a user to a namespace
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Membership {
+    /// the namespace has members, so it is closed to anyone else
+    pub owned: bool,
+    /// `None` unless the user is a member
+    pub role: Option<NamespaceRole>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Invitation {
+    pub token: String,
+    pub namespace: String,
+    pub role: NamespaceRole,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// a key of `POST /keys`, bound to its namespace
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiKey {
+    pub id: i32,
+    pub namespace: String,
+    pub name: String,
+    /// no more than the role of its creator
+    pub role: NamespaceRole,
+    pub created_by: i32,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// the secret of a created `ApiKey`, it is not stored
+#[derive(Debug, Clone)]
+pub struct NewApiKey {
+    pub key: String,
+    pub api_key: ApiKey,
+}
+
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// a single-use token opening a stream, which cannot send the bearer token in a header
+#[derive(Debug, Clone)]
+pub struct Ticket {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// the personal namespaces start with it, nobody else may own or use them
+pub const PERSONAL_PREFIX: char = '@';
+
+/// the namespace without a `t-ns` header, it cannot be owned
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+pub const MAX_NAMESPACE_LEN: usize = 36;
+
+/// the API keys start with it, so they are told apart from the bearer tokens by a glance or a secret scanner
+pub const API_KEY_PREFIX: &str = "tk_";
+
+pub const MAX_API_KEY_NAME_LEN: usize = 64;
+
+#[derive(Error, Debug)]
+pub enum AuthError {
+    #[error("invalid username or password")]
+    InvalidCredentials,
+    #[error("missing, invalid or expired credentials")]
+    Unauthenticated,
+    #[error("Namespace [{0}] is owned by another user.")]
+    Forbidden(String),
+    #[error("Namespace [{0}] requires the {1} role.")]
+    RoleRequired(String, NamespaceRole),
+    #[error("Member [user_id: {0}] not found.")]
+    MemberNotFound(i32),
+    #[error("invitation not found, used or expired")]
+    InvitationNotFound,
+    #[error("Namespace [{0}] has no other owner.")]
+    LastOwner(String),
+    #[error("ApiKey [id: {0}] not found.")]
+    ApiKeyNotFound(i32),
+    #[error("User [username: {0}] already exists.")]
+    UserAlreadyExists(String),
+    #[error("Namespace [{0}] is already owned.")]
+    NamespaceAlreadyOwned(String),
+    #[error("Namespace [{0}] already holds todos, only an empty one can be claimed.")]
+    NamespaceNotEmpty(String),
+    #[error("invalid username [{0}], expect 3 to 32 lowercase letters, digits, `-` or `_`")]
+    InvalidUsername(String),
+    #[error("invalid password, expect 8 to 128 characters")]
+    InvalidPassword,
+    #[error("invalid namespace [{0}], expect 1 to 36 characters, neither `default` nor starting with `@`")]
+    InvalidNamespace(String),
+    #[error("malformed namespace [{0}], expect 1 to 36 ascii letters, digits, `-`, `_`, `.` or `/`, or an alias")]
+    MalformedNamespace(String),
+    #[error("invalid api key name [{0}], expect 1 to 64 characters")]
+    InvalidApiKeyName(String),
+    #[error("invalid api key role [{0}], expect a viewer or an editor")]
+    InvalidApiKeyRole(NamespaceRole),
+}
+
+/// 3 to 32 lowercase ascii letters, digits, `-` or `_`
+pub fn check_username(username: &str) -> Result<(), AuthError> {
+    let valid = (3..=32).contains(&username.len()) &&
+        username.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+
+    if !valid {
+        return Err(AuthError::InvalidUsername(username.to_string()));
+    }
+    Ok(())
+}
+
+/// 8 to 128 characters
+pub fn check_password(password: &str) -> Result<(), AuthError> {
+    if !(8..=128).contains(&password.chars().count()) {
+        return Err(AuthError::InvalidPassword);
+    }
+    Ok(())
+}
+
+/// what fits the `VARCHAR(36)` columns: 1 to 36 ascii letters, digits, `-`, `_`, `.` or `/`, led by `@` for a personal one
+pub fn check_namespace_id(namespace: &str) -> Result<(), AuthError> {
+    let name = namespace.strip_prefix(PERSONAL_PREFIX).unwrap_or(namespace);
+    let valid = !name.is_empty() && namespace.len() <= MAX_NAMESPACE_LEN &&
+        name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' || c == '/');
+
+    if !valid {
+        return Err(AuthError::MalformedNamespace(namespace.to_string()));
+    }
+    Ok(())
+}
+
+/// a namespace which can be claimed by a user
+pub fn check_namespace(namespace: &str) -> Result<(), AuthError> {
+    check_namespace_id(namespace)?;
+
+    if namespace == DEFAULT_NAMESPACE || namespace.starts_with(PERSONAL_PREFIX) {
+        return Err(AuthError::InvalidNamespace(namespace.to_string()));
+    }
+    Ok(())
+}
+
+/// the role of the caller in the namespace, anyone may edit a namespace without members
+pub fn check_access(namespace: &str, membership: &Membership, user: Option<&User>, anonymous: bool) -> Result<NamespaceRole, AuthError> {
+    match (membership, user) {
+        (Membership { role: Some(role), .. }, Some(_)) => Ok(*role),
+        (Membership { owned: true, .. }, Some(_)) => Err(AuthError::Forbidden(namespace.to_string())),
+        (Membership { owned: true, .. }, None) => Err(AuthError::Unauthenticated),
+        // reserved for the users to come
+        _ if namespace.starts_with(PERSONAL_PREFIX) => Err(AuthError::Forbidden(namespace.to_string())),
+        (_, Some(_)) => Ok(NamespaceRole::Editor),
+        (_, None) if anonymous => Ok(NamespaceRole::Editor),
+        (_, None) => Err(AuthError::Unauthenticated),
+    }
+}
+
+/// an API key may use its own namespace only, with its role
+pub fn check_api_key(namespace: &str, api_key: &ApiKey) -> Result<NamespaceRole, AuthError> {
+    if namespace != api_key.namespace {
+        return Err(AuthError::Forbidden(namespace.to_string()));
+    }
+    Ok(api_key.role)
+}
+
+/// 1 to 64 characters, a viewer or an editor, the owners are people
+pub fn check_api_key_request(name: &str, role: NamespaceRole) -> Result<(), AuthError> {
+    if name.trim().is_empty() || name.chars().count() > MAX_API_KEY_NAME_LEN {
+        return Err(AuthError::InvalidApiKeyName(name.to_string()));
+    }
+    if role > NamespaceRole::Editor {
+        return Err(AuthError::InvalidApiKeyRole(role));
+    }
+    Ok(())
+}
+
+/// whether the role allows what requires the `required` one
+pub fn check_role(namespace: &str, role: NamespaceRole, required: NamespaceRole) -> Result<(), AuthError> {
+    if role < required {
+        return Err(AuthError::RoleRequired(namespace.to_string(), required));
+    }
+    Ok(())
+}
+
+/// PHC string of scrypt with a random salt
+pub fn hash_password(password: &str) -> Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    Ok(Scrypt.hash_password(password.as_bytes(), &salt)
+        .map_err(|err| anyhow::anyhow!("failed to hash password: {}", err))?
+        .to_string())
+}
+
+pub fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .is_ok_and(|hash| Scrypt.verify_password(password.as_bytes(), &hash).is_ok())
+}
+
+/// verified in place of the hash of an unknown user, hashed with the same parameters as the others
+static DUMMY_PASSWORD_HASH: LazyLock<String> = LazyLock::new(|| {
+    hash_password(&new_token()).expect("a random password is hashed")
+});
+
+/// only the sha-256 of a token is stored
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// 32 random bytes in hex
+fn new_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+#[async_trait]
+pub trait AuthDomainTrait {
+    /// the user owns its personal namespace from now on
+    async fn register(&self, username: &str, password: &str) -> Result<User>;
+    async fn login(&self, username: &str, password: &str) -> Result<Session>;
+    async fn logout(&self, token: &str) -> Result<()>;
+    /// the user of an unexpired token
+    async fn authenticate(&self, token: &str) -> Result<Option<User>>;
+    /// a ticket of the user, for `GET /todos/events` and `GET /todos/sync` only
+    async fn issue_ticket(&self, user: &User) -> Result<Ticket>;
+    /// the user of an unexpired ticket, which is used up
+    async fn authenticate_ticket(&self, ticket: &str) -> Result<Option<User>>;
+    async fn membership(&self, namespace: &str, user: Option<&User>) -> Result<Membership>;
+    /// the namespaces the user is a member of, the personal one first
+    async fn namespaces(&self, user: &User) -> Result<Vec<String>>;
+    /// own a namespace without members, claiming it again as an owner is fine
+    async fn claim_namespace(&self, user: &User, namespace: &str) -> Result<()>;
+    /// owners first
+    async fn list_members(&self, namespace: &str) -> Result<Vec<Member>>;
+    /// an owned namespace keeps at least one owner
+    async fn update_member(&self, namespace: &str, user_id: i32, role: NamespaceRole) -> Result<Member>;
+    /// an owned namespace keeps at least one owner
+    async fn remove_member(&self, namespace: &str, user_id: i32) -> Result<()>;
+    /// a single-use token to join the namespace with the role
+    async fn invite(&self, user: &User, namespace: &str, role: NamespaceRole) -> Result<Invitation>;
+    /// the namespace joined
+    async fn accept_invitation(&self, user: &User, token: &str) -> Result<String>;
+    /// the key is shown once
+    async fn create_api_key(&self, user: &User, namespace: &str, name: &str, role: NamespaceRole) -> Result<NewApiKey>;
+    /// newest first
+    async fn list_api_keys(&self, namespace: &str) -> Result<Vec<ApiKey>>;
+    async fn revoke_api_key(&self, namespace: &str, id: i32) -> Result<()>;
+    /// the key, used just now, while its creator is still a member of its namespace
+    async fn authenticate_api_key(&self, key: &str) -> Result<Option<ApiKey>>;
+}
+
+#[derive(Clone)]
+pub struct AuthDomain {
+    repo: AuthRepository,
+    config: AuthConfig,
+}
+
+impl AuthDomain {
+    pub fn new(pool: deadpool_postgres::Pool, config: AuthConfig) -> Self {
+        // hashed up front, not by the first login of an unknown user
+        LazyLock::force(&DUMMY_PASSWORD_HASH);
+        Self { repo: AuthRepository::new(pool), config }
+    }
+}
+
+#[async_trait]
+impl AuthDomainTrait for AuthDomain {
+    async fn register(&self, username: &str, password: &str) -> Result<User> {
+        check_username(username)?;
+        check_password(password)?;
+
+        let password = password.to_string();
+        let password_hash = tokio::task::spawn_blocking(move || hash_password(&password)).await??;
+
+        self.repo.insert_user(username, &password_hash).await
+    }
+
+    async fn login(&self, username: &str, password: &str) -> Result<Session> {
+        let credentials = self.repo.query_credentials(username).await?;
+
+        // an unknown username takes as long as a wrong password, so the timing does not tell the users apart
+        let password = password.to_string();
+        let password_hash = credentials.as_ref().map(|(_, it)| it.clone());
+        let verified = tokio::task::spawn_blocking(move || match password_hash {
+            Some(password_hash) => verify_password(&password, &password_hash),
+            None => {
+                verify_password(&password, &DUMMY_PASSWORD_HASH);
+                false
+            }
+        }).await?;
+        let user = match credentials {
+            Some((user, _)) if verified => user,
+            _ => return Err(AuthError::InvalidCredentials.into()),
+        };
+
+        let session = Session {
+            token: new_token(),
+            expires_at: Utc::now() + chrono::Duration::days(self.config.session_days),
+        };
+        self.repo.insert_session(&hash_token(&session.token), user.id, session.expires_at).await?;
+
+        Ok(session)
+    }
+
+    async fn logout(&self, token: &str) -> Result<()> {
+        self.repo.delete_session(&hash_token(token)).await
+    }
+
+    async fn authenticate(&self, token: &str) -> Result<Option<User>> {
+        self.repo.query_session_user(&hash_token(token)).await
+    }
+
+    async fn issue_ticket(&self, user: &User) -> Result<Ticket> {
+        let ticket = Ticket {
+            token: new_token(),
+            expires_at: Utc::now() + chrono::Duration::seconds(self.config.ticket_secs),
+        };
+        self.repo.insert_ticket(&hash_token(&ticket.token), user.id, ticket.expires_at).await?;
+
+        Ok(ticket)
+    }
+
+    async fn authenticate_ticket(&self, ticket: &str) -> Result<Option<User>> {
+        self.repo.use_ticket(&hash_token(ticket)).await
+    }
+
+    async fn membership(&self, namespace: &str, user: Option<&User>) -> Result<Membership> {
+        self.repo.query_membership(namespace, user.map(|it| it.id)).await
+    }
+
+    async fn namespaces(&self, user: &User) -> Result<Vec<String>> {
+        self.repo.query_namespaces(user.id).await
+    }
+
+    async fn claim_namespace(&self, user: &User, namespace: &str) -> Result<()> {
+        check_namespace(namespace)?;
+
+        match self.repo.insert_first_owner(namespace, user.id, !self.config.claim_used).await? {
+            true => Ok(()),
+            false => Err(AuthError::NamespaceAlreadyOwned(namespace.to_string()).into()),
+        }
+    }
+
+    async fn list_members(&self, namespace: &str) -> Result<Vec<Member>> {
+        self.repo.query_members(namespace).await
+    }
+
+    async fn update_member(&self, namespace: &str, user_id: i32, role: NamespaceRole) -> Result<Member> {
+        self.repo.update_role(namespace, user_id, role).await
+    }
+
+    async fn remove_member(&self, namespace: &str, user_id: i32) -> Result<()> {
+        self.repo.delete_member(namespace, user_id).await
+    }
+
+    async fn invite(&self, user: &User, namespace: &str, role: NamespaceRole) -> Result<Invitation> {
+        let invitation = Invitation {
+            token: new_token(),
+            namespace: namespace.to_string(),
+            role,
+            expires_at: Utc::now() + chrono::Duration::days(self.config.invitation_days),
+        };
+        self.repo.insert_invitation(&hash_token(&invitation.token), namespace, role, user.id, invitation.expires_at).await?;
+
+        Ok(invitation)
+    }
+
+    async fn accept_invitation(&self, user: &User, token: &str) -> Result<String> {
+        self.repo.accept_invitation(&hash_token(token), user.id).await
+    }
+
+    async fn create_api_key(&self, user: &User, namespace: &str, name: &str, role: NamespaceRole) -> Result<NewApiKey> {
+        check_api_key_request(name, role)?;
+
+        let key = format!("{}{}", API_KEY_PREFIX, new_token());
+        let api_key = self.repo.insert_api_key(&hash_token(&key), namespace, name.trim(), role, user.id).await?;
+
+        Ok(NewApiKey { key, api_key })
+    }
+
+    async fn list_api_keys(&self, namespace: &str) -> Result<Vec<ApiKey>> {
+        self.repo.query_api_keys(namespace).await
+    }
+
+    async fn revoke_api_key(&self, namespace: &str, id: i32) -> Result<()> {
+        self.repo.delete_api_key(namespace, id).await
+    }
+
+    async fn authenticate_api_key(&self, key: &str) -> Result<Option<ApiKey>> {
+        if !key.starts_with(API_KEY_PREFIX) {
+            return Ok(None);
+        }
+        self.repo.use_api_key(&hash_token(key)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    fn user(id: i32) -> User {
+        User { id, username: format!("user-{}", id), created_at: Utc::now() }
+    }
+
+    #[test]
+    fn validation() {
+        assert!(check_username("alice_2").is_ok());
+        for invalid in ["al", "Alice", "a l", "alice@", &"a".repeat(33)] {
+            assert!(matches!(check_username(invalid), Err(AuthError::InvalidUsername(_))), "{}", invalid);
+        }
+
+        assert!(check_password("12345678").is_ok());
+        assert!(check_password("1234567").is_err());
+
+        assert!(check_namespace("work").is_ok());
+        for invalid in ["default", "@alice"] {
+            assert!(matches!(check_namespace(invalid), Err(AuthError::InvalidNamespace(_))), "{}", invalid);
+        }
+        for malformed in ["", "a b", &"n".repeat(37)] {
+            assert!(matches!(check_namespace(malformed), Err(AuthError::MalformedNamespace(_))), "{}", malformed);
+        }
+
+        for valid in ["default", "@alice_2", "Work.v2", "testing/integration", "0c5ed5ad-9e8a-4bd8-8fb6-ac1d5b47a1e4"] {
+            assert!(check_namespace_id(valid).is_ok(), "{}", valid);
+        }
+        for malformed in ["", "@", "@@alice", "wörk", "a:b", "a b", &"n".repeat(37)] {
+            assert!(matches!(check_namespace_id(malformed), Err(AuthError::MalformedNamespace(_))), "{}", malformed);
+        }
+    }
+
+    #[test]
+    fn resolve_namespace() {
+        let config = AuthConfig {
+            aliases: HashMap::from([
+                ("Équipe Web".to_string(), "0c5ed5ad-9e8a-4bd8-8fb6-ac1d5b47a1e4".to_string()),
+                ("broken".to_string(), "not an id".to_string()),
+            ]),
+            ..Default::default()
+        };
+
+        assert_eq!("work", config.resolve_namespace(" work ").unwrap());
+        assert_eq!("0c5ed5ad-9e8a-4bd8-8fb6-ac1d5b47a1e4", config.resolve_namespace("équipe web").unwrap());
+        assert!(matches!(config.resolve_namespace("Équipe"), Err(AuthError::MalformedNamespace(_))));
+        assert!(matches!(config.resolve_namespace("broken"), Err(AuthError::MalformedNamespace(_))));
+    }
+
+    #[test]
+    fn ambiguous_aliases() {
+        let load = |toml: &str| ::config::Config::builder()
+            .add_source(::config::File::from_str(toml, ::config::FileFormat::Toml))
+            .build().unwrap()
+            .try_deserialize::<AuthConfig>();
+
+        let config = load("[aliases]\n\"Team\" = \"team-a\"\n\"Équipe\" = \"team-b\"\n").unwrap();
+        assert_eq!(2, config.aliases.len());
+
+        let err = load("[aliases]\n\"Team\" = \"team-a\"\n\" team\" = \"team-b\"\n").unwrap_err();
+        assert!(err.to_string().contains("differ only in case"), "{}", err);
+    }
+
+    #[test]
+    fn access() {
+        let (alice, bob) = (user(1), user(2));
+        let member = |role| Membership { owned: true, role: Some(role) };
+        let (owned, open) = (Membership { owned: true, role: None }, Membership::default());
+
+        assert_eq!(NamespaceRole::Owner, check_access("work", &member(NamespaceRole::Owner), Some(&alice), false).unwrap());
+        assert_eq!(NamespaceRole::Viewer, check_access("work", &member(NamespaceRole::Viewer), Some(&bob), false).unwrap());
+        assert!(matches!(check_access("work", &owned, Some(&bob), true), Err(AuthError::Forbidden(_))));
+        assert!(matches!(check_access("work", &owned, None, true), Err(AuthError::Unauthenticated)));
+
+        assert_eq!(NamespaceRole::Editor, check_access("open", &open, Some(&bob), false).unwrap());
+        assert_eq!(NamespaceRole::Editor, check_access("open", &open, None, true).unwrap());
+        assert!(matches!(check_access("open", &open, None, false), Err(AuthError::Unauthenticated)));
+        assert!(matches!(check_access("@carol", &open, Some(&bob), true), Err(AuthError::Forbidden(_))));
+    }
+
+    #[test]
+    fn api_key() {
+        let api_key = ApiKey {
+            id: 1,
+            namespace: "work".to_string(),
+            name: "ci".to_string(),
+            role: NamespaceRole::Viewer,
+            created_by: 1,
+            created_at: Utc::now(),
+            last_used_at: None,
+        };
+
+        assert_eq!(NamespaceRole::Viewer, check_api_key("work", &api_key).unwrap());
+        assert!(matches!(check_api_key("other", &api_key), Err(AuthError::Forbidden(_))));
+
+        assert!(check_api_key_request("nightly ci", NamespaceRole::Editor).is_ok());
+        for invalid in ["", "  ", &"k".repeat(65)] {
+            assert!(matches!(check_api_key_request(invalid, NamespaceRole::Viewer), Err(AuthError::InvalidApiKeyName(_))), "{}", invalid);
+        }
+        assert!(matches!(check_api_key_request("ci", NamespaceRole::Owner), Err(AuthError::InvalidApiKeyRole(_))));
+    }
+
+    #[test]
+    fn role() {
+        assert!(check_role("work", NamespaceRole::Owner, NamespaceRole::Editor).is_ok());
+        assert!(check_role("work", NamespaceRole::Editor, NamespaceRole::Editor).is_ok());
+        assert!(matches!(check_role("work", NamespaceRole::Viewer, NamespaceRole::Editor), Err(AuthError::RoleRequired(_, NamespaceRole::Editor))));
+        assert!(matches!(check_role("work", NamespaceRole::Editor, NamespaceRole::Owner), Err(AuthError::RoleRequired(_, NamespaceRole::Owner))));
+    }
+
+    #[test]
+    fn password() {
+        let hash = hash_password("correct horse").unwrap();
+
+        assert!(hash.starts_with("$scrypt$"));
+        assert!(verify_password("correct horse", &hash));
+        assert!(!verify_password("wrong horse", &hash));
+        assert!(!verify_password("correct horse", "not a hash"));
+    }
+
+    #[test]
+    fn token() {
+        let token = new_token();
+
+        assert_eq!(64, token.len());
+        assert_ne!(token, new_token());
+        assert_eq!(64, hash_token(&token).len());
+        assert_eq!(hash_token(&token), hash_token(&token));
+    }
+}
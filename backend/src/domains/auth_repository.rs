@@ -0,0 +1,622 @@
+use std::time::SystemTime;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::{Pool, Transaction};
+use tokio_pg_mapper::FromTokioPostgresRow;
+use tokio_pg_mapper_derive::PostgresMapper;
+use tokio_postgres::error::SqlState;
+
+use common::model::NamespaceRole;
+
+use crate::domains::auth_domain::{ApiKey, AuthError, Member, Membership, User};
+
+#[derive(PostgresMapper, Debug)]
+#[pg_mapper(table = "users")]
+struct UserEntity {
+    id: i32,
+    username: String,
+    created_at: SystemTime,
+}
+
+#[derive(PostgresMapper, Debug)]
+#[pg_mapper(table = "namespace_members")]
+struct MemberEntity {
+    user_id: i32,
+    username: String,
+    role: i16,
+    created_at: SystemTime,
+}
+
+#[derive(PostgresMapper, Debug)]
+#[pg_mapper(table = "api_keys")]
+struct ApiKeyEntity {
+    id: i32,
+    namespace: String,
+    name: String,
+    role: i16,
+    created_by: i32,
+    created_at: SystemTime,
+    last_used_at: Option<SystemTime>,
+}
+
+#[derive(Clone)]
+pub struct AuthRepository {
+    db: Pool,
+}
+
+impl AuthRepository {
+    pub fn new(db: Pool) -> Self {
+        Self { db }
+    }
+
+    /// the user owns its personal namespace along with it
+    pub async fn insert_user(&self, username: &str, password_hash: &str) -> Result<User> {
+        let mut client = self.db.get().await?;
+        let tx = client.transaction().await?;
+        let now = SystemTime::now();
+
+        let statement = tx.prepare_cached(r#"
+            INSERT INTO users (username, password_hash, created_at)
+            VALUES ($1, $2, $3)
+            RETURNING id, username, created_at
+        "#).await?;
+
+        let row = tx.query_one(&statement, &[&username, &password_hash, &now]).await
+            .map_err(|err| match err.code() {
+                Some(&SqlState::UNIQUE_VIOLATION) => AuthError::UserAlreadyExists(username.to_string()).into(),
+                _ => anyhow::Error::from(err),
+            })?;
+        let user = UserEntity::from_row(row).map(User::from)?;
+
+        let statement = tx.prepare_cached(r#"
+            INSERT INTO namespace_members (namespace, user_id, role, created_at)
+            VALUES ($1, $2, $3, $4)
+        "#).await?;
+
+        tx.execute(&statement, &[&user.namespace(), &user.id, &(NamespaceRole::Owner as i16), &now]).await?;
+        tx.commit().await?;
+
+        Ok(user)
+    }
+
+    /// the user with its password hash
+    pub async fn query_credentials(&self, username: &str) -> Result<Option<(User, String)>> {
+        let client = self.db.get().await?;
+
+        let statement = client
+            .prepare_cached("SELECT id, username, created_at, password_hash FROM users WHERE username = $1").await?;
+
+        match client.query_opt(&statement, &[&username]).await? {
+            None => Ok(None),
+            Some(row) => {
+                let password_hash = row.try_get::<_, String>("password_hash")?;
+                Ok(Some((UserEntity::from_row(row).map(User::from)?, password_hash)))
+            }
+        }
+    }
+
+    /// the expired sessions of the user are deleted meanwhile
+    pub async fn insert_session(&self, token_hash: &str, user_id: i32, expires_at: DateTime<Utc>) -> Result<()> {
+        let client = self.db.get().await?;
+        let now = SystemTime::now();
+
+        let statement = client
+            .prepare_cached("DELETE FROM sessions WHERE user_id = $1 AND expires_at <= $2").await?;
+        client.execute(&statement, &[&user_id, &now]).await?;
+
+        let statement = client.prepare_cached(r#"
+            INSERT INTO sessions (token_hash, user_id, created_at, expires_at)
+            VALUES ($1, $2, $3, $4)
+        "#).await?;
+        client.execute(&statement, &[&token_hash, &user_id, &now, &SystemTime::from(expires_at)]).await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_session(&self, token_hash: &str) -> Result<()> {
+        let client = self.db.get().await?;
+
+        let statement = client
+            .prepare_cached("DELETE FROM sessions WHERE token_hash = $1").await?;
+        client.execute(&statement, &[&token_hash]).await?;
+
+        Ok(())
+    }
+
+    /// the user of an unexpired session
+    pub async fn query_session_user(&self, token_hash: &str) -> Result<Option<User>> {
+        let client = self.db.get().await?;
+
+        let statement = client.prepare_cached(r#"
+            SELECT u.id, u.username, u.created_at
+            FROM sessions s JOIN users u ON u.id = s.user_id
+            WHERE s.token_hash = $1 AND s.expires_at > $2
+        "#).await?;
+
+        let row = client.query_opt(&statement, &[&token_hash, &SystemTime::now()]).await?;
+
+        Ok(row.map(|row| UserEntity::from_row(row).map(User::from)).transpose()?)
+    }
+
+    /// the expired tickets of anyone are deleted meanwhile
+    pub async fn insert_ticket(&self, token_hash: &str, user_id: i32, expires_at: DateTime<Utc>) -> Result<()> {
+        let client = self.db.get().await?;
+        let now = SystemTime::now();
+
+        let statement = client
+            .prepare_cached("DELETE FROM tickets WHERE expires_at <= $1").await?;
+        client.execute(&statement, &[&now]).await?;
+
+        let statement = client.prepare_cached(r#"
+            INSERT INTO tickets (token_hash, user_id, created_at, expires_at)
+            VALUES ($1, $2, $3, $4)
+        "#).await?;
+        client.execute(&statement, &[&token_hash, &user_id, &now, &SystemTime::from(expires_at)]).await?;
+
+        Ok(())
+    }
+
+    /// the user of an unexpired ticket, which is deleted along
+    pub async fn use_ticket(&self, token_hash: &str) -> Result<Option<User>> {
+        let client = self.db.get().await?;
+
+        let statement = client.prepare_cached(r#"
+            DELETE FROM tickets t
+            USING users u
+            WHERE t.token_hash = $1 AND t.expires_at > $2 AND u.id = t.user_id
+            RETURNING u.id, u.username, u.created_at
+        "#).await?;
+
+        let row = client.query_opt(&statement, &[&token_hash, &SystemTime::now()]).await?;
+
+        Ok(row.map(|row| UserEntity::from_row(row).map(User::from)).transpose()?)
+    }
+
+    /// whether the namespace has members, and the role of the user among them
+    pub async fn query_membership(&self, namespace: &str, user_id: Option<i32>) -> Result<Membership> {
+        let client = self.db.get().await?;
+
+        let statement = client.prepare_cached(r#"
+            SELECT EXISTS (SELECT 1 FROM namespace_members WHERE namespace = $1) AS owned,
+                   (SELECT role FROM namespace_members WHERE namespace = $1 AND user_id = $2) AS role
+        "#).await?;
+
+        let row = client.query_one(&statement, &[&namespace, &user_id]).await?;
+
+        Ok(Membership {
+            owned: row.try_get("owned")?,
+            role: row.try_get::<_, Option<i16>>("role")?.map(NamespaceRole::try_from).transpose()?,
+        })
+    }
+
+    /// in the order they have been joined
+    pub async fn query_namespaces(&self, user_id: i32) -> Result<Vec<String>> {
+        let client = self.db.get().await?;
+
+        let statement = client.prepare_cached(r#"
+            SELECT namespace FROM namespace_members
+            WHERE user_id = $1
+            ORDER BY created_at, namespace
+        "#).await?;
+
+        let rows = client.query(&statement, &[&user_id]).await?;
+
+        Ok(rows.into_iter()
+            .map(|row| row.try_get::<_, String>("namespace"))
+            .collect::<Result<Vec<String>, _>>()?)
+    }
+
+    /// owners first
+    pub async fn query_members(&self, namespace: &str) -> Result<Vec<Member>> {
+        let client = self.db.get().await?;
+
+        let statement = client.prepare_cached(r#"
+            SELECT m.user_id, u.username, m.role, m.created_at
+            FROM namespace_members m JOIN users u ON u.id = m.user_id
+            WHERE m.namespace = $1
+            ORDER BY m.role DESC, m.created_at, m.user_id
+        "#).await?;
+
+        let rows = client.query(&statement, &[&namespace]).await?;
+
+        rows.into_iter()
+            .map(|row| MemberEntity::from_row(row).map_err(anyhow::Error::from).and_then(Member::try_from))
+            .collect()
+    }
+
+    /// the user becomes the owner of a namespace without members, and without todos for `empty_only`,
+    /// false once the namespace has members and the user is not an owner of them
+    pub async fn insert_first_owner(&self, namespace: &str, user_id: i32, empty_only: bool) -> Result<bool> {
+        let mut client = self.db.get().await?;
+        let tx = client.transaction().await?;
+        lock_members(&tx, namespace).await?;
+
+        let statement = tx
+            .prepare_cached("SELECT user_id, role FROM namespace_members WHERE namespace = $1").await?;
+        let members = tx.query(&statement, &[&namespace]).await?
+            .into_iter()
+            .map(|row| Ok((row.try_get::<_, i32>("user_id")?, NamespaceRole::try_from(row.try_get::<_, i16>("role")?)?)))
+            .collect::<Result<Vec<(i32, NamespaceRole)>>>()?;
+        if !members.is_empty() {
+            return Ok(members.contains(&(user_id, NamespaceRole::Owner)));
+        }
+
+        if empty_only {
+            let statement = tx.prepare_cached("SELECT EXISTS (SELECT 1 FROM todos WHERE namespace = $1)").await?;
+            if tx.query_one(&statement, &[&namespace]).await?.get::<_, bool>(0) {
+                return Err(AuthError::NamespaceNotEmpty(namespace.to_string()).into());
+            }
+        }
+
+        let statement = tx.prepare_cached(r#"
+            INSERT INTO namespace_members (namespace, user_id, role, created_at)
+            VALUES ($1, $2, $3, $4)
+        "#).await?;
+        tx.execute(&statement, &[&namespace, &user_id, &(NamespaceRole::Owner as i16), &SystemTime::now()]).await?;
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    /// an owner cannot be demoted while the last one
+    pub async fn update_role(&self, namespace: &str, user_id: i32, role: NamespaceRole) -> Result<Member> {
+        let mut client = self.db.get().await?;
+        let tx = client.transaction().await?;
+        lock_members(&tx, namespace).await?;
+
+        if role != NamespaceRole::Owner {
+            check_last_owner(&tx, namespace, user_id).await?;
+        }
+
+        let statement = tx.prepare_cached(r#"
+            UPDATE namespace_members m SET role = $3
+            FROM users u
+            WHERE m.namespace = $1 AND m.user_id = $2 AND u.id = m.user_id
+            RETURNING m.user_id, u.username, m.role, m.created_at
+        "#).await?;
+        let row = tx.query_opt(&statement, &[&namespace, &user_id, &(role as i16)]).await?
+            .ok_or(AuthError::MemberNotFound(user_id))?;
+        tx.commit().await?;
+
+        MemberEntity::from_row(row).map_err(anyhow::Error::from).and_then(Member::try_from)
+    }
+
+    /// an owner cannot be removed while the last one
+    pub async fn delete_member(&self, namespace: &str, user_id: i32) -> Result<()> {
+        let mut client = self.db.get().await?;
+        let tx = client.transaction().await?;
+        lock_members(&tx, namespace).await?;
+
+        check_last_owner(&tx, namespace, user_id).await?;
+
+        let statement = tx
+            .prepare_cached("DELETE FROM namespace_members WHERE namespace = $1 AND user_id = $2").await?;
+        if tx.execute(&statement, &[&namespace, &user_id]).await? == 0 {
+            return Err(AuthError::MemberNotFound(user_id).into());
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// the expired invitations are deleted meanwhile
+    pub async fn insert_invitation(&self, token_hash: &str, namespace: &str, role: NamespaceRole, created_by: i32, expires_at: DateTime<Utc>) -> Result<()> {
+        let client = self.db.get().await?;
+        let now = SystemTime::now();
+
+        let statement = client
+            .prepare_cached("DELETE FROM namespace_invitations WHERE expires_at <= $1").await?;
+        client.execute(&statement, &[&now]).await?;
+
+        let statement = client.prepare_cached(r#"
+            INSERT INTO namespace_invitations (token_hash, namespace, role, created_by, created_at, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+        "#).await?;
+        client.execute(&statement, &[&token_hash, &namespace, &(role as i16), &created_by, &now, &SystemTime::from(expires_at)]).await?;
+
+        Ok(())
+    }
+
+    /// use up an unexpired invitation, a member keeps the greater of its role and the invited one
+    pub async fn accept_invitation(&self, token_hash: &str, user_id: i32) -> Result<String> {
+        let mut client = self.db.get().await?;
+        let tx = client.transaction().await?;
+        let now = SystemTime::now();
+
+        let statement = tx.prepare_cached(r#"
+            DELETE FROM namespace_invitations
+            WHERE token_hash = $1 AND expires_at > $2
+            RETURNING namespace, role
+        "#).await?;
+        let row = tx.query_opt(&statement, &[&token_hash, &now]).await?
+            .ok_or(AuthError::InvitationNotFound)?;
+        let (namespace, role) = (row.try_get::<_, String>("namespace")?, row.try_get::<_, i16>("role")?);
+
+        let statement = tx.prepare_cached(r#"
+            INSERT INTO namespace_members (namespace, user_id, role, created_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (namespace, user_id) DO UPDATE SET role = GREATEST(namespace_members.role, EXCLUDED.role)
+        "#).await?;
+        tx.execute(&statement, &[&namespace, &user_id, &role, &now]).await?;
+        tx.commit().await?;
+
+        Ok(namespace)
+    }
+
+    pub async fn insert_api_key(&self, key_hash: &str, namespace: &str, name: &str, role: NamespaceRole, created_by: i32) -> Result<ApiKey> {
+        let client = self.db.get().await?;
+
+        let statement = client.prepare_cached(r#"
+            INSERT INTO api_keys (namespace, name, key_hash, role, created_by, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, namespace, name, role, created_by, created_at, last_used_at
+        "#).await?;
+        let row = client.query_one(&statement, &[&namespace, &name, &key_hash, &(role as i16), &created_by, &SystemTime::now()]).await?;
+
+        ApiKeyEntity::from_row(row).map_err(anyhow::Error::from).and_then(ApiKey::try_from)
+    }
+
+    /// newest first
+    pub async fn query_api_keys(&self, namespace: &str) -> Result<Vec<ApiKey>> {
+        let client = self.db.get().await?;
+
+        let statement = client.prepare_cached(r#"
+            SELECT id, namespace, name, role, created_by, created_at, last_used_at
+            FROM api_keys
+            WHERE namespace = $1
+            ORDER BY id DESC
+        "#).await?;
+
+        let rows = client.query(&statement, &[&namespace]).await?;
+
+        rows.into_iter()
+            .map(|row| ApiKeyEntity::from_row(row).map_err(anyhow::Error::from).and_then(ApiKey::try_from))
+            .collect()
+    }
+
+    pub async fn delete_api_key(&self, namespace: &str, id: i32) -> Result<()> {
+        let client = self.db.get().await?;
+
+        let statement = client
+            .prepare_cached("DELETE FROM api_keys WHERE namespace = $1 AND id = $2").await?;
+        if client.execute(&statement, &[&namespace, &id]).await? == 0 {
+            return Err(AuthError::ApiKeyNotFound(id).into());
+        }
+
+        Ok(())
+    }
+
+    /// touch the last use of the key, with the lesser of its role and the one of its creator,
+    /// `None` once the creator is not a member anymore
+    pub async fn use_api_key(&self, key_hash: &str) -> Result<Option<ApiKey>> {
+        let client = self.db.get().await?;
+
+        let statement = client.prepare_cached(r#"
+            UPDATE api_keys k SET last_used_at = $2
+            FROM namespace_members m
+            WHERE k.key_hash = $1 AND m.namespace = k.namespace AND m.user_id = k.created_by
+            RETURNING k.id, k.namespace, k.name, LEAST(k.role, m.role) AS role, k.created_by, k.created_at, k.last_used_at
+        "#).await?;
+
+        let row = client.query_opt(&statement, &[&key_hash, &SystemTime::now()]).await?;
+
+        row.map(|row| ApiKeyEntity::from_row(row).map_err(anyhow::Error::from).and_then(ApiKey::try_from)).transpose()
+    }
+}
+
+/// the changes of the members of a namespace are serialized
+async fn lock_members(tx: &Transaction<'_>, namespace: &str) -> Result<()> {
+    tx.execute("SELECT pg_advisory_xact_lock(hashtext('members:' || $1))", &[&namespace]).await?;
+    Ok(())
+}
+
+/// fails if the user is the only owner of the namespace
+async fn check_last_owner(tx: &Transaction<'_>, namespace: &str, user_id: i32) -> Result<()> {
+    let statement = tx.prepare_cached(r#"
+        SELECT bool_and(user_id = $2) AS last
+        FROM namespace_members
+        WHERE namespace = $1 AND role = $3
+    "#).await?;
+
+    let row = tx.query_one(&statement, &[&namespace, &user_id, &(NamespaceRole::Owner as i16)]).await?;
+    if row.try_get::<_, Option<bool>>("last")?.unwrap_or(false) {
+        return Err(AuthError::LastOwner(namespace.to_string()).into());
+    }
+    Ok(())
+}
+
+impl From<UserEntity> for User {
+    fn from(user: UserEntity) -> Self {
+        Self {
+            id: user.id,
+            username: user.username,
+            created_at: DateTime::from(user.created_at),
+        }
+    }
+}
+
+impl TryFrom<MemberEntity> for Member {
+    type Error = anyhow::Error;
+
+    fn try_from(member: MemberEntity) -> Result<Self, Self::Error> {
+        Ok(Self {
+            user_id: member.user_id,
+            username: member.username,
+            role: NamespaceRole::try_from(member.role)?,
+            created_at: DateTime::from(member.created_at),
+        })
+    }
+}
+
+impl TryFrom<ApiKeyEntity> for ApiKey {
+    type Error = anyhow::Error;
+
+    fn try_from(api_key: ApiKeyEntity) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: api_key.id,
+            namespace: api_key.namespace,
+            name: api_key.name,
+            role: NamespaceRole::try_from(api_key.role)?,
+            created_by: api_key.created_by,
+            created_at: DateTime::from(api_key.created_at),
+            last_used_at: api_key.last_used_at.map(DateTime::from),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use common::model::NamespaceRole;
+
+    use crate::domains::auth_domain::AuthError;
+    use crate::domains::todo_domain::Todo;
+    use crate::domains::todo_repository::TodoRepository;
+    use crate::infra::{config, db};
+    use super::AuthRepository;
+
+    fn repo() -> AuthRepository {
+        AuthRepository::new(db::must_init(&config::must_get().db))
+    }
+
+    #[actix_web::test]
+    async fn users_and_sessions() {
+        let repo = repo();
+        let username = format!("repo-{}", Utc::now().timestamp_micros());
+
+        let user = repo.insert_user(&username, "hash").await.unwrap();
+        let duplicated = repo.insert_user(&username, "hash").await.unwrap_err();
+        assert!(matches!(duplicated.downcast_ref::<AuthError>(), Some(AuthError::UserAlreadyExists(_))));
+
+        let (found, hash) = repo.query_credentials(&username).await.unwrap().unwrap();
+        assert_eq!((user.id, "hash"), (found.id, hash.as_str()));
+        assert!(repo.query_credentials("nobody").await.unwrap().is_none());
+
+        let token_hash = format!("{:0>64}", user.id);
+        repo.insert_session(&token_hash, user.id, Utc::now() + Duration::days(1)).await.unwrap();
+        assert_eq!(Some(user.clone()), repo.query_session_user(&token_hash).await.unwrap());
+
+        repo.delete_session(&token_hash).await.unwrap();
+        assert_eq!(None, repo.query_session_user(&token_hash).await.unwrap());
+
+        let expired = format!("{:f>64}", user.id);
+        repo.insert_session(&expired, user.id, Utc::now() - Duration::days(1)).await.unwrap();
+        assert_eq!(None, repo.query_session_user(&expired).await.unwrap());
+    }
+
+    #[actix_web::test]
+    async fn tickets() {
+        let repo = repo();
+        let user = repo.insert_user(&format!("repo-t{}", Utc::now().timestamp_micros()), "hash").await.unwrap();
+
+        let token_hash = format!("{:t>64}", user.id);
+        repo.insert_ticket(&token_hash, user.id, Utc::now() + Duration::minutes(1)).await.unwrap();
+        assert_eq!(Some(user.clone()), repo.use_ticket(&token_hash).await.unwrap());
+        assert_eq!(None, repo.use_ticket(&token_hash).await.unwrap());
+
+        let expired = format!("{:x>64}", user.id);
+        repo.insert_ticket(&expired, user.id, Utc::now() - Duration::minutes(1)).await.unwrap();
+        assert_eq!(None, repo.use_ticket(&expired).await.unwrap());
+    }
+
+    #[actix_web::test]
+    async fn members() {
+        let repo = repo();
+        let micros = Utc::now().timestamp_micros();
+        let alice = repo.insert_user(&format!("repo-a{}", micros), "hash").await.unwrap();
+        let bob = repo.insert_user(&format!("repo-b{}", micros), "hash").await.unwrap();
+        let namespace = format!("repo-owned-{}", micros);
+
+        let personal = repo.query_membership(&alice.namespace(), Some(alice.id)).await.unwrap();
+        assert_eq!((true, Some(NamespaceRole::Owner)), (personal.owned, personal.role));
+        let open = repo.query_membership(&namespace, None).await.unwrap();
+        assert_eq!((false, None), (open.owned, open.role));
+
+        assert!(repo.insert_first_owner(&namespace, alice.id, true).await.unwrap());
+        assert!(repo.insert_first_owner(&namespace, alice.id, true).await.unwrap());
+        assert!(!repo.insert_first_owner(&namespace, bob.id, true).await.unwrap());
+
+        let token_hash = format!("{:e>64}", bob.id);
+        repo.insert_invitation(&token_hash, &namespace, NamespaceRole::Viewer, alice.id, Utc::now() + Duration::days(1)).await.unwrap();
+        assert_eq!(namespace, repo.accept_invitation(&token_hash, bob.id).await.unwrap());
+        let used = repo.accept_invitation(&token_hash, bob.id).await.unwrap_err();
+        assert!(matches!(used.downcast_ref::<AuthError>(), Some(AuthError::InvitationNotFound)));
+
+        let members = repo.query_members(&namespace).await.unwrap();
+        assert_eq!(
+            vec![(alice.id, NamespaceRole::Owner), (bob.id, NamespaceRole::Viewer)],
+            members.iter().map(|it| (it.user_id, it.role)).collect::<Vec<_>>(),
+        );
+        assert_eq!(vec![alice.namespace(), namespace.clone()], repo.query_namespaces(alice.id).await.unwrap());
+        assert_eq!(vec![bob.namespace(), namespace.clone()], repo.query_namespaces(bob.id).await.unwrap());
+
+        let last = repo.update_role(&namespace, alice.id, NamespaceRole::Editor).await.unwrap_err();
+        assert!(matches!(last.downcast_ref::<AuthError>(), Some(AuthError::LastOwner(_))));
+        let last = repo.delete_member(&namespace, alice.id).await.unwrap_err();
+        assert!(matches!(last.downcast_ref::<AuthError>(), Some(AuthError::LastOwner(_))));
+
+        assert_eq!(NamespaceRole::Owner, repo.update_role(&namespace, bob.id, NamespaceRole::Owner).await.unwrap().role);
+        assert_eq!(NamespaceRole::Editor, repo.update_role(&namespace, alice.id, NamespaceRole::Editor).await.unwrap().role);
+        repo.delete_member(&namespace, alice.id).await.unwrap();
+
+        let missing = repo.delete_member(&namespace, alice.id).await.unwrap_err();
+        assert!(matches!(missing.downcast_ref::<AuthError>(), Some(AuthError::MemberNotFound(_))));
+    }
+
+    #[actix_web::test]
+    async fn insert_first_owner_not_empty() {
+        let pool = db::must_init(&config::must_get().db);
+        let (repo, todos) = (AuthRepository::new(pool.clone()), TodoRepository::new(pool));
+        let micros = Utc::now().timestamp_micros();
+        let alice = repo.insert_user(&format!("repo-na{}", micros), "hash").await.unwrap();
+        let namespace = format!("repo-used-{}", micros);
+        let todo = todos.insert_todo(Todo::create(&namespace, "of an anonymous user")).await.unwrap();
+
+        let used = repo.insert_first_owner(&namespace, alice.id, true).await.unwrap_err();
+        assert!(matches!(used.downcast_ref::<AuthError>(), Some(AuthError::NamespaceNotEmpty(_))));
+        assert_eq!(None, repo.query_membership(&namespace, Some(alice.id)).await.unwrap().role);
+
+        // `[auth] claim_used`
+        assert!(repo.insert_first_owner(&namespace, alice.id, false).await.unwrap());
+
+        todos.clear_todos(namespace, vec![todo.id.1]).await.unwrap();
+    }
+
+    #[actix_web::test]
+    async fn api_keys() {
+        let repo = repo();
+        let micros = Utc::now().timestamp_micros();
+        let alice = repo.insert_user(&format!("repo-ka{}", micros), "hash").await.unwrap();
+        let bob = repo.insert_user(&format!("repo-kb{}", micros), "hash").await.unwrap();
+        let namespace = format!("repo-keys-{}", micros);
+        repo.insert_first_owner(&namespace, alice.id, true).await.unwrap();
+        repo.insert_invitation(&format!("{:k>64}", micros), &namespace, NamespaceRole::Viewer, alice.id, Utc::now() + Duration::days(1)).await.unwrap();
+        repo.accept_invitation(&format!("{:k>64}", micros), bob.id).await.unwrap();
+
+        let (alice_hash, bob_hash) = (format!("{:a>64}", micros), format!("{:b>64}", micros));
+        let alice_key = repo.insert_api_key(&alice_hash, &namespace, "ci", NamespaceRole::Editor, alice.id).await.unwrap();
+        let bob_key = repo.insert_api_key(&bob_hash, &namespace, "script", NamespaceRole::Editor, bob.id).await.unwrap();
+        assert_eq!(None, alice_key.last_used_at);
+        assert_eq!(
+            vec![bob_key.id, alice_key.id],
+            repo.query_api_keys(&namespace).await.unwrap().iter().map(|it| it.id).collect::<Vec<_>>(),
+        );
+
+        let used = repo.use_api_key(&alice_hash).await.unwrap().unwrap();
+        assert_eq!((alice_key.id, NamespaceRole::Editor), (used.id, used.role));
+        assert!(used.last_used_at.is_some());
+        // no more than the viewer who created it
+        assert_eq!(NamespaceRole::Viewer, repo.use_api_key(&bob_hash).await.unwrap().unwrap().role);
+        assert_eq!(None, repo.use_api_key(&format!("{:c>64}", micros)).await.unwrap());
+
+        repo.delete_member(&namespace, bob.id).await.unwrap();
+        assert_eq!(None, repo.use_api_key(&bob_hash).await.unwrap());
+
+        repo.delete_api_key(&namespace, alice_key.id).await.unwrap();
+        assert_eq!(None, repo.use_api_key(&alice_hash).await.unwrap());
+        let missing = repo.delete_api_key(&namespace, alice_key.id).await.unwrap_err();
+        assert!(matches!(missing.downcast_ref::<AuthError>(), Some(AuthError::ApiKeyNotFound(_))));
+    }
+}
@@ -1,3 +1,9 @@
 pub mod todo_domain;
+pub mod tag_domain;
+pub mod purge_job;
+pub mod todo_events;
+pub mod auth_domain;
 
 mod todo_repository;
+mod tag_repository;
+mod auth_repository;
@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::domains::todo_repository::TodoRepository;
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct PurgeConfig {
+    pub enabled: bool,
+    /// how long a `Deleted` todo is kept, counted from its last update
+    pub retention_days: i64,
+    /// how long the id of a hard-deleted todo is kept for `GET /todos/changes`, never shorter than `retention_days`
+    pub tombstone_retention_days: i64,
+    pub interval_secs: u64,
+}
+
+impl Default for PurgeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            retention_days: 30,
+            tombstone_retention_days: 90,
+            interval_secs: 3600,
+        }
+    }
+}
+
+/// purged rows per namespace since the server started
+#[derive(Default)]
+pub struct PurgeMetrics {
+    purged: Mutex<BTreeMap<String, u64>>,
+    last_run: Mutex<Option<DateTime<Utc>>>,
+}
+
+impl PurgeMetrics {
+    pub(crate) fn record(&self, purged: &[(String, u64)]) {
+        let mut total = self.purged.lock().unwrap();
+        for (namespace, rows) in purged {
+            *total.entry(namespace.clone()).or_default() += rows;
+        }
+        *self.last_run.lock().unwrap() = Some(Utc::now());
+    }
+
+    pub fn purged(&self) -> BTreeMap<String, u64> {
+        self.purged.lock().unwrap().clone()
+    }
+
+    pub fn last_run(&self) -> Option<DateTime<Utc>> {
+        *self.last_run.lock().unwrap()
+    }
+}
+
+/// hard-deletes the todos which have been `Deleted` longer than the retention, and the tombstones older than theirs
+pub struct PurgeJob {
+    repo: TodoRepository,
+    config: PurgeConfig,
+    metrics: Arc<PurgeMetrics>,
+}
+
+impl PurgeJob {
+    pub fn new(pool: deadpool_postgres::Pool, config: PurgeConfig, metrics: Arc<PurgeMetrics>) -> Self {
+        Self { repo: TodoRepository::new(pool), config, metrics }
+    }
+
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.interval_secs.max(1)));
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = self.purge_once().await {
+                log::error!("failed to purge deleted todos: {:?}", err);
+            }
+        }
+    }
+
+    pub async fn purge_once(&self) -> Result<Vec<(String, u64)>> {
+        let now = Utc::now();
+        let before = now - chrono::Duration::days(self.config.retention_days);
+
+        let purged = self.repo.purge_deleted(before).await?;
+        for (namespace, rows) in &purged {
+            log::info!("purged {} deleted todos of namespace [{}]", rows, namespace);
+        }
+        self.metrics.record(&purged);
+
+        // the tombstones written by this run outlive it, the offline clients catch up on them
+        let tombstone_days = self.config.tombstone_retention_days.max(self.config.retention_days);
+        let tombstones = self.repo.purge_tombstones(now - chrono::Duration::days(tombstone_days)).await?;
+        if tombstones > 0 {
+            log::info!("purged {} tombstones", tombstones);
+        }
+
+        Ok(purged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::{Duration, Utc};
+    use common::model::TodoStatus;
+
+    use crate::domains::todo_domain::Todo;
+    use crate::domains::todo_repository::TodoRepository;
+    use crate::infra::{config, db};
+    use super::{PurgeConfig, PurgeJob, PurgeMetrics};
+
+    #[test]
+    fn record_metrics() {
+        let metrics = PurgeMetrics::default();
+        assert!(metrics.last_run().is_none());
+
+        metrics.record(&[("a".to_string(), 2), ("b".to_string(), 1)]);
+        metrics.record(&[("a".to_string(), 3)]);
+
+        assert_eq!(Some(&5), metrics.purged().get("a"));
+        assert_eq!(Some(&1), metrics.purged().get("b"));
+        assert!(metrics.last_run().is_some());
+    }
+
+    #[actix_web::test]
+    async fn purge_once() {
+        let pool = db::must_init(&config::must_get().db);
+        let repo = TodoRepository::new(pool.clone());
+        let ns = "testing/purge_job";
+
+        let mut expired = Todo::create(ns, "deleted long ago");
+        expired.status = TodoStatus::Deleted;
+        let expired = repo.insert_todo(expired).await.unwrap();
+        repo.touch(expired.id.clone(), Utc::now() - Duration::days(31)).await.unwrap();
+
+        let mut recent = Todo::create(ns, "deleted just now");
+        recent.status = TodoStatus::Deleted;
+        let recent = repo.insert_todo(recent).await.unwrap();
+
+        let metrics = Arc::new(PurgeMetrics::default());
+        let job = PurgeJob::new(pool, PurgeConfig::default(), metrics.clone());
+        let purged = job.purge_once().await.unwrap();
+
+        assert!(purged.contains(&(ns.to_string(), 1)));
+        assert_eq!(Some(&1), metrics.purged().get(ns));
+        assert!(repo.query_by_id(expired.id.clone()).await.is_err());
+        assert!(repo.query_by_id(recent.id.clone()).await.is_ok());
+
+        let changes = repo.query_changes(ns.to_string(), Some(Utc::now() - Duration::hours(1))).await.unwrap();
+        assert!(changes.deleted.contains(&expired.id.1));
+
+        repo.clear_todos(ns.to_string(), vec![recent.id.1]).await.unwrap();
+    }
+}
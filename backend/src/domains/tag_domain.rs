@@ -0,0 +1,130 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+use crate::domains::tag_repository::TagRepository;
+use crate::infra::db::RecordNotFound;
+
+#[derive(Debug, Clone)]
+pub struct Tag {
+    pub id: TagID,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+pub type TagID = (String, i32);
+
+pub const MAX_TAG_NAME_LEN: usize = 64;
+
+#[derive(Error, Debug)]
+pub enum TagError {
+    #[error("Tag [id: {0}] not found.")]
+    TagNotFound(i32),
+    #[error("Tag [name: {0}] already exists.")]
+    TagAlreadyExists(String),
+    #[error("invalid tag name [{0}], expect 1 to 64 characters without whitespace or comma")]
+    InvalidTagName(String),
+}
+
+/// trimmed name of 1 to `MAX_TAG_NAME_LEN` characters, without whitespace or comma
+pub fn normalize_tag_name(name: &str) -> Result<String, TagError> {
+    let trimmed = name.trim();
+    let len = trimmed.chars().count();
+
+    if len == 0 || len > MAX_TAG_NAME_LEN || trimmed.contains(|c: char| c.is_whitespace() || c == ',') {
+        return Err(TagError::InvalidTagName(name.to_string()));
+    }
+
+    Ok(trimmed.to_string())
+}
+
+/// normalized names, sorted without duplicates
+pub fn normalize_tags(names: Vec<String>) -> Result<Vec<String>, TagError> {
+    let mut tags = names.iter()
+        .map(|it| normalize_tag_name(it))
+        .collect::<Result<Vec<String>, TagError>>()?;
+    tags.sort();
+    tags.dedup();
+
+    Ok(tags)
+}
+
+#[async_trait]
+pub trait TagDomainTrait {
+    async fn list_tags(&self, namespace: String) -> Result<Vec<Tag>>;
+    async fn create_tag(&self, namespace: String, name: &str) -> Result<Tag>;
+    async fn rename_tag(&self, id: TagID, name: &str) -> Result<Tag>;
+    async fn delete_tag(&self, id: TagID) -> Result<()>;
+}
+
+#[derive(Clone)]
+pub struct TagDomain {
+    repo: TagRepository,
+}
+
+impl TagDomain {
+    pub fn new(pool: deadpool_postgres::Pool) -> Self {
+        Self { repo: TagRepository::new(pool) }
+    }
+}
+
+#[async_trait]
+impl TagDomainTrait for TagDomain {
+    async fn list_tags(&self, namespace: String) -> Result<Vec<Tag>> {
+        self.repo.query_tags(namespace).await
+    }
+
+    async fn create_tag(&self, namespace: String, name: &str) -> Result<Tag> {
+        let name = normalize_tag_name(name)?;
+
+        self.repo.insert_tag(namespace, name).await
+    }
+
+    async fn rename_tag(&self, id: TagID, name: &str) -> Result<Tag> {
+        let name = normalize_tag_name(name)?;
+        let tag_id = id.1;
+
+        self.repo.rename_tag(id, name).await.map_err(|err| {
+            if err.is::<RecordNotFound>() {
+                TagError::TagNotFound(tag_id).into()
+            } else {
+                err
+            }
+        })
+    }
+
+    async fn delete_tag(&self, id: TagID) -> Result<()> {
+        let tag_id = id.1;
+
+        if !self.repo.delete_tag(id).await? {
+            return Err(TagError::TagNotFound(tag_id).into());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_tag_name, normalize_tags, TagError};
+
+    #[test]
+    fn tag_name() {
+        assert_eq!("release-1.2", normalize_tag_name(" release-1.2 ").unwrap());
+        assert_eq!("后端", normalize_tag_name("后端").unwrap());
+
+        for invalid in ["", "  ", "two words", "a,b", &"x".repeat(65)] {
+            let err = normalize_tag_name(invalid).unwrap_err();
+            assert!(matches!(err, TagError::InvalidTagName(_)), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn tags() {
+        let tags = normalize_tags(vec!["b".to_string(), " a".to_string(), "b ".to_string()]).unwrap();
+        assert_eq!(vec!["a", "b"], tags);
+
+        assert!(normalize_tags(vec!["a".to_string(), "".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+use std::time::SystemTime;
+
+use anyhow::Result;
+use chrono::DateTime;
+use deadpool_postgres::Pool;
+use tokio_pg_mapper::FromTokioPostgresRow;
+use tokio_pg_mapper_derive::PostgresMapper;
+use tokio_postgres::error::SqlState;
+
+use crate::domains::tag_domain::{Tag, TagError, TagID};
+use crate::infra::db::RecordNotFound;
+
+#[derive(PostgresMapper, Debug)]
+#[pg_mapper(table = "tags")]
+struct TagEntity {
+    namespace: String,
+    id: i32,
+    name: String,
+    created_at: SystemTime,
+}
+
+#[derive(Clone)]
+pub struct TagRepository {
+    db: Pool,
+}
+
+impl TagRepository {
+    pub fn new(db: Pool) -> Self {
+        Self { db }
+    }
+
+    pub async fn query_tags(&self, namespace: String) -> Result<Vec<Tag>> {
+        let client = self.db.get().await?;
+
+        let statement = client
+            .prepare_cached("SELECT * FROM tags WHERE namespace = $1 ORDER BY name").await?;
+
+        let rows = client.query(&statement, &[&namespace]).await?;
+
+        let tags = rows
+            .into_iter()
+            .map(|r| TagEntity::from_row(r).map(Tag::from))
+            .collect::<Result<Vec<Tag>, _>>()?;
+
+        Ok(tags)
+    }
+
+    pub async fn insert_tag(&self, namespace: String, name: String) -> Result<Tag> {
+        let client = self.db.get().await?;
+
+        let statement = client.prepare_cached(r#"
+            INSERT INTO tags (namespace, name, created_at)
+            VALUES ($1, $2, $3)
+            RETURNING *
+        "#).await?;
+
+        let row = client.query_one(&statement, &[&namespace, &name, &SystemTime::now()]).await
+            .map_err(|err| already_exists(err, &name))?;
+
+        Ok(TagEntity::from_row(row).map(Tag::from)?)
+    }
+
+    pub async fn rename_tag(&self, (namespace, id): TagID, name: String) -> Result<Tag> {
+        let client = self.db.get().await?;
+
+        let statement = client.prepare_cached(r#"
+            UPDATE tags SET name = $3
+            WHERE namespace = $1 AND id = $2
+            RETURNING *
+        "#).await?;
+
+        match client.query_opt(&statement, &[&namespace, &id, &name]).await
+            .map_err(|err| already_exists(err, &name))? {
+            None => Err(RecordNotFound.into()),
+            Some(row) => Ok(TagEntity::from_row(row).map(Tag::from)?),
+        }
+    }
+
+    /// the tag is removed from its todos by the cascading foreign key
+    pub async fn delete_tag(&self, (namespace, id): TagID) -> Result<bool> {
+        let client = self.db.get().await?;
+
+        let statement = client
+            .prepare_cached("DELETE FROM tags WHERE namespace = $1 AND id = $2").await?;
+
+        let rows = client.execute(&statement, &[&namespace, &id]).await?;
+
+        Ok(rows == 1)
+    }
+}
+
+/// the unique (namespace, name) is violated by an existing tag
+fn already_exists(err: tokio_postgres::Error, name: &str) -> anyhow::Error {
+    if err.code() == Some(&SqlState::UNIQUE_VIOLATION) {
+        TagError::TagAlreadyExists(name.to_string()).into()
+    } else {
+        err.into()
+    }
+}
+
+impl From<TagEntity> for Tag {
+    fn from(tag: TagEntity) -> Self {
+        Self {
+            id: (tag.namespace, tag.id),
+            name: tag.name,
+            created_at: DateTime::from(tag.created_at),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domains::tag_domain::TagError;
+    use crate::infra::{config, db};
+    use super::TagRepository;
+
+    fn repo() -> TagRepository {
+        TagRepository::new(db::must_init(&config::must_get().db))
+    }
+
+    #[actix_web::test]
+    async fn crud() {
+        let repo = repo();
+        let ns = "testing/repository/tags";
+
+        let created = repo.insert_tag(ns.to_string(), "backend".to_string()).await.unwrap();
+        assert_eq!("backend", created.name);
+
+        let duplicated = repo.insert_tag(ns.to_string(), "backend".to_string()).await.unwrap_err();
+        assert!(matches!(duplicated.downcast_ref::<TagError>(), Some(TagError::TagAlreadyExists(_))));
+
+        let renamed = repo.rename_tag(created.id.clone(), "frontend".to_string()).await.unwrap();
+        assert_eq!("frontend", renamed.name);
+
+        let tags = repo.query_tags(ns.to_string()).await.unwrap();
+        assert_eq!(vec!["frontend"], tags.iter().map(|it| it.name.as_str()).collect::<Vec<&str>>());
+
+        assert!(repo.delete_tag(created.id.clone()).await.unwrap());
+        assert!(!repo.delete_tag(created.id).await.unwrap());
+    }
+}
@@ -1,26 +1,254 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use thiserror::Error;
 
-use common::model::TodoStatus;
+use common::model::{is_overdue, TodoEvent, TodoPriority, TodoSort, TodoStatus};
 
-use crate::domains::todo_repository::TodoRepository;
+use crate::domains::tag_domain::normalize_tags;
+use crate::domains::todo_events::TodoChange;
+use crate::domains::todo_repository::{TodoRepository, TodoTx};
+use crate::infra::db::RecordNotFound;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Todo {
     pub id: TodoID,
     pub content: String,
     pub status: TodoStatus,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub due_at: Option<DateTime<Utc>>,
+    pub priority: TodoPriority,
+    /// manual order in the namespace, ascending, see `position_between`
+    pub position: i64,
+    /// tag names in alphabetical order
+    pub tags: Vec<String>,
+    /// the todo this one is a subtask of, in the same namespace
+    pub parent_id: Option<i32>,
+    /// counts the writes, starts at 1
+    pub version: i32,
+    pub hit: Option<SearchHit>,
+}
+
+/// rank and highlighted content of a full-text search hit
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub rank: f32,
+    pub snippet: String,
 }
 
 pub type TodoID = (String, i32);
 
+#[derive(Default)]
+pub struct CreateTodo {
+    pub content: String,
+    pub due_at: Option<DateTime<Utc>>,
+    pub priority: TodoPriority,
+    pub tags: Vec<String>,
+    /// create as a subtask of an existing todo
+    pub parent_id: Option<i32>,
+}
+
+#[derive(Default)]
 pub struct UpdateTodo {
     pub content: Option<String>,
     pub status: Option<TodoStatus>,
+    /// `Some(None)` clears the due date
+    pub due_at: Option<Option<DateTime<Utc>>>,
+    pub priority: Option<TodoPriority>,
+    /// replaces all the tags
+    pub tags: Option<Vec<String>>,
+    /// marking `Done`, `Archived` or `Deleted` moves the open subtasks along,
+    /// instead of failing with `TodoError::OpenSubtasks`
+    pub cascade: bool,
+    /// the version the changes are based on, `TodoError::VersionConflict` when it is not the current one
+    pub if_match: Option<i32>,
+}
+
+/// place a todo next to the other one in the manual order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MoveTo {
+    Before(i32),
+    After(i32),
+}
+
+impl MoveTo {
+    pub fn target(&self) -> i32 {
+        match self {
+            MoveTo::Before(id) | MoveTo::After(id) => *id,
+        }
+    }
+}
+
+/// gap between the positions of adjacent todos, after an insert or a rebalance
+pub const POSITION_GAP: i64 = 1024;
+
+/// a position strictly between `lower` and `upper`, `None` when there is no room left
+/// and the positions of the namespace need a rebalance
+pub fn position_between(lower: Option<i64>, upper: Option<i64>) -> Option<i64> {
+    match (lower, upper) {
+        (Some(lower), Some(upper)) if upper - lower > 1 => Some(lower + (upper - lower) / 2),
+        (Some(_), Some(_)) => None,
+        (Some(lower), None) => lower.checked_add(POSITION_GAP),
+        (None, Some(upper)) => upper.checked_sub(POSITION_GAP),
+        (None, None) => Some(0),
+    }
+}
+
+/// one operation of `TodoDomainTrait::batch`, a status change is an `Update` of the status
+pub enum BatchOp {
+    Create(CreateTodo),
+    Update(i32, UpdateTodo),
+    /// hard-delete, the subtasks are left as top-level todos, like `TodoDomainTrait::clear_todos`
+    Delete(i32),
+}
+
+pub const MAX_BATCH_SIZE: usize = 100;
+
+/// a todo carried over from another source, keeping its status and `created_at`
+#[derive(Debug, Clone)]
+pub struct ImportTodo {
+    /// the id in the source, the `parent_id` of its subtasks
+    pub source_id: i32,
+    pub content: String,
+    pub status: TodoStatus,
+    pub created_at: DateTime<Utc>,
+    pub due_at: Option<DateTime<Utc>>,
+    pub priority: TodoPriority,
+    pub tags: Vec<String>,
+    /// the source id of the parent, imported along
+    pub parent_id: Option<i32>,
+}
+
+pub enum Imported {
+    Created(Todo),
+    /// the namespace already has a todo with the same content and `created_at`, which is left as is
+    Duplicate(Todo),
+}
+
+impl Imported {
+    pub fn todo(&self) -> &Todo {
+        match self {
+            Imported::Created(todo) | Imported::Duplicate(todo) => todo,
+        }
+    }
+}
+
+pub const MAX_IMPORT_SIZE: usize = 1000;
+
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+pub const MAX_PAGE_SIZE: i64 = 200;
+
+/// keyset pagination on id, newest first, or by rank when searching by `q`
+#[derive(Default)]
+pub struct ListTodo {
+    pub status: Option<TodoStatus>,
+    pub cursor: Option<Cursor>,
+    pub limit: i64,
+    pub q: Option<String>,
+    pub due_before: Option<DateTime<Utc>>,
+    pub overdue: Option<bool>,
+    pub sort: TodoSort,
+    pub tag: Option<String>,
+}
+
+impl ListTodo {
+    /// the limit falls back to `DEFAULT_PAGE_SIZE` and is capped by `MAX_PAGE_SIZE`
+    pub fn page_size(limit: Option<i64>) -> i64 {
+        limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+    }
+
+    /// the trimmed `q` unless blank
+    pub fn search(&self) -> Option<&str> {
+        self.q.as_deref().map(str::trim).filter(|q| !q.is_empty())
+    }
+}
+
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<Cursor>,
+}
+
+/// where a page ends, the sort key of its last todo along with the id, so that the next page
+/// does not depend on that todo still being there.
+/// `{id}` newest first, `{priority}:{id}` by priority, `{position}:{id}` in the manual order, `{rank}:{id}` searching
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cursor {
+    Id(i32),
+    Priority(i16, i32),
+    Position(i64, i32),
+    Rank(f32, i32),
+}
+
+impl Cursor {
+    /// the cursor after the todo, in the order of the `sort` unless it is a search hit
+    pub fn after(todo: &Todo, sort: TodoSort) -> Self {
+        match (&todo.hit, sort) {
+            (Some(hit), _) => Cursor::Rank(hit.rank, todo.id.1),
+            (None, TodoSort::Newest) => Cursor::Id(todo.id.1),
+            (None, TodoSort::Priority) => Cursor::Priority(todo.priority as i16, todo.id.1),
+            (None, TodoSort::Manual) => Cursor::Position(todo.position, todo.id.1),
+        }
+    }
+
+    /// `TodoError::InvalidCursor` unless `to_string` of a cursor in the same order gives it
+    pub fn parse(cursor: &str, sort: TodoSort, searching: bool) -> Result<Self, TodoError> {
+        let invalid = || TodoError::InvalidCursor(cursor.to_string());
+        let key_and_id = || cursor.split_once(':').ok_or_else(invalid);
+
+        let parsed = match sort {
+            _ if searching => key_and_id()
+                .map(|(key, id)| Some(Cursor::Rank(key.parse().ok()?, id.parse().ok()?)))?,
+            TodoSort::Newest => cursor.parse().map(Cursor::Id).ok(),
+            TodoSort::Priority => key_and_id()
+                .map(|(key, id)| Some(Cursor::Priority(key.parse().ok()?, id.parse().ok()?)))?,
+            TodoSort::Manual => key_and_id()
+                .map(|(key, id)| Some(Cursor::Position(key.parse().ok()?, id.parse().ok()?)))?,
+        };
+        parsed.ok_or_else(invalid)
+    }
+
+    pub fn id(&self) -> i32 {
+        match self {
+            Cursor::Id(id) | Cursor::Priority(_, id) | Cursor::Position(_, id) | Cursor::Rank(_, id) => *id,
+        }
+    }
+}
+
+impl Display for Cursor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Cursor::Id(id) => write!(f, "{}", id),
+            Cursor::Priority(priority, id) => write!(f, "{}:{}", priority, id),
+            Cursor::Position(position, id) => write!(f, "{}:{}", position, id),
+            Cursor::Rank(rank, id) => write!(f, "{}:{}", rank, id),
+        }
+    }
+}
+
+/// the todos written and the ids hard-deleted in a namespace since a point in time
+pub struct Changes {
+    pub todos: Vec<Todo>,
+    pub deleted: Vec<i32>,
+    /// the `since` of the next changes
+    pub until: DateTime<Utc>,
+    /// `todos` are all of them and `deleted` is empty, without `since` or when its tombstones are purged
+    pub full: bool,
+}
+
+impl Page<Todo> {
+    /// `rows` are queried with `limit + 1`, the extra row tells there is a next page
+    pub fn of(mut rows: Vec<Todo>, limit: i64, sort: TodoSort) -> Self {
+        let has_next = rows.len() as i64 > limit;
+        rows.truncate(limit as usize);
+
+        let next_cursor = if has_next { rows.last().map(|it| Cursor::after(it, sort)) } else { None };
+
+        Self { items: rows, next_cursor }
+    }
 }
 
 impl Todo {
@@ -31,6 +259,51 @@ impl Todo {
             ..Default::default()
         }
     }
+
+    /// apply the changes, status must follow the `TodoStatusMachine`,
+    /// tags are expected to be normalized by `normalize_tags`
+    pub fn apply(&mut self, to_update: UpdateTodo) -> Result<(), TodoError> {
+        if let Some(status) = to_update.status {
+            self.status = self.status.transit_to(status)?;
+        }
+        if let Some(content) = to_update.content {
+            self.content = content;
+        }
+        if let Some(due_at) = to_update.due_at {
+            self.due_at = due_at;
+        }
+        if let Some(priority) = to_update.priority {
+            self.priority = priority;
+        }
+        if let Some(tags) = to_update.tags {
+            self.tags = tags;
+        }
+
+        Ok(())
+    }
+
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        is_overdue(self.status, self.due_at, now)
+    }
+
+    /// the status the update moves the todo to when it leaves it for `Done`, `Archived` or `Deleted`,
+    /// its open subtasks need to be closed
+    pub fn closed_by(&self, to_update: &UpdateTodo) -> Option<TodoStatus> {
+        to_update.status.filter(|&status| status != self.status && status != TodoStatus::Todo)
+    }
+
+    /// the update moves a closed todo back to `Todo`, its parent needs to be open
+    pub fn reopened_by(&self, to_update: &UpdateTodo) -> bool {
+        self.status != TodoStatus::Todo && to_update.status == Some(TodoStatus::Todo)
+    }
+
+    /// a subtask is open only under an open parent, like a parent is closed only once its subtasks are
+    pub fn check_open(&self) -> Result<(), TodoError> {
+        match self.status {
+            TodoStatus::Todo => Ok(()),
+            status => Err(TodoError::ClosedParent(self.id.1, status)),
+        }
+    }
 }
 
 impl Default for Todo {
@@ -41,6 +314,13 @@ impl Default for Todo {
             status: TodoStatus::Todo,
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            due_at: None,
+            priority: TodoPriority::Normal,
+            position: 0,
+            tags: vec![],
+            parent_id: None,
+            version: 0,
+            hit: None,
         }
     }
 }
@@ -48,42 +328,68 @@ impl Default for Todo {
 #[async_trait]
 pub trait TodoDomainTrait {
     async fn get_todo_by_id(&self, id: TodoID) -> Result<Todo>;
-    async fn list_todo(&self, namespace: String, status: Option<TodoStatus>) -> Result<Vec<Todo>>;
-    async fn create_todo(&self, namespace: String, content: &str) -> Result<Todo>;
+    async fn list_todo(&self, namespace: String, query: ListTodo) -> Result<Page<Todo>>;
+    async fn create_todo(&self, namespace: String, to_create: CreateTodo) -> Result<Todo>;
     async fn update_todo(&self, id: TodoID, to_update: UpdateTodo) -> Result<Todo>;
+    /// `if_match` is the version to move only, like `UpdateTodo::if_match`
+    async fn move_todo(&self, id: TodoID, to: MoveTo, if_match: Option<i32>) -> Result<Todo>;
+    async fn list_children(&self, id: TodoID) -> Result<Vec<Todo>>;
     async fn clear_todos(&self, namespace: String, ids: Vec<i32>) -> Result<()>;
+    /// apply the operations in order in one transaction, a failed one is rolled back alone
+    async fn batch(&self, namespace: String, ops: Vec<BatchOp>) -> Result<Vec<Result<Todo>>>;
+    /// all the todos without `since`, with no deleted ids then
+    async fn list_changes(&self, namespace: String, since: Option<DateTime<Utc>>) -> Result<Changes>;
+    /// the results in the order of `todos`, in one transaction, a failed one is rolled back alone
+    async fn import_todos(&self, namespace: String, todos: Vec<ImportTodo>) -> Result<Vec<Result<Imported>>>;
 }
 
 #[async_trait]
 impl TodoDomainTrait for TodoDomain {
     async fn get_todo_by_id(&self, id: TodoID) -> Result<Todo> {
-        self.repo.query_by_id(id).await
+        self.find(id).await
     }
 
-    async fn list_todo(&self, namespace: String, status: Option<TodoStatus>) -> Result<Vec<Todo>> {
-        self.repo.query_todos(namespace, status).await
+    async fn list_todo(&self, namespace: String, query: ListTodo) -> Result<Page<Todo>> {
+        TodoDomain::list_todo(self, namespace, query).await
     }
 
-    async fn create_todo(&self, namespace: String, content: &str) -> Result<Todo> {
-        self.repo.insert_todo(Todo::create(&namespace, &content)).await
+    async fn create_todo(&self, namespace: String, to_create: CreateTodo) -> Result<Todo> {
+        TodoDomain::create_todo(self, &namespace, to_create).await
     }
 
     async fn update_todo(&self, id: TodoID, to_update: UpdateTodo) -> Result<Todo> {
-        let mut found = self.repo.query_by_id(id).await?;
+        TodoDomain::update_todo(self, id, to_update).await
+    }
+
+    async fn move_todo(&self, id: TodoID, to: MoveTo, if_match: Option<i32>) -> Result<Todo> {
+        TodoDomain::move_todo(self, id, to, if_match).await
+    }
 
-        found.content = to_update.content.unwrap_or_else(|| found.content);
-        let to_status = to_update.status.unwrap_or_else(|| found.status);
-        // TODO check status
-        found.status = to_status;
+    async fn list_children(&self, id: TodoID) -> Result<Vec<Todo>> {
+        let parent = self.find(id).await?;
 
-        self.repo.update_todo(found).await
+        self.repo.query_children(parent.id).await
     }
 
     async fn clear_todos(&self, namespace: String, ids: Vec<i32>) -> Result<()> {
-        self.repo.clear_todos(namespace, ids).await?;
+        let cleared = self.repo.clear_todos(namespace.clone(), ids).await?;
+
+        self.notify(&namespace, cleared.into_iter().map(|id| TodoEvent::Deleted { id })).await;
 
         Ok(())
     }
+
+    async fn batch(&self, namespace: String, ops: Vec<BatchOp>) -> Result<Vec<Result<Todo>>> {
+        TodoDomain::batch(self, &namespace, ops).await
+    }
+
+    async fn list_changes(&self, namespace: String, since: Option<DateTime<Utc>>) -> Result<Changes> {
+        self.repo.query_changes(namespace, since).await
+    }
+
+    async fn import_todos(&self, namespace: String, todos: Vec<ImportTodo>) -> Result<Vec<Result<Imported>>> {
+        TodoDomain::import_todos(self, &namespace, todos).await
+    }
 }
 
 #[derive(Clone)]
@@ -96,77 +402,784 @@ impl TodoDomain {
         Self { repo: TodoRepository::new(pool) }
     }
 
-    pub async fn list_todo(&self, namespace: String, status: Option<TodoStatus>) -> Result<Vec<Todo>> {
-        self.repo.query_todos(namespace, status).await
+    /// query by id, `RecordNotFound` is reported as `TodoError::TodoNotFound`
+    async fn find(&self, id: TodoID) -> Result<Todo> {
+        let todo_id = id.1;
+        self.repo.query_by_id(id).await.map_err(|err| {
+            if err.is::<RecordNotFound>() {
+                TodoError::TodoNotFound(todo_id).into()
+            } else {
+                err
+            }
+        })
+    }
+
+    pub async fn list_todo(&self, namespace: String, query: ListTodo) -> Result<Page<Todo>> {
+        let limit = query.limit;
+        let query = ListTodo { limit: limit + 1, ..query };
+        let rows = match query.search() {
+            Some(q) => self.repo.search_todos(namespace, q, &query).await?,
+            None => self.repo.query_todos(namespace, &query).await?,
+        };
+
+        Ok(Page::of(rows, limit, query.sort))
     }
 
-    pub async fn create_todo(&self, namespace: &str, content: &str) -> Result<Todo> {
-        self.repo.insert_todo(Todo::create(namespace, content)).await
+    pub async fn create_todo(&self, namespace: &str, to_create: CreateTodo) -> Result<Todo> {
+        let mut conn = self.repo.connect().await?;
+        let tx = TodoTx::begin(&mut conn).await?;
+
+        let todo = create_in(&tx, namespace, to_create).await?;
+
+        tx.commit().await?;
+
+        Ok(todo)
     }
 
     pub async fn update_todo(&self, id: TodoID, to_update: UpdateTodo) -> Result<Todo> {
-        let mut found = self.repo.query_by_id(id).await?;
+        let mut conn = self.repo.connect().await?;
+        let tx = TodoTx::begin(&mut conn).await?;
 
-        found.content = to_update.content.unwrap_or_else(|| found.content);
-        let to_status = to_update.status.unwrap_or_else(|| found.status);
-        // TODO check status
-        found.status = to_status;
+        let todo = update_in(&tx, id, to_update).await?;
 
-        self.repo.update_todo(found).await
+        tx.commit().await?;
+
+        Ok(todo)
     }
 
-    pub async fn toggle_todo(&self, todo: Todo) -> Result<Todo> {
-        let mut todo = self.repo.query_by_id(todo.id).await?;
+    pub async fn batch(&self, namespace: &str, ops: Vec<BatchOp>) -> Result<Vec<Result<Todo>>> {
+        let mut conn = self.repo.connect().await?;
+        let mut tx = TodoTx::begin(&mut conn).await?;
 
-        let new_status = match todo.status {
-            TodoStatus::Todo => TodoStatus::Done,
-            TodoStatus::Done => TodoStatus::Todo,
-            current => return Err(TodoError::InvalidStatusTransition(current, TodoStatus::Todo).into()),
-        };
+        let mut results = Vec::with_capacity(ops.len());
+        for op in ops {
+            let savepoint = tx.savepoint().await?;
+            let result = match op {
+                BatchOp::Create(to_create) => create_in(&savepoint, namespace, to_create).await,
+                BatchOp::Update(id, to_update) => update_in(&savepoint, (namespace.to_string(), id), to_update).await,
+                BatchOp::Delete(id) => delete_in(&savepoint, (namespace.to_string(), id)).await,
+            };
+            match result {
+                Ok(_) => savepoint.commit().await?,
+                Err(_) => savepoint.rollback().await?,
+            }
+            results.push(result);
+        }
 
-        todo.status = new_status;
+        tx.commit().await?;
 
-        self.repo.update_todo(todo).await
+        Ok(results)
+    }
+
+    pub async fn import_todos(&self, namespace: &str, todos: Vec<ImportTodo>) -> Result<Vec<Result<Imported>>> {
+        let mut conn = self.repo.connect().await?;
+        let mut tx = TodoTx::begin(&mut conn).await?;
+
+        let mut ids = HashMap::new();
+        let mut results = todos.iter().map(|_| None).collect::<Vec<Option<Result<Imported>>>>();
+        for index in import_order(&todos) {
+            let to_import = todos[index].clone();
+            let source_id = to_import.source_id;
+            let savepoint = tx.savepoint().await?;
+            let result = import_in(&savepoint, namespace, to_import, &ids).await;
+            match &result {
+                Ok(imported) => {
+                    ids.insert(source_id, imported.todo().id.1);
+                    savepoint.commit().await?
+                }
+                Err(_) => savepoint.rollback().await?,
+            }
+            results[index] = Some(result);
+        }
+
+        tx.commit().await?;
+
+        Ok(results.into_iter().map(|it| it.expect("a result per todo")).collect())
+    }
+
+    pub async fn move_todo(&self, id: TodoID, to: MoveTo, if_match: Option<i32>) -> Result<Todo> {
+        let found = self.find(id).await?;
+        let target = self.find((found.id.0.clone(), to.target())).await?;
+
+        if found.id == target.id {
+            if if_match.is_some_and(|version| version != found.version) {
+                return Err(TodoError::VersionConflict(found.id.1).into());
+            }
+            return Ok(found);
+        }
+
+        let todo = self.repo.move_todo(found.id, to, if_match).await?;
+
+        self.notify(&todo.id.0, [TodoEvent::Updated { id: todo.id.1 }]).await;
+
+        Ok(todo)
     }
-    pub async fn archive_todo(&self, todo: Todo) -> Result<Todo> {
-        let mut found = self.repo.query_by_id(todo.id).await?;
+
+    pub async fn toggle_todo(&self, todo: Todo) -> Result<Todo> {
+        let found = self.find(todo.id).await?;
 
         let new_status = match found.status {
-            TodoStatus::Done => TodoStatus::Archived,
-            current => return Err(TodoError::InvalidStatusTransition(current, TodoStatus::Archived).into()),
+            TodoStatus::Done => TodoStatus::Todo,
+            _ => TodoStatus::Done,
         };
 
-        found.status = new_status;
+        self.update_todo(found.id, UpdateTodo {
+            status: Some(new_status),
+            if_match: Some(found.version),
+            ..Default::default()
+        }).await
+    }
 
-        self.repo.update_todo(found).await
+    pub async fn archive_todo(&self, todo: Todo) -> Result<Todo> {
+        self.update_todo(todo.id, UpdateTodo { status: Some(TodoStatus::Archived), ..Default::default() }).await
     }
+
     pub async fn delete_todo(&self, todo: Todo) -> Result<Todo> {
-        let mut found = self.repo.query_by_id(todo.id).await?;
+        self.update_todo(todo.id, UpdateTodo { status: Some(TodoStatus::Deleted), ..Default::default() }).await
+    }
 
-        let new_status = match found.status {
-            TodoStatus::Archived => TodoStatus::Deleted,
-            current => return Err(TodoError::InvalidStatusTransition(current, TodoStatus::Deleted).into()),
-        };
+    /// `NOTIFY` the changes committed outside of a `TodoTx`, a failure is logged only as the write is done
+    async fn notify(&self, namespace: &str, events: impl IntoIterator<Item=TodoEvent>) {
+        let changes = events.into_iter()
+            .map(|event| TodoChange::new(namespace, event))
+            .collect::<Vec<TodoChange>>();
+
+        if let Err(err) = self.repo.notify(&changes).await {
+            log::error!("failed to notify {:?}: {:?}", changes, err);
+        }
+    }
+}
 
-        found.status = new_status;
+/// `NOTIFY` the change along with the transaction
+async fn notify_in(tx: &TodoTx<'_>, (namespace, _): &TodoID, event: TodoEvent) -> Result<()> {
+    tx.notify(&TodoChange::new(namespace, event)).await
+}
+
+/// `TodoTx::query_by_id`, `RecordNotFound` is reported as `TodoError::TodoNotFound`
+async fn find_in(tx: &TodoTx<'_>, id: &TodoID) -> Result<Todo> {
+    tx.query_by_id(id).await.map_err(|err| {
+        if err.is::<RecordNotFound>() {
+            TodoError::TodoNotFound(id.1).into()
+        } else {
+            err
+        }
+    })
+}
+
+async fn create_in(tx: &TodoTx<'_>, namespace: &str, to_create: CreateTodo) -> Result<Todo> {
+    let mut todo = Todo::create(namespace, &to_create.content);
+    todo.due_at = to_create.due_at;
+    todo.priority = to_create.priority;
+    todo.tags = normalize_tags(to_create.tags)?;
 
-        self.repo.update_todo(found).await
+    if let Some(parent_id) = to_create.parent_id {
+        let parent = find_in(tx, &(namespace.to_string(), parent_id)).await.map_err(|err| {
+            match err.downcast_ref::<TodoError>() {
+                Some(TodoError::TodoNotFound(_)) => TodoError::ParentNotFound(parent_id).into(),
+                _ => err,
+            }
+        })?;
+        parent.check_open()?;
+        todo.parent_id = Some(parent.id.1);
     }
+
+    let todo = tx.insert_todo(todo).await?;
+    notify_in(tx, &todo.id, TodoEvent::Created { id: todo.id.1 }).await?;
+
+    Ok(todo)
 }
 
+/// the parents before their subtasks, and the last todos of the source first, so that the first ones end up on top
+fn import_order(todos: &[ImportTodo]) -> Vec<usize> {
+    let mut order = Vec::with_capacity(todos.len());
+    let mut imported = HashSet::new();
+    let mut left = (0..todos.len()).rev().collect::<Vec<usize>>();
+
+    loop {
+        let count = left.len();
+        left.retain(|&index| {
+            let ready = todos[index].parent_id.is_none_or(|parent_id| imported.contains(&parent_id));
+            if ready {
+                order.push(index);
+                imported.insert(todos[index].source_id);
+            }
+            !ready
+        });
+        if left.len() == count {
+            break;
+        }
+    }
+
+    // the parent is not in the source, they fail with `TodoError::ParentNotFound`
+    order.extend(left);
+    order
+}
+
+/// `ids` are the todos imported so far by their source id
+async fn import_in(tx: &TodoTx<'_>, namespace: &str, to_import: ImportTodo, ids: &HashMap<i32, i32>) -> Result<Imported> {
+    let parent_id = to_import.parent_id
+        .map(|parent_id| ids.get(&parent_id).copied().ok_or(TodoError::ParentNotFound(parent_id)))
+        .transpose()?;
+
+    if let Some(found) = tx.query_duplicate(namespace, &to_import.content, to_import.created_at).await? {
+        return Ok(Imported::Duplicate(found));
+    }
+
+    let mut todo = Todo::create(namespace, &to_import.content);
+    todo.status = to_import.status;
+    todo.created_at = to_import.created_at;
+    todo.due_at = to_import.due_at;
+    todo.priority = to_import.priority;
+    todo.tags = normalize_tags(to_import.tags)?;
+    todo.parent_id = parent_id;
+
+    let todo = tx.insert_todo(todo).await?;
+    notify_in(tx, &todo.id, TodoEvent::Created { id: todo.id.1 }).await?;
+
+    Ok(Imported::Created(todo))
+}
+
+async fn update_in(tx: &TodoTx<'_>, id: TodoID, mut to_update: UpdateTodo) -> Result<Todo> {
+    to_update.tags = to_update.tags.map(normalize_tags).transpose()?;
+    let mut found = find_in(tx, &id).await?;
+    if to_update.if_match.is_some_and(|version| version != found.version) {
+        return Err(TodoError::VersionConflict(found.id.1).into());
+    }
+
+    if let Some(parent_id) = found.parent_id.filter(|_| found.reopened_by(&to_update)) {
+        find_in(tx, &(id.0.clone(), parent_id)).await?.check_open()?;
+    }
+
+    let closing = found.closed_by(&to_update);
+    let cascade = to_update.cascade;
+    found.apply(to_update)?;
+
+    if let Some(status) = closing {
+        close_subtasks(tx, &found.id, status, cascade).await?;
+    }
+
+    let todo = tx.update_todo(found).await?;
+    notify_in(tx, &todo.id, TodoEvent::Updated { id: todo.id.1 }).await?;
+
+    Ok(todo)
+}
+
+/// the todo as it was before deleted, one not `Deleted` yet is moved to `Deleted` like `DELETE /todos/{id}` instead
+async fn delete_in(tx: &TodoTx<'_>, id: TodoID) -> Result<Todo> {
+    let found = find_in(tx, &id).await?;
+    if found.status != TodoStatus::Deleted {
+        return update_in(tx, id, UpdateTodo { status: Some(TodoStatus::Deleted), ..Default::default() }).await;
+    }
+
+    tx.delete_todo(&found.id).await?;
+    notify_in(tx, &found.id, TodoEvent::Deleted { id: found.id.1 }).await?;
+
+    Ok(found)
+}
+
+/// a parent leaves `Todo` only once all of its subtasks have, `cascade` moves the open ones to `status` too
+async fn close_subtasks(tx: &TodoTx<'_>, id: &TodoID, status: TodoStatus, cascade: bool) -> Result<()> {
+    if cascade {
+        // through the legal steps, e.g. `Todo` -> `Archived` -> `Deleted`
+        let steps = TodoStatus::Todo.steps_to(status)?;
+        for closed in tx.close_subtasks(id, &steps).await? {
+            notify_in(tx, id, TodoEvent::Updated { id: closed }).await?;
+        }
+        return Ok(());
+    }
+
+    match tx.count_open_subtasks(id).await? {
+        0 => Ok(()),
+        open => Err(TodoError::OpenSubtasks(id.1, open).into()),
+    }
+}
 
 #[derive(Error, Debug)]
 pub enum TodoError {
+    #[error("Todo [id: {0}] not found.")]
+    TodoNotFound(i32),
     #[error("invalid status {0} -> {1}")]
     InvalidStatusTransition(TodoStatus, TodoStatus),
     #[error("invalid status from str {0}")]
     InvalidStatusFromStr(String),
+    #[error("parent todo [id: {0}] not found.")]
+    ParentNotFound(i32),
+    #[error("Todo [id: {0}] has {1} open subtasks.")]
+    OpenSubtasks(i32, i64),
+    #[error("parent todo [id: {0}] is {1}, reopen it first.")]
+    ClosedParent(i32, TodoStatus),
+    #[error("Todo [id: {0}] has been changed since.")]
+    VersionConflict(i32),
+    #[error("invalid cursor [{0}], expect the next_cursor of the previous page in the same order")]
+    InvalidCursor(String),
+}
+
+/// legal status transitions, see `doc/status_transform.png`
+const STATUS_TRANSITIONS: &[(TodoStatus, TodoStatus)] = &[
+    (TodoStatus::Todo, TodoStatus::Done),
+    (TodoStatus::Done, TodoStatus::Todo),
+    (TodoStatus::Todo, TodoStatus::Archived),
+    (TodoStatus::Done, TodoStatus::Archived),
+    (TodoStatus::Archived, TodoStatus::Deleted),
+];
+
+pub trait TodoStatusMachine: Sized {
+    fn can_transit_to(&self, to: TodoStatus) -> bool;
+
+    fn transit_to(self, to: TodoStatus) -> Result<TodoStatus, TodoError>;
+
+    /// the fewest legal transitions to `to`, none when it is the same status
+    fn steps_to(self, to: TodoStatus) -> Result<Vec<TodoStatus>, TodoError>;
 }
 
-trait TodoStatusMachine {}
+impl TodoStatusMachine for TodoStatus {
+    fn can_transit_to(&self, to: TodoStatus) -> bool {
+        *self == to || STATUS_TRANSITIONS.contains(&(*self, to))
+    }
+
+    fn transit_to(self, to: TodoStatus) -> Result<TodoStatus, TodoError> {
+        if self.can_transit_to(to) {
+            Ok(to)
+        } else {
+            Err(TodoError::InvalidStatusTransition(self, to))
+        }
+    }
+
+    fn steps_to(self, to: TodoStatus) -> Result<Vec<TodoStatus>, TodoError> {
+        // breadth-first, each status reached along with the steps to it
+        let mut reached = vec![(self, vec![])];
+        let mut index = 0;
+        while let Some((from, steps)) = reached.get(index).cloned() {
+            if from == to {
+                return Ok(steps);
+            }
+            for &(_, next) in STATUS_TRANSITIONS.iter().filter(|(it, _)| *it == from) {
+                if reached.iter().all(|(it, _)| *it != next) {
+                    reached.push((next, [steps.as_slice(), &[next]].concat()));
+                }
+            }
+            index += 1;
+        }
+
+        Err(TodoError::InvalidStatusTransition(self, to))
+    }
+}
 
 #[cfg(test)]
 mod tests {
+    use chrono::{Duration, Utc};
+    use common::model::{TodoSort, TodoStatus};
+    use common::model::TodoStatus::*;
+
+    use crate::infra::{config, db};
+    use super::{CreateTodo, Cursor, import_order, ImportTodo, ListTodo, MAX_PAGE_SIZE, Page, POSITION_GAP, position_between};
+    use super::{BatchOp, Imported, TodoDomain, TodoDomainTrait, TodoError, TodoStatusMachine, UpdateTodo};
+    use crate::domains::tag_domain::TagError;
+
+    fn domain() -> TodoDomain {
+        TodoDomain::new(db::must_init(&config::must_get().db))
+    }
+
+    fn subtask_of(parent: &super::Todo, content: &str) -> CreateTodo {
+        CreateTodo { content: content.to_string(), parent_id: Some(parent.id.1), ..Default::default() }
+    }
+
+    fn to_status(status: common::model::TodoStatus, cascade: bool) -> UpdateTodo {
+        UpdateTodo { status: Some(status), cascade, ..Default::default() }
+    }
+
+    #[test]
+    fn check() {
+        let legal = [
+            (Todo, Done), (Done, Todo),
+            (Todo, Archived), (Done, Archived),
+            (Archived, Deleted),
+        ];
+        let all = [Todo, Done, Archived, Deleted];
+
+        for from in all {
+            for to in all {
+                let expect = from == to || legal.contains(&(from, to));
+                assert_eq!(expect, from.can_transit_to(to), "{} -> {}", from, to);
+            }
+        }
+    }
+
+    #[test]
+    fn transit_to() {
+        assert_eq!(Done, Todo.transit_to(Done).unwrap());
+
+        let err = Deleted.transit_to(Todo).unwrap_err();
+        assert!(matches!(err, TodoError::InvalidStatusTransition(Deleted, Todo)));
+    }
+
+    #[test]
+    fn steps_to() {
+        assert_eq!(vec![Done], Todo.steps_to(Done).unwrap());
+        assert_eq!(vec![Archived, Deleted], Todo.steps_to(Deleted).unwrap());
+        assert!(Done.steps_to(Done).unwrap().is_empty());
+
+        let err = Deleted.steps_to(Todo).unwrap_err();
+        assert!(matches!(err, TodoError::InvalidStatusTransition(Deleted, Todo)));
+    }
+
+    #[test]
+    fn list_todo_page_size() {
+        assert_eq!(50, ListTodo::page_size(None));
+        assert_eq!(1, ListTodo::page_size(Some(0)));
+        assert_eq!(MAX_PAGE_SIZE, ListTodo::page_size(Some(10_000)));
+    }
+
+    #[test]
+    fn cursor() {
+        for (cursor, sort) in [
+            (Cursor::Id(7), TodoSort::Newest),
+            (Cursor::Priority(2, 7), TodoSort::Priority),
+            (Cursor::Position(-1024, 7), TodoSort::Manual),
+        ] {
+            assert_eq!(Ok(cursor), Cursor::parse(&cursor.to_string(), sort, false).map_err(|it| it.to_string()));
+        }
+        assert_eq!("2:7", Cursor::Priority(2, 7).to_string());
+        let rank = Cursor::Rank(0.0607927, 7);
+        assert_eq!(rank, Cursor::parse(&rank.to_string(), TodoSort::Manual, true).unwrap());
+        assert!(Cursor::parse("7", TodoSort::Newest, true).is_err());
+
+        for (cursor, sort) in [("x", TodoSort::Newest), ("7", TodoSort::Priority), ("1024:x", TodoSort::Manual), ("2:7", TodoSort::Newest)] {
+            assert!(matches!(Cursor::parse(cursor, sort, false), Err(TodoError::InvalidCursor(_))), "{}", cursor);
+        }
+    }
+
+    #[test]
+    fn page_of() {
+        let rows = |ids: &[i32]| ids.iter().map(|id| super::Todo {
+            id: ("default".to_string(), *id),
+            ..Default::default()
+        }).collect::<Vec<super::Todo>>();
+
+        let page = Page::of(rows(&[5, 4, 3]), 2, TodoSort::Newest);
+        assert_eq!(2, page.items.len());
+        assert_eq!(Some(Cursor::Id(4)), page.next_cursor);
+
+        let page = Page::of(rows(&[2, 1]), 2, TodoSort::Newest);
+        assert_eq!(2, page.items.len());
+        assert_eq!(None, page.next_cursor);
+    }
+
+    #[test]
+    fn apply() {
+        let mut todo = super::Todo::default();
+
+        todo.apply(UpdateTodo {
+            content: Some("updated".to_string()),
+            status: Some(Done),
+            ..Default::default()
+        }).unwrap();
+        assert_eq!("updated", todo.content);
+        assert_eq!(TodoStatus::Done, todo.status);
+
+        let err = todo.apply(UpdateTodo {
+            content: Some("ignored".to_string()),
+            status: Some(Deleted),
+            ..Default::default()
+        });
+        assert!(err.is_err());
+        assert_eq!("updated", todo.content);
+        assert_eq!(TodoStatus::Done, todo.status);
+    }
+
+    #[test]
+    fn apply_due_at() {
+        let mut todo = super::Todo::default();
+        let due_at = Utc::now();
+
+        todo.apply(UpdateTodo { due_at: Some(Some(due_at)), ..Default::default() }).unwrap();
+        assert_eq!(Some(due_at), todo.due_at);
+
+        todo.apply(UpdateTodo::default()).unwrap();
+        assert_eq!(Some(due_at), todo.due_at);
+
+        todo.apply(UpdateTodo { due_at: Some(None), ..Default::default() }).unwrap();
+        assert_eq!(None, todo.due_at);
+    }
+
+    #[test]
+    fn position_between_neighbors() {
+        assert_eq!(Some(0), position_between(None, None));
+        assert_eq!(Some(-POSITION_GAP), position_between(None, Some(0)));
+        assert_eq!(Some(POSITION_GAP), position_between(Some(0), None));
+        assert_eq!(Some(512), position_between(Some(0), Some(1024)));
+        assert_eq!(Some(-1), position_between(Some(-2), Some(0)));
+        assert_eq!(None, position_between(Some(1), Some(2)));
+        assert_eq!(None, position_between(Some(i64::MAX), None));
+    }
+
+    #[test]
+    fn is_overdue() {
+        let now = Utc::now();
+        let todo = |status, due_at| super::Todo { status, due_at, ..Default::default() };
+
+        assert!(todo(Todo, Some(now - Duration::hours(1))).is_overdue(now));
+        assert!(!todo(Todo, Some(now + Duration::hours(1))).is_overdue(now));
+        assert!(!todo(Todo, None).is_overdue(now));
+        assert!(!todo(Done, Some(now - Duration::hours(1))).is_overdue(now));
+    }
+
+    #[test]
+    fn closed_by() {
+        let todo = |status| super::Todo { status, ..Default::default() };
+        let to = |status| UpdateTodo { status: Some(status), ..Default::default() };
+
+        assert_eq!(Some(Done), todo(Todo).closed_by(&to(Done)));
+        assert_eq!(Some(Archived), todo(Todo).closed_by(&to(Archived)));
+        assert_eq!(Some(Archived), todo(Done).closed_by(&to(Archived)));
+        assert_eq!(Some(Deleted), todo(Archived).closed_by(&to(Deleted)));
+        assert_eq!(None, todo(Done).closed_by(&to(Done)));
+        assert_eq!(None, todo(Done).closed_by(&to(Todo)));
+        assert_eq!(None, todo(Todo).closed_by(&UpdateTodo::default()));
+    }
+
+    fn todo_of(content: &str) -> CreateTodo {
+        CreateTodo { content: content.to_string(), ..Default::default() }
+    }
+
+    #[actix_web::test]
+    async fn create_todo() {
+        let domain = domain();
+        let ns = "testing/domain/create_todo".to_string();
+
+        let tags = vec!["release-1.2".to_string(), "backend".to_string(), "backend".to_string()];
+        let created = domain.create_todo(&ns, CreateTodo { tags, ..todo_of("tagged") }).await.unwrap();
+        assert_eq!(vec!["backend".to_string(), "release-1.2".to_string()], created.tags);
+
+        let err = domain.create_todo(&ns, CreateTodo { tags: vec!["two words".to_string()], ..todo_of("invalid") }).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<TagError>(), Some(TagError::InvalidTagName(_))));
+
+        let err = domain.create_todo(&ns, CreateTodo { parent_id: Some(created.id.1 + 1000), ..todo_of("orphan") }).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<TodoError>(), Some(TodoError::ParentNotFound(_))));
+
+        domain.clear_todos(ns, vec![created.id.1]).await.unwrap();
+    }
+
+    #[actix_web::test]
+    async fn update_todo() {
+        let domain = domain();
+        let ns = "testing/domain/update_todo".to_string();
+        let created = domain.create_todo(&ns, todo_of("todo")).await.unwrap();
+
+        let err = domain.update_todo(created.id.clone(), to_status(Deleted, false)).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<TodoError>(), Some(TodoError::InvalidStatusTransition(Todo, Deleted))));
+
+        let to_update = |if_match| UpdateTodo { content: Some("updated".to_string()), if_match, ..Default::default() };
+        let updated = domain.update_todo(created.id.clone(), to_update(Some(created.version))).await.unwrap();
+        assert_eq!(created.version + 1, updated.version);
+        let err = domain.update_todo(created.id.clone(), to_update(Some(created.version))).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<TodoError>(), Some(TodoError::VersionConflict(_))));
+        assert_eq!(updated.version, domain.get_todo_by_id(created.id.clone()).await.unwrap().version);
+
+        domain.clear_todos(ns, vec![created.id.1]).await.unwrap();
+    }
+
+    #[actix_web::test]
+    async fn batch() {
+        let domain = domain();
+        let ns = "testing/domain/batch".to_string();
+        let parent = domain.create_todo(&ns, todo_of("parent")).await.unwrap();
+        let child = domain.create_todo(&ns, subtask_of(&parent, "child")).await.unwrap();
+
+        let results = domain.batch(&ns, vec![
+            BatchOp::Update(parent.id.1, UpdateTodo { content: Some("renamed".to_string()), ..to_status(Done, false) }),
+            BatchOp::Update(parent.id.1, to_status(Done, true)),
+            BatchOp::Update(child.id.1 + 1000, to_status(Done, false)),
+            BatchOp::Create(todo_of("created")),
+        ]).await.unwrap();
+
+        // a failed operation is rolled back alone
+        let err = results[0].as_ref().unwrap_err();
+        assert!(matches!(err.downcast_ref::<TodoError>(), Some(TodoError::OpenSubtasks(_, 1))));
+        let closed = results[1].as_ref().unwrap();
+        assert_eq!(("parent", Done), (closed.content.as_str(), closed.status));
+        let err = results[2].as_ref().unwrap_err();
+        assert!(matches!(err.downcast_ref::<TodoError>(), Some(TodoError::TodoNotFound(_))));
+        let created = results[3].as_ref().unwrap();
+        assert_eq!(Done, domain.get_todo_by_id(child.id.clone()).await.unwrap().status);
+
+        domain.clear_todos(ns, vec![parent.id.1, child.id.1, created.id.1]).await.unwrap();
+    }
+
+    #[actix_web::test]
+    async fn batch_delete() {
+        let domain = domain();
+        let ns = "testing/domain/batch_delete".to_string();
+        let open = domain.create_todo(&ns, todo_of("open")).await.unwrap();
+        let archived = domain.create_todo(&ns, todo_of("archived")).await.unwrap();
+        domain.update_todo(archived.id.clone(), to_status(Archived, false)).await.unwrap();
+        let deleted = domain.create_todo(&ns, todo_of("deleted")).await.unwrap();
+        domain.update_todo(deleted.id.clone(), to_status(Archived, false)).await.unwrap();
+        domain.update_todo(deleted.id.clone(), to_status(Deleted, false)).await.unwrap();
+
+        let results = domain.batch(&ns, vec![
+            BatchOp::Delete(open.id.1),
+            BatchOp::Delete(archived.id.1),
+            BatchOp::Delete(deleted.id.1),
+        ]).await.unwrap();
+
+        // only `Archived` -> `Deleted` is legal, a `Deleted` one is removed for good
+        let err = results[0].as_ref().unwrap_err();
+        assert!(matches!(err.downcast_ref::<TodoError>(), Some(TodoError::InvalidStatusTransition(Todo, Deleted))));
+        assert_eq!(Todo, domain.get_todo_by_id(open.id.clone()).await.unwrap().status);
+        assert_eq!(Deleted, results[1].as_ref().unwrap().status);
+        assert_eq!(Deleted, domain.get_todo_by_id(archived.id.clone()).await.unwrap().status);
+        assert_eq!(deleted.id, results[2].as_ref().unwrap().id);
+        assert!(domain.get_todo_by_id(deleted.id).await.is_err());
+
+        domain.clear_todos(ns, vec![open.id.1, archived.id.1]).await.unwrap();
+    }
+
+    #[actix_web::test]
+    async fn import_todos() {
+        let domain = domain();
+        let ns = "testing/domain/import_todos".to_string();
+        let todo = |source_id, parent_id| ImportTodo {
+            source_id,
+            content: format!("imported {}", source_id),
+            status: Done,
+            created_at: Utc::now() - Duration::days(1),
+            due_at: None,
+            priority: Default::default(),
+            tags: vec![],
+            parent_id,
+        };
+        let todos = vec![todo(2, Some(1)), todo(1, None), todo(3, Some(9))];
+
+        let results = domain.import_todos(&ns, todos.clone()).await.unwrap();
+
+        let (Ok(Imported::Created(subtask)), Ok(Imported::Created(parent))) = (&results[0], &results[1]) else {
+            panic!("expect the parent and its subtask created");
+        };
+        assert_eq!((Some(parent.id.1), Done), (subtask.parent_id, subtask.status));
+        let err = results[2].as_ref().err().unwrap();
+        assert!(matches!(err.downcast_ref::<TodoError>(), Some(TodoError::ParentNotFound(9))));
+
+        // the same content created at the same time is not imported twice
+        let again = domain.import_todos(&ns, todos).await.unwrap();
+        assert!(matches!(&again[1], Ok(Imported::Duplicate(found)) if found.id == parent.id));
+
+        domain.clear_todos(ns, vec![parent.id.1, subtask.id.1]).await.unwrap();
+    }
+
+    #[actix_web::test]
+    async fn close_parent() {
+        let domain = domain();
+        let ns = "testing/domain/close_parent".to_string();
+        let parent = domain.create_todo(&ns, CreateTodo { content: "parent".to_string(), ..Default::default() }).await.unwrap();
+        let child = domain.create_todo(&ns, subtask_of(&parent, "child")).await.unwrap();
+        let grandchild = domain.create_todo(&ns, subtask_of(&child, "grandchild")).await.unwrap();
+
+        // archiving, like marking done, waits for the open subtasks at any depth
+        let err = domain.update_todo(parent.id.clone(), to_status(Archived, false)).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<TodoError>(), Some(TodoError::OpenSubtasks(_, 2))));
+
+        domain.update_todo(child.id.clone(), to_status(Done, true)).await.unwrap();
+        domain.update_todo(parent.id.clone(), to_status(Archived, false)).await.unwrap();
+        domain.update_todo(parent.id.clone(), to_status(Deleted, false)).await.unwrap();
+        assert_eq!(Done, domain.get_todo_by_id(grandchild.id.clone()).await.unwrap().status);
+
+        // the hard delete leaves the subtasks as top-level todos
+        domain.clear_todos(ns.clone(), vec![parent.id.1]).await.unwrap();
+        let child = domain.get_todo_by_id(child.id.clone()).await.unwrap();
+        assert_eq!((Done, None), (child.status, child.parent_id));
+        assert_eq!(Some(child.id.1), domain.get_todo_by_id(grandchild.id.clone()).await.unwrap().parent_id);
+
+        domain.clear_todos(ns, vec![child.id.1, grandchild.id.1]).await.unwrap();
+    }
+
+    #[actix_web::test]
+    async fn close_parent_cascade() {
+        let domain = domain();
+        let ns = "testing/domain/close_parent_cascade".to_string();
+        let parent = domain.create_todo(&ns, CreateTodo { content: "parent".to_string(), ..Default::default() }).await.unwrap();
+        let child = domain.create_todo(&ns, subtask_of(&parent, "child")).await.unwrap();
+        let done = domain.create_todo(&ns, subtask_of(&parent, "done child")).await.unwrap();
+        domain.update_todo(done.id.clone(), to_status(Done, false)).await.unwrap();
+
+        domain.update_todo(parent.id.clone(), to_status(Archived, true)).await.unwrap();
+
+        assert_eq!(Archived, domain.get_todo_by_id(child.id.clone()).await.unwrap().status);
+        assert_eq!(Done, domain.get_todo_by_id(done.id.clone()).await.unwrap().status);
+
+        domain.clear_todos(ns, vec![parent.id.1, child.id.1, done.id.1]).await.unwrap();
+    }
+
     #[actix_web::test]
-    async fn check() {}
+    async fn delete_parent_cascade() {
+        let domain = domain();
+        let ns = "testing/domain/delete_parent_cascade".to_string();
+        let parent = domain.create_todo(&ns, CreateTodo { content: "parent".to_string(), ..Default::default() }).await.unwrap();
+        let child = domain.create_todo(&ns, subtask_of(&parent, "child")).await.unwrap();
+        // an open subtask left under an archived parent, as written before the parent had to be open
+        let mut archived = parent.clone();
+        archived.status = Archived;
+        domain.repo.update_todo(archived).await.unwrap();
+
+        domain.update_todo(parent.id.clone(), to_status(Deleted, true)).await.unwrap();
+
+        // `Todo` -> `Archived` -> `Deleted`, never `Todo` -> `Deleted` at once
+        let deleted = domain.get_todo_by_id(child.id.clone()).await.unwrap();
+        assert_eq!((Deleted, child.version + 2), (deleted.status, deleted.version));
+
+        domain.clear_todos(ns, vec![parent.id.1, child.id.1]).await.unwrap();
+    }
+
+    #[actix_web::test]
+    async fn reopen_under_closed_parent() {
+        let domain = domain();
+        let ns = "testing/domain/reopen".to_string();
+        let parent = domain.create_todo(&ns, CreateTodo { content: "parent".to_string(), ..Default::default() }).await.unwrap();
+        let child = domain.create_todo(&ns, subtask_of(&parent, "child")).await.unwrap();
+        domain.update_todo(parent.id.clone(), to_status(Done, true)).await.unwrap();
+
+        let err = domain.update_todo(child.id.clone(), to_status(Todo, false)).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<TodoError>(), Some(TodoError::ClosedParent(id, Done)) if *id == parent.id.1));
+        let err = domain.create_todo(&ns, subtask_of(&parent, "added")).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<TodoError>(), Some(TodoError::ClosedParent(id, Done)) if *id == parent.id.1));
+        assert_eq!(Done, domain.get_todo_by_id(child.id.clone()).await.unwrap().status);
+
+        // reopen the parent first
+        domain.update_todo(parent.id.clone(), to_status(Todo, false)).await.unwrap();
+        domain.update_todo(child.id.clone(), to_status(Todo, false)).await.unwrap();
+        let added = domain.create_todo(&ns, subtask_of(&parent, "added")).await.unwrap();
+        assert_eq!(Some(parent.id.1), added.parent_id);
+
+        domain.clear_todos(ns, vec![parent.id.1, child.id.1, added.id.1]).await.unwrap();
+    }
+
+    #[test]
+    fn reopened_by() {
+        let todo = |status| super::Todo { status, ..Default::default() };
+        let to = |status| UpdateTodo { status: Some(status), ..Default::default() };
+
+        assert!(todo(Done).reopened_by(&to(Todo)));
+        assert!(!todo(Todo).reopened_by(&to(Todo)));
+        assert!(!todo(Todo).reopened_by(&to(Done)));
+        assert!(!todo(Done).reopened_by(&UpdateTodo::default()));
+    }
+
+    #[test]
+    fn import_order_parents_first() {
+        let todo = |source_id, parent_id| ImportTodo {
+            source_id,
+            content: source_id.to_string(),
+            status: Todo,
+            created_at: Utc::now(),
+            due_at: None,
+            priority: Default::default(),
+            tags: vec![],
+            parent_id,
+        };
+
+        // newest first like the local todos, with a subtask of a subtask and an orphan
+        let todos = [todo(4, Some(2)), todo(3, None), todo(2, Some(1)), todo(1, None), todo(5, Some(9))];
+
+        assert_eq!(vec![3, 2, 1, 0, 4], import_order(&todos));
+    }
 }
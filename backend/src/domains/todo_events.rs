@@ -0,0 +1,156 @@
+use std::future::poll_fn;
+use std::pin::pin;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tokio_postgres::{AsyncMessage, NoTls};
+
+use common::model::TodoEvent;
+
+/// the channel of `LISTEN`/`NOTIFY`
+pub const CHANNEL: &str = "todo_events";
+
+/// events kept for a slow subscriber before it gets `TodoEvent::Lagged`
+const CAPACITY: usize = 1024;
+
+/// the payload of a `NOTIFY` on [`CHANNEL`]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct TodoChange {
+    pub namespace: String,
+    #[serde(flatten)]
+    pub event: TodoEvent,
+}
+
+impl TodoChange {
+    pub fn new(namespace: &str, event: TodoEvent) -> Self {
+        Self { namespace: namespace.to_string(), event }
+    }
+}
+
+/// fans the changes notified by any backend instance out to the subscribers of this one
+#[derive(Clone)]
+pub struct TodoEvents {
+    sender: broadcast::Sender<TodoChange>,
+}
+
+impl Default for TodoEvents {
+    fn default() -> Self {
+        Self { sender: broadcast::channel(CAPACITY).0 }
+    }
+}
+
+impl TodoEvents {
+    /// the changes of every namespace from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<TodoChange> {
+        self.sender.subscribe()
+    }
+
+    pub fn publish(&self, change: TodoChange) {
+        // no subscriber is not an error
+        let _ = self.sender.send(change);
+    }
+
+    /// `LISTEN` on a dedicated connection, reconnecting until the server stops
+    pub async fn listen(self, config: tokio_postgres::Config) {
+        loop {
+            if let Err(err) = self.listen_once(&config).await {
+                log::error!("failed to listen to {}: {:?}", CHANNEL, err);
+            }
+
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+
+    async fn listen_once(&self, config: &tokio_postgres::Config) -> Result<()> {
+        let (client, mut connection) = config.connect(NoTls).await?;
+
+        // the connection has to be polled for the `LISTEN` to complete
+        let statement = format!("LISTEN {}", CHANNEL);
+        let mut listen = pin!(client.batch_execute(&statement));
+        let mut listening = false;
+
+        loop {
+            tokio::select! {
+                result = &mut listen, if !listening => {
+                    result?;
+                    listening = true;
+                    log::info!("listening to {}", CHANNEL);
+                }
+                message = poll_fn(|cx| connection.poll_message(cx)) => match message {
+                    Some(Ok(AsyncMessage::Notification(notification))) => {
+                        match serde_json::from_str::<TodoChange>(notification.payload()) {
+                            Ok(change) => self.publish(change),
+                            Err(err) => log::warn!("invalid {} payload {:?}: {}", CHANNEL, notification.payload(), err),
+                        }
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(err)) => return Err(err.into()),
+                    None => bail!("connection closed"),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::sync::broadcast::Receiver;
+    use tokio::time::timeout;
+
+    use common::model::TodoEvent;
+
+    use crate::domains::todo_repository::{TodoRepository, TodoTx};
+    use crate::infra::{config, db};
+    use super::{TodoChange, TodoEvents};
+
+    static NS: &str = "testing/todo_events";
+
+    /// the next change of `NS`, `None` when there is none for a while
+    async fn next_change(receiver: &mut Receiver<TodoChange>) -> Option<TodoChange> {
+        loop {
+            match timeout(Duration::from_millis(500), receiver.recv()).await {
+                Ok(Ok(change)) if change.namespace == NS => return Some(change),
+                Ok(_) => continue,
+                Err(_) => return None,
+            }
+        }
+    }
+
+    #[actix_web::test]
+    async fn listen() {
+        let config = config::must_get();
+        let repo = TodoRepository::new(db::must_init(&config.db));
+        let events = TodoEvents::default();
+        let mut receiver = events.subscribe();
+        tokio::spawn(events.clone().listen(config.db.get_pg_config().unwrap()));
+
+        // notified again until the listener is up
+        let ready = TodoChange::new(NS, TodoEvent::Lagged);
+        loop {
+            repo.notify(std::slice::from_ref(&ready)).await.unwrap();
+            if let Some(change) = next_change(&mut receiver).await {
+                assert_eq!(ready, change);
+                break;
+            }
+        }
+
+        let mut conn = repo.connect().await.unwrap();
+
+        let rolled_back = TodoChange::new(NS, TodoEvent::Created { id: 1 });
+        let tx = TodoTx::begin(&mut conn).await.unwrap();
+        tx.notify(&rolled_back).await.unwrap();
+        tx.rollback().await.unwrap();
+
+        let committed = TodoChange::new(NS, TodoEvent::Updated { id: 2 });
+        let tx = TodoTx::begin(&mut conn).await.unwrap();
+        tx.notify(&committed).await.unwrap();
+        tx.commit().await.unwrap();
+
+        assert_eq!(Some(committed), next_change(&mut receiver).await);
+        assert_eq!(None, next_change(&mut receiver).await);
+    }
+}
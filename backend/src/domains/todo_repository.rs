@@ -1,15 +1,18 @@
 use std::str::FromStr;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 use anyhow::Result;
-use chrono::DateTime;
-use deadpool_postgres::Pool;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::{Pool, Transaction};
+use tokio_postgres::IsolationLevel;
 use postgres_types::{FromSql, ToSql};
 use tokio_pg_mapper::FromTokioPostgresRow;
 use tokio_pg_mapper_derive::PostgresMapper;
+use tokio_postgres::Row;
 
-use common::model::TodoStatus;
+use common::model::{TodoPriority, TodoSort, TodoStatus};
 
-use crate::domains::todo_domain::{Todo, TodoID};
+use crate::domains::todo_events::{CHANNEL, TodoChange};
+use crate::domains::todo_domain::{Changes, Cursor, ListTodo, MoveTo, POSITION_GAP, position_between, SearchHit, Todo, TodoError, TodoID};
 use crate::infra::db::RecordNotFound;
 
 #[derive(PostgresMapper, Debug, FromSql, ToSql)]
@@ -21,8 +24,32 @@ struct TodoEntity {
     status: String,
     created_at: SystemTime,
     updated_at: SystemTime,
+    due_at: Option<SystemTime>,
+    priority: i16,
+    position: i64,
+    parent_id: Option<i32>,
+    version: i32,
 }
 
+/// how far back `Changes::until` is from the snapshot, longer than a write transaction
+const CHANGES_OVERLAP: Duration = Duration::from_secs(5);
+
+/// tag names of the `todos` row in alphabetical order
+const TAG_NAMES: &str = r#"ARRAY(
+    SELECT tags.name FROM todo_tags
+    JOIN tags ON tags.namespace = todo_tags.namespace AND tags.id = todo_tags.tag_id
+    WHERE todo_tags.namespace = todos.namespace AND todo_tags.todo_id = todos.id
+    ORDER BY tags.name
+)"#;
+
+/// `subtasks` CTE, all the descendants of the todo `($1, $2)`
+const SUBTASKS: &str = r#"WITH RECURSIVE subtasks AS (
+    SELECT namespace, id, status FROM todos WHERE namespace = $1 AND parent_id = $2
+    UNION ALL
+    SELECT todos.namespace, todos.id, todos.status FROM todos
+    JOIN subtasks ON todos.namespace = subtasks.namespace AND todos.parent_id = subtasks.id
+)"#;
+
 #[derive(Clone)]
 pub struct TodoRepository {
     db: Pool,
@@ -36,109 +63,561 @@ impl TodoRepository {
     pub async fn query_by_id(&self, (namespace, id): TodoID) -> Result<Todo> {
         let client = self.db.get().await?;
 
-        let statement = client
-            .prepare_cached("SELECT * FROM todos WHERE namespace = $1 AND id = $2").await?;
+        let statement = client.prepare_cached(&format!(
+            "SELECT todos.*, {TAG_NAMES} AS tags FROM todos WHERE namespace = $1 AND id = $2")).await?;
 
         match client.query_opt(&statement, &[&namespace, &id]).await? {
             None => Err(RecordNotFound.into()),
-            Some(row) => {
-                let entity = TodoEntity::from_row(row).map(Todo::from)?;
-                Ok(entity)
-            }
+            Some(row) => todo_of(row),
         }
     }
 
-    pub async fn query_todos(&self, namespace: String, status: Option<TodoStatus>) -> Result<Vec<Todo>> {
+    /// keyset pagination in the `sort` order, rows after the sort key and the id of the `cursor`
+    pub async fn query_todos(&self, namespace: String, query: &ListTodo) -> Result<Vec<Todo>> {
         let client = self.db.get().await?;
 
-        // todo improve
-        let rows = match status {
-            Some(value) => {
-                let statement = client
-                    .prepare_cached("SELECT * FROM todos WHERE namespace = $1 AND status = $2 ORDER BY id DESC").await?;
+        let (after_cursor, order_by) = match query.sort {
+            // the id is the sort key itself
+            TodoSort::Newest => ("$8::BIGINT IS NULL AND id < $3", "id DESC"),
+            TodoSort::Priority => ("(priority, id) < ($8::BIGINT, $3)", "priority DESC, id DESC"),
+            TodoSort::Manual => ("(position, id) > ($8::BIGINT, $3)", "position, id"),
+        };
 
-                client.query(&statement, &[&namespace, &value.to_string()]).await?
-            }
-            None => {
-                let statement = client
-                    .prepare_cached("SELECT * FROM todos WHERE namespace = $1 ORDER BY id DESC").await?;
+        let statement = client.prepare_cached(&format!(r#"
+            SELECT todos.*, {TAG_NAMES} AS tags FROM todos
+            WHERE namespace = $1
+              AND ($2::VARCHAR IS NULL OR status = $2)
+              AND ($3::INT IS NULL OR {after_cursor})
+              AND ($4::TIMESTAMPTZ IS NULL OR due_at < $4)
+              AND ($5::BOOL IS NULL OR (status = 'Todo' AND COALESCE(due_at < now(), false)) = $5)
+              AND ($7::VARCHAR IS NULL OR $7 = ANY({TAG_NAMES}))
+            ORDER BY {order_by}
+            LIMIT $6
+        "#)).await?;
 
-                client.query(&statement, &[&namespace]).await?
-            }
+        let status = query.status.map(|it| it.to_string());
+        let due_before = query.due_before.map(SystemTime::from);
+        let cursor_id = query.cursor.map(|it| it.id());
+        let cursor_key = match query.cursor {
+            Some(Cursor::Priority(priority, _)) => Some(priority as i64),
+            Some(Cursor::Position(position, _)) => Some(position),
+            _ => None,
         };
+        let rows = client.query(&statement, &[
+            &namespace, &status, &cursor_id, &due_before, &query.overdue, &query.limit, &query.tag, &cursor_key,
+        ]).await?;
 
-        let entities = rows
-            .into_iter()
-            .map(|r| TodoEntity::from_row(r).unwrap())
-            .map(Todo::from)
-            .collect();
+        rows.into_iter().map(todo_of).collect()
+    }
 
-        Ok(entities)
+    /// full-text search on content, ordered by rank, keyset on the rank and the id of the `cursor`.
+    /// the tags and the highlighted snippet are of the hits of the page only
+    pub async fn search_todos(&self, namespace: String, q: &str, query: &ListTodo) -> Result<Vec<Todo>> {
+        let client = self.db.get().await?;
+
+        let statement = client.prepare_cached(&format!(r#"
+            WITH hits AS (
+                SELECT todos.*, ts_rank(to_tsvector('simple', content), query) AS rank, query
+                FROM todos, websearch_to_tsquery('simple', $2) query
+                WHERE namespace = $1
+                  AND to_tsvector('simple', content) @@ query
+                  AND ($3::VARCHAR IS NULL OR status = $3)
+                  AND ($4::TIMESTAMPTZ IS NULL OR due_at < $4)
+                  AND ($5::BOOL IS NULL OR (status = 'Todo' AND COALESCE(due_at < now(), false)) = $5)
+                  AND ($8::VARCHAR IS NULL OR $8 = ANY({TAG_NAMES}))
+                  AND ($6::INT IS NULL OR (ts_rank(to_tsvector('simple', content), query), id) < ($9::REAL, $6))
+                ORDER BY rank DESC, id DESC
+                LIMIT $7
+            )
+            SELECT todos.*, {TAG_NAMES} AS tags,
+                   ts_headline('simple', content, query,
+                               'StartSel=<mark>, StopSel=</mark>, HighlightAll=true') AS snippet
+            FROM hits todos
+            ORDER BY rank DESC, id DESC
+        "#)).await?;
+
+        let status = query.status.map(|it| it.to_string());
+        let due_before = query.due_before.map(SystemTime::from);
+        let cursor_id = query.cursor.map(|it| it.id());
+        let cursor_rank = match query.cursor {
+            Some(Cursor::Rank(rank, _)) => Some(rank),
+            _ => None,
+        };
+        let rows = client.query(&statement, &[
+            &namespace, &q, &status, &due_before, &query.overdue, &cursor_id, &query.limit, &query.tag, &cursor_rank,
+        ]).await?;
+
+        rows.into_iter()
+            .map(|r| {
+                let hit = SearchHit { rank: r.get("rank"), snippet: r.get("snippet") };
+                let mut todo = todo_of(r)?;
+                todo.hit = Some(hit);
+                Ok(todo)
+            })
+            .collect()
+    }
+
+    /// a connection to run a `TodoTx` on
+    pub async fn connect(&self) -> Result<Connection> {
+        Ok(self.db.get().await?)
     }
 
+    #[cfg(test)]
     pub async fn insert_todo(&self, todo: Todo) -> Result<Todo> {
-        let entity = TodoEntity::from(todo);
+        let mut conn = self.connect().await?;
+        let tx = TodoTx::begin(&mut conn).await?;
+
+        let todo = tx.insert_todo(todo).await?;
+
+        tx.commit().await?;
+
+        Ok(todo)
+    }
+
+    /// the domain updates in a `TodoTx` with its rules, this one seeds the tests
+    #[cfg(test)]
+    pub async fn update_todo(&self, todo: Todo) -> Result<Todo> {
+        let mut conn = self.connect().await?;
+        let tx = TodoTx::begin(&mut conn).await?;
+
+        let todo = tx.update_todo(todo).await?;
+
+        tx.commit().await?;
+
+        Ok(todo)
+    }
+
+    /// position the todo between the target and its neighbor, the positions of the namespace
+    /// are spread by `POSITION_GAP` again when there is no room left in between,
+    /// `TodoError::VersionConflict` when the todo is no longer at the version of `if_match`
+    pub async fn move_todo(&self, (namespace, id): TodoID, to: MoveTo, if_match: Option<i32>) -> Result<Todo> {
+        let mut client = self.db.get().await?;
+        let tx = client.transaction().await?;
+
+        // moves in the same namespace are serialized
+        tx.execute("SELECT pg_advisory_xact_lock(hashtext($1))", &[&namespace]).await?;
+
+        let version: i32 = match tx.query_opt(
+            "SELECT version FROM todos WHERE namespace = $1 AND id = $2 FOR UPDATE",
+            &[&namespace, &id],
+        ).await? {
+            None => return Err(RecordNotFound.into()),
+            Some(row) => row.get(0),
+        };
+        if if_match.is_some_and(|it| it != version) {
+            return Err(TodoError::VersionConflict(id).into());
+        }
+
+        let target_statement = tx.prepare_cached(
+            "SELECT position FROM todos WHERE namespace = $1 AND id = $2").await?;
+        let neighbor_statement = tx.prepare_cached(match to {
+            MoveTo::Before(_) => "SELECT max(position) FROM todos WHERE namespace = $1 AND position < $2 AND id <> $3",
+            MoveTo::After(_) => "SELECT min(position) FROM todos WHERE namespace = $1 AND position > $2 AND id <> $3",
+        }).await?;
 
+        let mut rebalanced = false;
+        let position = loop {
+            let target = match tx.query_opt(&target_statement, &[&namespace, &to.target()]).await? {
+                None => return Err(RecordNotFound.into()),
+                Some(row) => row.get::<_, i64>(0),
+            };
+            let neighbor: Option<i64> = tx.query_one(&neighbor_statement, &[&namespace, &target, &id]).await?.get(0);
+
+            let position = match to {
+                MoveTo::Before(_) => position_between(neighbor, Some(target)),
+                MoveTo::After(_) => position_between(Some(target), neighbor),
+            };
+
+            match position {
+                Some(position) => break position,
+                None if !rebalanced => {
+                    tx.execute(r#"
+                        UPDATE todos SET position = ranked.rn * $2
+                        FROM (SELECT id, row_number() OVER (ORDER BY position, id) AS rn
+                              FROM todos WHERE namespace = $1) ranked
+                        WHERE todos.namespace = $1 AND todos.id = ranked.id
+                    "#, &[&namespace, &POSITION_GAP]).await?;
+                    rebalanced = true;
+                }
+                None => anyhow::bail!("no room to move todo [{}] in namespace [{}]", id, namespace),
+            }
+        };
+
+        tx.execute(r#"
+            UPDATE todos SET position = $3, updated_at = $4, version = version + 1
+            WHERE namespace = $1 AND id = $2
+        "#, &[&namespace, &id, &position, &SystemTime::now()]).await?;
+        let todo = select_by_id(&tx, &(namespace, id)).await?;
+
+        tx.commit().await?;
+
+        Ok(todo)
+    }
+
+    /// the direct subtasks in the manual order
+    pub async fn query_children(&self, (namespace, id): TodoID) -> Result<Vec<Todo>> {
+        let client = self.db.get().await?;
+
+        let statement = client.prepare_cached(&format!(r#"
+            SELECT todos.*, {TAG_NAMES} AS tags FROM todos
+            WHERE namespace = $1 AND parent_id = $2
+            ORDER BY position, id
+        "#)).await?;
+
+        let rows = client.query(&statement, &[&namespace, &id]).await?;
+
+        rows.into_iter().map(todo_of).collect()
+    }
+
+    /// the ids deleted, their subtasks are left as top-level todos
+    pub async fn clear_todos(&self, namespace: String, ids: Vec<i32>) -> Result<Vec<i32>> {
+        let client = self.db.get().await?;
+
+        let statement = client
+            .prepare_cached("DELETE FROM todos WHERE namespace = $1 AND id = ANY($2) RETURNING id").await?;
+
+        let rows = client.query(&statement, &[&namespace, &ids]).await?;
+
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    /// the todos updated after `since` and the tombstones since then, in one snapshot
+    pub async fn query_changes(&self, namespace: String, since: Option<DateTime<Utc>>) -> Result<Changes> {
+        let mut client = self.db.get().await?;
+        let tx = client.build_transaction()
+            .isolation_level(IsolationLevel::RepeatableRead)
+            .read_only(true)
+            .start().await?;
+
+        // `updated_at` is set before the commit, so a write committed after the snapshot may be stamped
+        // earlier, `until` leaves it to the next changes, which may send some todos twice
+        let until: SystemTime = tx.query_one(
+            "SELECT (now() AT TIME ZONE 'UTC' - $1 * INTERVAL '1 second')::TIMESTAMP",
+            &[&(CHANGES_OVERLAP.as_secs() as f64)],
+        ).await?.get(0);
+        // the tombstones purged after `since` are missed, all the todos are sent instead
+        let horizon: Option<SystemTime> = tx.query_opt(
+            "SELECT purged_until FROM todo_tombstone_horizons WHERE namespace = $1",
+            &[&namespace],
+        ).await?.map(|row| row.get(0));
+        let since = since.map(SystemTime::from);
+        let full = since.is_none_or(|since| horizon.is_some_and(|horizon| since < horizon));
+        let since = if full { None } else { since };
+
+        let todos = tx.query(&format!(r#"
+            SELECT todos.*, {TAG_NAMES} AS tags FROM todos
+            WHERE namespace = $1 AND ($2::TIMESTAMP IS NULL OR updated_at > $2)
+            ORDER BY position, id
+        "#), &[&namespace, &since]).await?;
+
+        let deleted = match since {
+            None => vec![],
+            Some(since) => tx.query(
+                "SELECT id FROM todo_tombstones WHERE namespace = $1 AND deleted_at > $2 ORDER BY id",
+                &[&namespace, &since],
+            ).await?.iter().map(|row| row.get(0)).collect(),
+        };
+
+        tx.commit().await?;
+
+        Ok(Changes {
+            todos: todos.into_iter().map(todo_of).collect::<Result<Vec<Todo>>>()?,
+            deleted,
+            until: until.into(),
+            full,
+        })
+    }
+
+    /// `NOTIFY` the changes on `CHANNEL` at once, outside of a transaction
+    pub async fn notify(&self, changes: &[TodoChange]) -> Result<()> {
+        let client = self.db.get().await?;
+
+        let payloads = changes.iter()
+            .map(serde_json::to_string)
+            .collect::<serde_json::Result<Vec<String>>>()?;
+
+        client.execute("SELECT pg_notify($1, payload) FROM unnest($2::TEXT[]) payload", &[&CHANNEL, &payloads]).await?;
+
+        Ok(())
+    }
+}
+
+impl TodoRepository {
+    /// hard-delete the `Deleted` todos updated before `before`, counted by namespace
+    pub async fn purge_deleted(&self, before: DateTime<Utc>) -> Result<Vec<(String, u64)>> {
         let client = self.db.get().await?;
 
         let statement = client.prepare_cached(r#"
-                INSERT INTO todos (namespace, content, status, created_at, updated_at)
-                VALUES ($1, $2, $3, $4, $5)
-                RETURNING *
+            WITH purged AS (
+                DELETE FROM todos
+                WHERE status = $1 AND updated_at < $2
+                RETURNING namespace
+            )
+            SELECT namespace, count(*) AS rows FROM purged GROUP BY namespace
+        "#).await?;
+
+        let rows = client.query(&statement, &[
+            &TodoStatus::Deleted.to_string(),
+            &SystemTime::from(before),
+        ]).await?;
+
+        Ok(rows.iter()
+            .map(|r| (r.get("namespace"), r.get::<_, i64>("rows") as u64))
+            .collect())
+    }
+
+    /// forget the hard-deleted ids older than `before` and move the horizon of their namespaces past them,
+    /// the changes since before the horizon are all the todos
+    pub async fn purge_tombstones(&self, before: DateTime<Utc>) -> Result<u64> {
+        let client = self.db.get().await?;
+
+        let row = client.query_one(r#"
+            WITH purged AS (
+                DELETE FROM todo_tombstones WHERE deleted_at < $1
+                RETURNING namespace, deleted_at
+            ), horizons AS (
+                INSERT INTO todo_tombstone_horizons (namespace, purged_until)
+                SELECT namespace, max(deleted_at) FROM purged GROUP BY namespace
+                ON CONFLICT (namespace) DO UPDATE
+                SET purged_until = GREATEST(todo_tombstone_horizons.purged_until, EXCLUDED.purged_until)
+            )
+            SELECT count(*) FROM purged
+        "#, &[&SystemTime::from(before)]).await?;
+
+        Ok(row.get::<_, i64>(0) as u64)
+    }
+
+    #[cfg(test)]
+    pub async fn touch(&self, (namespace, id): TodoID, updated_at: DateTime<Utc>) -> Result<()> {
+        let client = self.db.get().await?;
+
+        client.execute(
+            "UPDATE todos SET updated_at = $3 WHERE namespace = $1 AND id = $2",
+            &[&namespace, &id, &SystemTime::from(updated_at)],
+        ).await?;
+
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub async fn touch_tombstone(&self, (namespace, id): TodoID, deleted_at: DateTime<Utc>) -> Result<()> {
+        let client = self.db.get().await?;
+
+        client.execute(
+            "UPDATE todo_tombstones SET deleted_at = $3 WHERE namespace = $1 AND id = $2",
+            &[&namespace, &id, &SystemTime::from(deleted_at)],
+        ).await?;
+
+        Ok(())
+    }
+}
+
+pub type Connection = deadpool_postgres::Object;
+
+/// the writes of a unit of work, nested ones are savepoints
+pub struct TodoTx<'a>(Transaction<'a>);
+
+impl<'a> TodoTx<'a> {
+    pub async fn begin(conn: &'a mut Connection) -> Result<Self> {
+        Ok(Self(conn.transaction().await?))
+    }
+
+    /// rolled back alone by `rollback`, or when dropped
+    pub async fn savepoint(&mut self) -> Result<TodoTx<'_>> {
+        Ok(TodoTx(self.0.transaction().await?))
+    }
+
+    pub async fn commit(self) -> Result<()> {
+        Ok(self.0.commit().await?)
+    }
+
+    pub async fn rollback(self) -> Result<()> {
+        Ok(self.0.rollback().await?)
+    }
+
+    pub async fn query_by_id(&self, id: &TodoID) -> Result<Todo> {
+        select_by_id(&self.0, id).await
+    }
+
+    /// a todo of the namespace with the same content and `created_at`
+    pub async fn query_duplicate(&self, namespace: &str, content: &str, created_at: DateTime<Utc>) -> Result<Option<Todo>> {
+        let statement = self.0.prepare_cached(&format!(r#"
+            SELECT todos.*, {TAG_NAMES} AS tags FROM todos
+            WHERE namespace = $1 AND content = $2 AND created_at = $3
+            ORDER BY id LIMIT 1
+        "#)).await?;
+
+        self.0.query_opt(&statement, &[&namespace, &content, &SystemTime::from(created_at)]).await?
+            .map(todo_of)
+            .transpose()
+    }
+
+    /// `NOTIFY` the change on `CHANNEL`, delivered once committed and dropped on rollback
+    pub async fn notify(&self, change: &TodoChange) -> Result<()> {
+        let payload = serde_json::to_string(change)?;
+
+        self.0.execute("SELECT pg_notify($1, $2)", &[&CHANNEL, &payload]).await?;
+
+        Ok(())
+    }
+
+    pub async fn insert_todo(&self, todo: Todo) -> Result<Todo> {
+        let tags = todo.tags.clone();
+        let entity = TodoEntity::from(todo);
+
+        let statement = self.0.prepare_cached(r#"
+                INSERT INTO todos (namespace, content, status, created_at, updated_at, due_at, priority, position, parent_id)
+                VALUES ($1::VARCHAR, $2, $3, $4, $5, $6, $7,
+                        COALESCE((SELECT min(position) FROM todos WHERE namespace = $1::VARCHAR), $8) - $8, $9)
+                RETURNING id
                 "#).await?;
 
-        let row = client.query_one(&statement,
+        let row = self.0.query_one(&statement,
                                    &[
                                        &entity.namespace,
                                        &entity.content,
                                        &entity.status,
                                        &entity.created_at,
-                                       &entity.updated_at
+                                       &entity.updated_at,
+                                       &entity.due_at,
+                                       &entity.priority,
+                                       &POSITION_GAP,
+                                       &entity.parent_id
                                    ]).await?;
+        let id = (entity.namespace, row.get(0));
 
-        let entity = TodoEntity::from_row(row).map(Todo::from)?;
-
-        Ok(entity)
+        set_tags(&self.0, &id, &tags).await?;
+        select_by_id(&self.0, &id).await
     }
 
+    /// write the todo read at `todo.version`, `TodoError::VersionConflict` when the row has been written since
     pub async fn update_todo(&self, todo: Todo) -> Result<Todo> {
+        let tags = todo.tags.clone();
         let entity = TodoEntity::from(todo);
 
-        let client = self.db.get().await?;
-
-        let statement = client.prepare_cached(r#"
-            UPDATE todos SET content = $3, status = $4, updated_at = $5
-            WHERE namespace = $1 AND id = $2
-            RETURNING *
+        let statement = self.0.prepare_cached(r#"
+            UPDATE todos SET content = $3, status = $4, updated_at = $5, due_at = $6, priority = $7,
+                             version = version + 1
+            WHERE namespace = $1 AND id = $2 AND version = $8
         "#).await?;
 
-        let row = client.query_one(
+        let rows = self.0.execute(
             &statement,
             &[
                 &entity.namespace,
                 &entity.id,
                 &entity.content,
                 &entity.status,
-                &entity.updated_at
+                &entity.updated_at,
+                &entity.due_at,
+                &entity.priority,
+                &entity.version
             ]).await?;
+        let id = (entity.namespace, entity.id);
+        if rows == 0 {
+            select_by_id(&self.0, &id).await?;
+            return Err(TodoError::VersionConflict(id.1).into());
+        }
+
+        set_tags(&self.0, &id, &tags).await?;
+        select_by_id(&self.0, &id).await
+    }
 
-        let entity = TodoEntity::from_row(row).map(Todo::from)?;
+    /// hard-delete the todo, its subtasks are left as top-level todos
+    pub async fn delete_todo(&self, (namespace, id): &TodoID) -> Result<()> {
+        let statement = self.0.prepare_cached("DELETE FROM todos WHERE namespace = $1 AND id = $2").await?;
 
-        Ok(entity)
+        match self.0.execute(&statement, &[namespace, id]).await? {
+            0 => Err(RecordNotFound.into()),
+            _ => Ok(()),
+        }
     }
 
-    pub async fn clear_todos(&self, namespace: String, ids: Vec<i32>) -> Result<bool> {
-        let client = self.db.get().await?;
+    /// the `Todo` subtasks at any depth
+    pub async fn count_open_subtasks(&self, (namespace, id): &TodoID) -> Result<i64> {
+        let statement = self.0.prepare_cached(&format!(
+            "{SUBTASKS} SELECT count(*) FROM subtasks WHERE status = 'Todo'")).await?;
 
-        let statement = client
-            .prepare_cached("DELETE FROM todos WHERE namespace = $1 AND id = ANY($2)").await?;
+        let row = self.0.query_one(&statement, &[namespace, id]).await?;
+
+        Ok(row.get(0))
+    }
+
+    /// move the `Todo` subtasks at any depth through `steps`, each one a legal transition from the one before,
+    /// the ids of the subtasks closed
+    pub async fn close_subtasks(&self, (namespace, id): &TodoID, steps: &[TodoStatus]) -> Result<Vec<i32>> {
+        let Some((first, rest)) = steps.split_first() else { return Ok(vec![]); };
+        let now = SystemTime::now();
+
+        let statement = self.0.prepare_cached(&format!(r#"
+            {SUBTASKS}
+            UPDATE todos SET status = $4, updated_at = $3, version = version + 1
+            FROM subtasks
+            WHERE todos.namespace = subtasks.namespace AND todos.id = subtasks.id
+              AND subtasks.status = 'Todo'
+            RETURNING todos.id
+        "#)).await?;
+        let rows = self.0.query(&statement, &[namespace, id, &now, &first.to_string()]).await?;
+        let closed = rows.iter().map(|row| row.get(0)).collect::<Vec<i32>>();
+
+        let statement = self.0.prepare_cached(r#"
+            UPDATE todos SET status = $4, updated_at = $3, version = version + 1
+            WHERE namespace = $1 AND id = ANY($2)
+        "#).await?;
+        for status in rest {
+            self.0.execute(&statement, &[namespace, &closed, &now, &status.to_string()]).await?;
+        }
+
+        Ok(closed)
+    }
+}
 
-        let rows = client.execute(&statement, &[&namespace, &ids]).await?;
+async fn select_by_id(tx: &Transaction<'_>, (namespace, id): &TodoID) -> Result<Todo> {
+    let statement = tx.prepare_cached(&format!(
+        "SELECT todos.*, {TAG_NAMES} AS tags FROM todos WHERE namespace = $1 AND id = $2")).await?;
 
-        Ok(rows as usize == ids.len())
+    match tx.query_opt(&statement, &[namespace, id]).await? {
+        None => Err(RecordNotFound.into()),
+        Some(row) => todo_of(row),
     }
 }
 
+/// replace the tags of the todo, the missing tags of the namespace are created
+async fn set_tags(tx: &Transaction<'_>, (namespace, id): &TodoID, tags: &[String]) -> Result<()> {
+    let statement = tx.prepare_cached(r#"
+        INSERT INTO tags (namespace, name, created_at)
+        SELECT $1::VARCHAR, name, $3 FROM unnest($2::VARCHAR[]) name
+        ON CONFLICT (namespace, name) DO NOTHING
+    "#).await?;
+    tx.execute(&statement, &[namespace, &tags, &SystemTime::now()]).await?;
+
+    let statement = tx.prepare_cached(r#"
+        DELETE FROM todo_tags
+        WHERE namespace = $1 AND todo_id = $2
+          AND tag_id NOT IN (SELECT id FROM tags WHERE namespace = $1 AND name = ANY($3))
+    "#).await?;
+    tx.execute(&statement, &[namespace, id, &tags]).await?;
+
+    let statement = tx.prepare_cached(r#"
+        INSERT INTO todo_tags (namespace, todo_id, tag_id)
+        SELECT $1::VARCHAR, $2, id FROM tags WHERE namespace = $1::VARCHAR AND name = ANY($3)
+        ON CONFLICT DO NOTHING
+    "#).await?;
+    tx.execute(&statement, &[namespace, id, &tags]).await?;
+
+    Ok(())
+}
+
+/// `todos` row with the `tags` column
+fn todo_of(row: Row) -> Result<Todo> {
+    let tags = row.try_get::<_, Vec<String>>("tags").unwrap_or_default();
+
+    let mut todo = Todo::from(TodoEntity::from_row(row)?);
+    todo.tags = tags;
+
+    Ok(todo)
+}
+
 impl From<Todo> for TodoEntity {
     fn from(todo: Todo) -> Self {
         Self {
@@ -148,6 +627,11 @@ impl From<Todo> for TodoEntity {
             status: todo.status.to_string(),
             created_at: SystemTime::from(todo.created_at),
             updated_at: SystemTime::now(),
+            due_at: todo.due_at.map(SystemTime::from),
+            priority: todo.priority as i16,
+            position: todo.position,
+            parent_id: todo.parent_id,
+            version: todo.version,
         }
     }
 }
@@ -160,6 +644,13 @@ impl From<TodoEntity> for Todo {
             status: TodoStatus::from_str(&todo.status).unwrap(),
             created_at: DateTime::from(todo.created_at),
             updated_at: DateTime::from(todo.updated_at),
+            due_at: todo.due_at.map(DateTime::from),
+            priority: TodoPriority::try_from(todo.priority).unwrap(),
+            position: todo.position,
+            tags: vec![],
+            parent_id: todo.parent_id,
+            version: todo.version,
+            hit: None,
         }
     }
 }
@@ -167,8 +658,13 @@ impl From<TodoEntity> for Todo {
 #[cfg(test)]
 mod tests {
     use deadpool_postgres::Pool;
+    use common::model::{TodoPriority, TodoSort, TodoStatus};
+    use chrono::{Duration, Utc};
+
+    use crate::domains::todo_domain::{Cursor, ListTodo, MoveTo, Todo, TodoError};
     use crate::infra::{config, db};
-    use super::TodoRepository;
+    use crate::infra::db::RecordNotFound;
+    use super::{TodoRepository, TodoTx};
 
     fn test_db() -> Pool {
         db::must_init(&config::must_get().db)
@@ -178,7 +674,11 @@ mod tests {
         TodoRepository::new(test_db())
     }
 
-    static NS: &'static str = "default";
+    static NS: &str = "default";
+
+    fn page(cursor: Option<Cursor>, limit: i64) -> ListTodo {
+        ListTodo { cursor, limit, ..Default::default() }
+    }
 
     #[actix_web::test]
     async fn query_by_id() {
@@ -188,7 +688,7 @@ mod tests {
         assert!(not_found.is_err());
         assert!(not_found.unwrap_err().is::<RecordNotFound>());
 
-        let created = repo.insert_todo(Todo::create(&NS, "new todo")).await
+        let created = repo.insert_todo(Todo::create(NS, "new todo")).await
             .unwrap();
 
         let found = repo.query_by_id(created.id).await.unwrap();
@@ -199,13 +699,282 @@ mod tests {
     #[actix_web::test]
     async fn query_todos() {
         let repository = repo();
-        let todos = repository.query_todos(NS.to_string(), None).await;
+        let todos = repository.query_todos(NS.to_string(), &ListTodo { limit: 10, ..Default::default() }).await;
         println!("{:?}", todos);
     }
 
+    #[actix_web::test]
+    async fn query_todos_by_cursor() {
+        let repo = repo();
+        let ns = "testing/repository/cursor";
+
+        let mut ids = vec![];
+        for content in ["first", "second", "third"] {
+            ids.push(repo.insert_todo(Todo::create(ns, content)).await.unwrap().id.1);
+        }
+
+        let first = repo.query_todos(ns.to_string(), &page(None, 2)).await.unwrap();
+        assert_eq!(vec![ids[2], ids[1]], first.iter().map(|it| it.id.1).collect::<Vec<i32>>());
+
+        let next = repo.query_todos(ns.to_string(), &page(Some(Cursor::Id(ids[1])), 2)).await.unwrap();
+        assert_eq!(vec![ids[0]], next.iter().map(|it| it.id.1).collect::<Vec<i32>>());
+
+        repo.clear_todos(ns.to_string(), ids).await.unwrap();
+    }
+
+    #[actix_web::test]
+    async fn search_todos() {
+        let repo = repo();
+        let ns = "testing/repository/search";
+
+        let mut ids = vec![];
+        for content in ["buy milk", "write the release notes", "release the milk truck"] {
+            ids.push(repo.insert_todo(Todo::create(ns, content)).await.unwrap().id.1);
+        }
+
+        let hits = repo.search_todos(ns.to_string(), "milk", &page(None, 10)).await.unwrap();
+        assert_eq!(2, hits.len());
+        let snippet = &hits[0].hit.as_ref().unwrap().snippet;
+        assert!(snippet.contains("<mark>milk</mark>"), "{}", snippet);
+
+        let hits = repo.search_todos(ns.to_string(), "release", &page(None, 1)).await.unwrap();
+        assert_eq!(1, hits.len());
+        let cursor = Cursor::after(&hits[0], TodoSort::Newest);
+        let next = repo.search_todos(ns.to_string(), "release", &page(Some(cursor), 1)).await.unwrap();
+        assert_eq!(1, next.len());
+        assert_ne!(hits[0].id, next[0].id);
+
+        // the next page does not depend on the hit of the cursor
+        repo.clear_todos(ns.to_string(), vec![hits[0].id.1]).await.unwrap();
+        let after_cleared = repo.search_todos(ns.to_string(), "release", &page(Some(cursor), 1)).await.unwrap();
+        assert_eq!(next[0].id, after_cleared[0].id);
+
+        let none = repo.search_todos(ns.to_string(), "nothing", &page(None, 10)).await.unwrap();
+        assert!(none.is_empty());
+
+        repo.clear_todos(ns.to_string(), ids).await.unwrap();
+    }
+
+    #[actix_web::test]
+    async fn query_todos_by_due_at() {
+        let repo = repo();
+        let ns = "testing/repository/due_at";
+        let now = Utc::now();
+
+        let mut ids = vec![];
+        for (content, due_at) in [
+            ("no due date", None),
+            ("overdue", Some(now - Duration::days(1))),
+            ("due tomorrow", Some(now + Duration::days(1))),
+        ] {
+            let mut todo = Todo::create(ns, content);
+            todo.due_at = due_at;
+            ids.push(repo.insert_todo(todo).await.unwrap().id.1);
+        }
+
+        let overdue = repo.query_todos(ns.to_string(), &ListTodo {
+            overdue: Some(true),
+            ..page(None, 10)
+        }).await.unwrap();
+        assert_eq!(vec![ids[1]], overdue.iter().map(|it| it.id.1).collect::<Vec<i32>>());
+
+        let not_overdue = repo.query_todos(ns.to_string(), &ListTodo {
+            overdue: Some(false),
+            ..page(None, 10)
+        }).await.unwrap();
+        assert_eq!(vec![ids[2], ids[0]], not_overdue.iter().map(|it| it.id.1).collect::<Vec<i32>>());
+
+        let due_before = repo.query_todos(ns.to_string(), &ListTodo {
+            due_before: Some(now + Duration::days(2)),
+            ..page(None, 10)
+        }).await.unwrap();
+        assert_eq!(vec![ids[2], ids[1]], due_before.iter().map(|it| it.id.1).collect::<Vec<i32>>());
+
+        let found = repo.query_by_id((ns.to_string(), ids[1])).await.unwrap();
+        assert_eq!(Some((now - Duration::days(1)).timestamp_micros()), found.due_at.map(|it| it.timestamp_micros()));
+
+        repo.clear_todos(ns.to_string(), ids).await.unwrap();
+    }
+
+    #[actix_web::test]
+    async fn move_todo() {
+        let repo = repo();
+        let ns = "testing/repository/move";
+
+        let mut ids = vec![];
+        for content in ["a", "b", "c"] {
+            ids.push(repo.insert_todo(Todo::create(ns, content)).await.unwrap().id.1);
+        }
+        let manual = || async {
+            repo.query_todos(ns.to_string(), &ListTodo { sort: TodoSort::Manual, ..page(None, 10) }).await.unwrap()
+                .into_iter().map(|it| it.content).collect::<Vec<String>>()
+        };
+        assert_eq!(vec!["c", "b", "a"], manual().await);
+
+        repo.move_todo((ns.to_string(), ids[0]), MoveTo::Before(ids[2]), None).await.unwrap();
+        assert_eq!(vec!["a", "c", "b"], manual().await);
+
+        repo.move_todo((ns.to_string(), ids[2]), MoveTo::After(ids[1]), None).await.unwrap();
+        assert_eq!(vec!["a", "b", "c"], manual().await);
+
+        // no room between the adjacent positions of `a` and `b`
+        let client = repo.db.get().await.unwrap();
+        client.execute("UPDATE todos SET position = id - $2 WHERE namespace = $1", &[&ns, &ids[0]]).await.unwrap();
+        repo.move_todo((ns.to_string(), ids[2]), MoveTo::After(ids[0]), None).await.unwrap();
+        assert_eq!(vec!["a", "c", "b"], manual().await);
+
+        // a stale version moves nothing
+        let moved = repo.query_by_id((ns.to_string(), ids[2])).await.unwrap();
+        let err = repo.move_todo(moved.id.clone(), MoveTo::After(ids[1]), Some(moved.version - 1)).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<TodoError>(), Some(TodoError::VersionConflict(_))));
+        assert_eq!(vec!["a", "c", "b"], manual().await);
+        let moved = repo.move_todo(moved.id.clone(), MoveTo::After(ids[1]), Some(moved.version)).await.unwrap();
+        assert_eq!(vec!["a", "b", "c"], manual().await);
+        repo.move_todo(moved.id, MoveTo::After(ids[0]), None).await.unwrap();
+
+        // the next page does not depend on the todo of the cursor
+        let first = repo.query_todos(ns.to_string(), &ListTodo { sort: TodoSort::Manual, ..page(None, 2) }).await.unwrap();
+        let cursor = Cursor::after(&first[1], TodoSort::Manual);
+        repo.clear_todos(ns.to_string(), vec![first[1].id.1]).await.unwrap();
+        let next = repo.query_todos(ns.to_string(), &ListTodo { sort: TodoSort::Manual, ..page(Some(cursor), 2) }).await.unwrap();
+        assert_eq!(vec!["b"], next.into_iter().map(|it| it.content).collect::<Vec<String>>());
+
+        repo.clear_todos(ns.to_string(), ids).await.unwrap();
+    }
+
+    #[actix_web::test]
+    async fn query_todos_by_priority() {
+        let repo = repo();
+        let ns = "testing/repository/priority";
+
+        let mut ids = vec![];
+        for priority in [TodoPriority::High, TodoPriority::Low, TodoPriority::Normal] {
+            let mut todo = Todo::create(ns, &priority.to_string());
+            todo.priority = priority;
+            ids.push(repo.insert_todo(todo).await.unwrap().id.1);
+        }
+
+        let sorted = |cursor| ListTodo { sort: TodoSort::Priority, ..page(cursor, 2) };
+        let first = repo.query_todos(ns.to_string(), &sorted(None)).await.unwrap();
+        assert_eq!(vec![ids[0], ids[2]], first.iter().map(|it| it.id.1).collect::<Vec<i32>>());
+
+        let cursor = Cursor::after(&first[1], TodoSort::Priority);
+        let next = repo.query_todos(ns.to_string(), &sorted(Some(cursor))).await.unwrap();
+        assert_eq!(vec![ids[1]], next.iter().map(|it| it.id.1).collect::<Vec<i32>>());
+
+        // the next page does not depend on the todo of the cursor
+        repo.clear_todos(ns.to_string(), vec![ids[2]]).await.unwrap();
+        let next = repo.query_todos(ns.to_string(), &sorted(Some(cursor))).await.unwrap();
+        assert_eq!(vec![ids[1]], next.iter().map(|it| it.id.1).collect::<Vec<i32>>());
+
+        repo.clear_todos(ns.to_string(), ids).await.unwrap();
+    }
+
+    #[actix_web::test]
+    async fn todo_tags() {
+        let repo = repo();
+        let ns = "testing/repository/todo_tags";
+
+        let mut todo = Todo::create(ns, "tagged");
+        todo.tags = vec!["backend".to_string(), "release-1.2".to_string()];
+        let mut tagged = repo.insert_todo(todo).await.unwrap();
+        assert_eq!(vec!["backend", "release-1.2"], tagged.tags);
+
+        let untagged = repo.insert_todo(Todo::create(ns, "untagged")).await.unwrap();
+        assert!(untagged.tags.is_empty());
+
+        let found = repo.query_todos(ns.to_string(), &ListTodo {
+            tag: Some("backend".to_string()),
+            ..page(None, 10)
+        }).await.unwrap();
+        assert_eq!(vec![tagged.id.1], found.iter().map(|it| it.id.1).collect::<Vec<i32>>());
+
+        tagged.tags = vec!["frontend".to_string()];
+        let updated = repo.update_todo(tagged).await.unwrap();
+        assert_eq!(vec!["frontend"], updated.tags);
+
+        let found = repo.search_todos(ns.to_string(), "tagged", &ListTodo {
+            tag: Some("frontend".to_string()),
+            ..page(None, 10)
+        }).await.unwrap();
+        assert_eq!(vec![updated.id.1], found.iter().map(|it| it.id.1).collect::<Vec<i32>>());
+        assert_eq!(vec!["frontend"], found[0].tags);
+
+        repo.clear_todos(ns.to_string(), vec![updated.id.1, untagged.id.1]).await.unwrap();
+        let client = repo.db.get().await.unwrap();
+        client.execute("DELETE FROM tags WHERE namespace = $1", &[&ns]).await.unwrap();
+    }
+
+    #[actix_web::test]
+    async fn subtasks() {
+        let repo = repo();
+        let ns = "testing/repository/subtasks";
+        let subtask_of = |parent: &Todo, content: &str| {
+            let mut todo = Todo::create(ns, content);
+            todo.parent_id = Some(parent.id.1);
+            todo
+        };
+
+        let parent = repo.insert_todo(Todo::create(ns, "parent")).await.unwrap();
+        let child = repo.insert_todo(subtask_of(&parent, "child")).await.unwrap();
+        let mut done = repo.insert_todo(subtask_of(&parent, "done child")).await.unwrap();
+        let grandchild = repo.insert_todo(subtask_of(&child, "grandchild")).await.unwrap();
+        assert_eq!(Some(parent.id.1), child.parent_id);
+
+        done.status = TodoStatus::Done;
+        repo.update_todo(done).await.unwrap();
+
+        let children = repo.query_children(parent.id.clone()).await.unwrap();
+        assert_eq!(vec!["done child", "child"], children.iter().map(|it| it.content.as_str()).collect::<Vec<&str>>());
+
+        let mut conn = repo.connect().await.unwrap();
+        let tx = TodoTx::begin(&mut conn).await.unwrap();
+        assert_eq!(2, tx.count_open_subtasks(&parent.id).await.unwrap());
+        assert_eq!(1, tx.count_open_subtasks(&child.id).await.unwrap());
+
+        let mut completed = tx.close_subtasks(&parent.id, &[TodoStatus::Done]).await.unwrap();
+        completed.sort();
+        assert_eq!(vec![child.id.1, grandchild.id.1], completed);
+        assert_eq!(0, tx.count_open_subtasks(&parent.id).await.unwrap());
+        tx.commit().await.unwrap();
+        assert_eq!(TodoStatus::Done, repo.query_by_id(grandchild.id.clone()).await.unwrap().status);
+
+        // subtasks outlive their parent as top-level todos
+        repo.clear_todos(ns.to_string(), vec![parent.id.1]).await.unwrap();
+        assert_eq!(None, repo.query_by_id(child.id.clone()).await.unwrap().parent_id);
+        assert_eq!(Some(child.id.1), repo.query_by_id(grandchild.id.clone()).await.unwrap().parent_id);
+        repo.clear_todos(ns.to_string(), children.iter().map(|it| it.id.1).chain([grandchild.id.1]).collect()).await.unwrap();
+    }
+
+    #[actix_web::test]
+    async fn savepoint() {
+        let repo = repo();
+        let ns = "testing/repository/savepoint";
+
+        let mut conn = repo.connect().await.unwrap();
+        let mut tx = TodoTx::begin(&mut conn).await.unwrap();
+
+        let savepoint = tx.savepoint().await.unwrap();
+        let kept = savepoint.insert_todo(Todo::create(ns, "kept")).await.unwrap();
+        savepoint.commit().await.unwrap();
+
+        let savepoint = tx.savepoint().await.unwrap();
+        let dropped = savepoint.insert_todo(Todo::create(ns, "dropped")).await.unwrap();
+        savepoint.rollback().await.unwrap();
+
+        let error = tx.delete_todo(&(ns.to_string(), dropped.id.1)).await.unwrap_err();
+        assert!(error.is::<RecordNotFound>());
+        tx.commit().await.unwrap();
+
+        assert_eq!("kept", repo.query_by_id(kept.id.clone()).await.unwrap().content);
+        assert!(repo.query_by_id(dropped.id).await.unwrap_err().is::<RecordNotFound>());
+
+        repo.clear_todos(ns.to_string(), vec![kept.id.1]).await.unwrap();
+    }
+
     #[actix_web::test]
     async fn insert_todo() {
-        let todo = Todo::create(&NS, "new todo");
+        let todo = Todo::create(NS, "new todo");
         let created = repo().insert_todo(todo).await.unwrap();
 
         assert_ne!(created.id.1, 0);
@@ -218,7 +987,7 @@ mod tests {
     async fn update_todo() {
         let repo = repo();
 
-        let todo = Todo::create(&NS, "new todo");
+        let todo = Todo::create(NS, "new todo");
         let mut created = repo.insert_todo(todo).await.unwrap();
 
         created.content = "updated todo".to_string();
@@ -233,16 +1002,85 @@ mod tests {
         assert_eq!(updated.status, TodoStatus::Done);
     }
 
+    #[actix_web::test]
+    async fn update_todo_stale() {
+        let repo = repo();
+
+        let todo = Todo::create(NS, "new todo");
+        let created = repo.insert_todo(todo).await.unwrap();
+
+        let updated = repo.update_todo(created.clone()).await.unwrap();
+        assert_eq!(updated.version, created.version + 1);
+
+        let err = repo.update_todo(created.clone()).await.unwrap_err();
+
+        assert!(matches!(err.downcast_ref::<TodoError>(), Some(TodoError::VersionConflict(id)) if *id == created.id.1));
+    }
+
     #[actix_web::test]
     async fn clear_todos() {
         let mut ids_to_clear = vec![];
         let repo = repo();
-        let todo = Todo::create(&NS, "new todo");
+        let todo = Todo::create(NS, "new todo");
         let created = repo.insert_todo(todo).await.unwrap();
         ids_to_clear.push(created.id.1);
 
-        let deleted = repo.clear_todos(NS.to_string(), ids_to_clear).await.unwrap();
+        let deleted = repo.clear_todos(NS.to_string(), ids_to_clear.clone()).await.unwrap();
+
+        assert_eq!(ids_to_clear, deleted);
+    }
+
+    #[actix_web::test]
+    async fn query_changes() {
+        let repo = repo();
+        let ns = "testing/changes".to_string();
+        let since = Utc::now() - Duration::seconds(1);
+        let kept = repo.insert_todo(Todo::create(&ns, "kept")).await.unwrap();
+        let deleted = repo.insert_todo(Todo::create(&ns, "deleted")).await.unwrap();
+        repo.clear_todos(ns.clone(), vec![deleted.id.1]).await.unwrap();
+
+        let changes = repo.query_changes(ns.clone(), Some(since)).await.unwrap();
+
+        assert!(changes.todos.iter().any(|it| it.id == kept.id));
+        assert!(changes.todos.iter().all(|it| it.id != deleted.id));
+        assert!(changes.deleted.contains(&deleted.id.1));
+        assert!(changes.until < Utc::now());
+
+        let changes = repo.query_changes(ns.clone(), None).await.unwrap();
+
+        assert!(changes.todos.iter().any(|it| it.id == kept.id));
+        assert!(changes.deleted.is_empty());
+        assert!(changes.full);
+
+        let changes = repo.query_changes(ns, Some(Utc::now() + Duration::hours(1))).await.unwrap();
+
+        assert!(changes.todos.is_empty());
+        assert!(changes.deleted.is_empty());
+        assert!(!changes.full);
+    }
+
+    #[actix_web::test]
+    async fn query_changes_past_horizon() {
+        let repo = repo();
+        let ns = "testing/changes/horizon".to_string();
+        let kept = repo.insert_todo(Todo::create(&ns, "kept")).await.unwrap();
+        let deleted = repo.insert_todo(Todo::create(&ns, "deleted")).await.unwrap();
+        repo.clear_todos(ns.clone(), vec![deleted.id.1]).await.unwrap();
+        repo.touch_tombstone(deleted.id.clone(), Utc::now() - Duration::days(400)).await.unwrap();
+
+        // older than any tombstone the other tests leave, `PurgeJob` may purge it first
+        repo.purge_tombstones(Utc::now() - Duration::days(365)).await.unwrap();
+
+        let changes = repo.query_changes(ns.clone(), Some(Utc::now() - Duration::days(500))).await.unwrap();
+
+        assert!(changes.full);
+        assert!(changes.todos.iter().any(|it| it.id == kept.id));
+        assert!(changes.deleted.is_empty());
+
+        let changes = repo.query_changes(ns.clone(), Some(Utc::now() - Duration::days(300))).await.unwrap();
+
+        assert!(!changes.full);
 
-        assert!(deleted);
+        repo.clear_todos(ns, vec![kept.id.1]).await.unwrap();
     }
 }
@@ -0,0 +1,207 @@
+use std::fmt::{Display, Formatter};
+
+use actix_web::{HttpResponse, ResponseError};
+use actix_web::http::StatusCode;
+use actix_web::http::header::WWW_AUTHENTICATE;
+
+use common::model::{ErrorCode, ErrorResponse};
+
+use crate::domains::auth_domain::AuthError;
+use crate::domains::tag_domain::TagError;
+use crate::domains::todo_domain::TodoError;
+use crate::infra::db::RecordNotFound;
+
+/// error of the handlers, responds as `ErrorResponse` json
+#[derive(Debug)]
+pub struct ApiError {
+    code: ErrorCode,
+    message: String,
+}
+
+impl ApiError {
+    pub fn new(code: ErrorCode, message: impl ToString) -> Self {
+        Self { code, message: message.to_string() }
+    }
+
+    pub fn validation(message: impl ToString) -> Self {
+        Self::new(ErrorCode::ValidationFailed, message)
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self.code {
+            ErrorCode::TodoNotFound | ErrorCode::TagNotFound => StatusCode::NOT_FOUND,
+            ErrorCode::MemberNotFound | ErrorCode::InvitationNotFound | ErrorCode::ApiKeyNotFound => StatusCode::NOT_FOUND,
+            ErrorCode::TagAlreadyExists => StatusCode::CONFLICT,
+            ErrorCode::InvalidStatusTransition | ErrorCode::OpenSubtasks | ErrorCode::ClosedParent => StatusCode::CONFLICT,
+            ErrorCode::VersionConflict => StatusCode::PRECONDITION_FAILED,
+            ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCode::Forbidden => StatusCode::FORBIDDEN,
+            ErrorCode::UserAlreadyExists | ErrorCode::NamespaceAlreadyOwned | ErrorCode::LastOwner => StatusCode::CONFLICT,
+            ErrorCode::NamespaceNotEmpty => StatusCode::CONFLICT,
+            ErrorCode::ValidationFailed => StatusCode::BAD_REQUEST,
+            ErrorCode::DbUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut response = HttpResponse::build(self.status_code());
+        if self.code == ErrorCode::Unauthorized {
+            response.insert_header((WWW_AUTHENTICATE, "Bearer"));
+        }
+
+        response.json(ErrorResponse {
+            code: self.code,
+            message: self.message.clone(),
+        })
+    }
+}
+
+impl From<ApiError> for ErrorResponse {
+    fn from(err: ApiError) -> Self {
+        Self { code: err.code, message: err.message }
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(err: anyhow::Error) -> Self {
+        let code = if let Some(err) = err.downcast_ref::<TodoError>() {
+            match err {
+                TodoError::TodoNotFound(_) => ErrorCode::TodoNotFound,
+                TodoError::InvalidStatusTransition(..) => ErrorCode::InvalidStatusTransition,
+                TodoError::InvalidStatusFromStr(_) => ErrorCode::InternalError,
+                TodoError::ParentNotFound(_) => ErrorCode::ValidationFailed,
+                TodoError::OpenSubtasks(..) => ErrorCode::OpenSubtasks,
+                TodoError::ClosedParent(..) => ErrorCode::ClosedParent,
+                TodoError::VersionConflict(_) => ErrorCode::VersionConflict,
+                TodoError::InvalidCursor(_) => ErrorCode::ValidationFailed,
+            }
+        } else if let Some(err) = err.downcast_ref::<TagError>() {
+            match err {
+                TagError::TagNotFound(_) => ErrorCode::TagNotFound,
+                TagError::TagAlreadyExists(_) => ErrorCode::TagAlreadyExists,
+                TagError::InvalidTagName(_) => ErrorCode::ValidationFailed,
+            }
+        } else if let Some(err) = err.downcast_ref::<AuthError>() {
+            match err {
+                AuthError::InvalidCredentials | AuthError::Unauthenticated => ErrorCode::Unauthorized,
+                AuthError::Forbidden(_) | AuthError::RoleRequired(..) => ErrorCode::Forbidden,
+                AuthError::MemberNotFound(_) => ErrorCode::MemberNotFound,
+                AuthError::InvitationNotFound => ErrorCode::InvitationNotFound,
+                AuthError::LastOwner(_) => ErrorCode::LastOwner,
+                AuthError::ApiKeyNotFound(_) => ErrorCode::ApiKeyNotFound,
+                AuthError::UserAlreadyExists(_) => ErrorCode::UserAlreadyExists,
+                AuthError::NamespaceAlreadyOwned(_) => ErrorCode::NamespaceAlreadyOwned,
+                AuthError::NamespaceNotEmpty(_) => ErrorCode::NamespaceNotEmpty,
+                AuthError::InvalidUsername(_) | AuthError::InvalidPassword | AuthError::InvalidNamespace(_) => ErrorCode::ValidationFailed,
+                AuthError::InvalidApiKeyName(_) | AuthError::InvalidApiKeyRole(_) => ErrorCode::ValidationFailed,
+                AuthError::MalformedNamespace(_) => ErrorCode::ValidationFailed,
+            }
+        } else if err.is::<RecordNotFound>() {
+            ErrorCode::TodoNotFound
+        } else if is_db_unavailable(&err) {
+            ErrorCode::DbUnavailable
+        } else {
+            ErrorCode::InternalError
+        };
+
+        // the cause of a 5xx is for the log only, it may tell the tables, constraints or connection of the db
+        match code {
+            ErrorCode::InternalError => {
+                log::error!("{:?}", err);
+                Self::new(code, "internal error")
+            }
+            ErrorCode::DbUnavailable => {
+                log::error!("{:?}", err);
+                Self::new(code, "database unavailable, try again later")
+            }
+            _ => Self::new(code, err),
+        }
+    }
+}
+
+/// pool errors, or postgres errors without a sql state, are connection failures
+fn is_db_unavailable(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause.is::<deadpool_postgres::PoolError>() ||
+            cause.downcast_ref::<tokio_postgres::Error>()
+                .is_some_and(|e| e.code().is_none() && e.as_db_error().is_none())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::ResponseError;
+    use actix_web::http::StatusCode;
+    use common::model::{ErrorCode, NamespaceRole, TodoStatus};
+
+    use crate::domains::auth_domain::AuthError;
+    use crate::domains::tag_domain::TagError;
+    use crate::domains::todo_domain::TodoError;
+    use crate::infra::db::RecordNotFound;
+    use super::ApiError;
+
+    #[test]
+    fn from_anyhow() {
+        let cases = [
+            (anyhow::Error::from(TodoError::TodoNotFound(1)), ErrorCode::TodoNotFound, StatusCode::NOT_FOUND),
+            (TodoError::InvalidStatusTransition(TodoStatus::Todo, TodoStatus::Deleted).into(),
+             ErrorCode::InvalidStatusTransition, StatusCode::CONFLICT),
+            (TodoError::ParentNotFound(1).into(), ErrorCode::ValidationFailed, StatusCode::BAD_REQUEST),
+            (TodoError::OpenSubtasks(1, 2).into(), ErrorCode::OpenSubtasks, StatusCode::CONFLICT),
+            (TodoError::ClosedParent(1, TodoStatus::Done).into(), ErrorCode::ClosedParent, StatusCode::CONFLICT),
+            (TodoError::VersionConflict(1).into(), ErrorCode::VersionConflict, StatusCode::PRECONDITION_FAILED),
+            (RecordNotFound.into(), ErrorCode::TodoNotFound, StatusCode::NOT_FOUND),
+            (TagError::TagNotFound(1).into(), ErrorCode::TagNotFound, StatusCode::NOT_FOUND),
+            (TagError::TagAlreadyExists("a".to_string()).into(), ErrorCode::TagAlreadyExists, StatusCode::CONFLICT),
+            (TagError::InvalidTagName("".to_string()).into(), ErrorCode::ValidationFailed, StatusCode::BAD_REQUEST),
+            (AuthError::InvalidCredentials.into(), ErrorCode::Unauthorized, StatusCode::UNAUTHORIZED),
+            (AuthError::Forbidden("a".to_string()).into(), ErrorCode::Forbidden, StatusCode::FORBIDDEN),
+            (AuthError::RoleRequired("a".to_string(), NamespaceRole::Editor).into(), ErrorCode::Forbidden, StatusCode::FORBIDDEN),
+            (AuthError::LastOwner("a".to_string()).into(), ErrorCode::LastOwner, StatusCode::CONFLICT),
+            (AuthError::InvitationNotFound.into(), ErrorCode::InvitationNotFound, StatusCode::NOT_FOUND),
+            (AuthError::ApiKeyNotFound(1).into(), ErrorCode::ApiKeyNotFound, StatusCode::NOT_FOUND),
+            (AuthError::InvalidApiKeyRole(NamespaceRole::Owner).into(), ErrorCode::ValidationFailed, StatusCode::BAD_REQUEST),
+            (AuthError::UserAlreadyExists("a".to_string()).into(), ErrorCode::UserAlreadyExists, StatusCode::CONFLICT),
+            (AuthError::NamespaceNotEmpty("a".to_string()).into(), ErrorCode::NamespaceNotEmpty, StatusCode::CONFLICT),
+            (AuthError::InvalidPassword.into(), ErrorCode::ValidationFailed, StatusCode::BAD_REQUEST),
+            (AuthError::MalformedNamespace("a b".to_string()).into(), ErrorCode::ValidationFailed, StatusCode::BAD_REQUEST),
+            (anyhow::anyhow!("unknown"), ErrorCode::InternalError, StatusCode::INTERNAL_SERVER_ERROR),
+            (deadpool_postgres::PoolError::Closed.into(), ErrorCode::DbUnavailable, StatusCode::SERVICE_UNAVAILABLE),
+        ];
+
+        for (err, code, status) in cases {
+            let err = ApiError::from(err);
+            assert_eq!(code, err.code());
+            assert_eq!(status, err.status_code());
+        }
+    }
+
+    #[test]
+    fn from_anyhow_server_error() {
+        let err = ApiError::from(anyhow::anyhow!("relation \"todos\" does not exist"));
+        assert_eq!("internal error", err.message);
+
+        let err = ApiError::from(anyhow::Error::from(deadpool_postgres::PoolError::Closed).context("connect to 10.0.0.1"));
+        assert_eq!(ErrorCode::DbUnavailable, err.code());
+        assert!(!err.message.contains("10.0.0.1"));
+
+        // a 4xx tells what is wrong with the request
+        let err = ApiError::from(anyhow::Error::from(TodoError::TodoNotFound(1)));
+        assert_eq!("Todo [id: 1] not found.", err.message);
+    }
+}
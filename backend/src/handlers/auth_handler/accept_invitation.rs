@@ -0,0 +1,53 @@
+use actix_web::{HttpResponse, web};
+use common::model::InvitationRequest;
+use crate::domains::auth_domain::AuthDomainTrait;
+use crate::handlers::{ApiError, Caller};
+use super::user_response;
+
+/// join the namespace of an invitation, its token is used up
+pub async fn accept_invitation(
+    domain: web::Data<dyn AuthDomainTrait>,
+    caller: web::ReqData<Caller>,
+    body: web::Json<InvitationRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let user = caller.user()?.clone();
+
+    domain.accept_invitation(&user, &body.token).await?;
+
+    Ok(HttpResponse::Ok().json(user_response(domain.as_ref(), user).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+    use serde_json::json;
+
+    use crate::handlers::auth_handler::configure;
+    use crate::tests::{json_body, test_request_at};
+
+    #[actix_web::test]
+    async fn test_accept_invitation() {
+        let request = test::TestRequest::post()
+            .uri("/auth/invitations")
+            .insert_header(("Authorization", "Bearer bob-token"))
+            .set_json(json!({"token": "owned-invitation"}));
+
+        let response = test_request_at("/auth", configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["namespaces"], json!(["@bob", "shared", "owned"]));
+    }
+
+    #[actix_web::test]
+    async fn test_accept_invitation_not_found() {
+        let request = test::TestRequest::post()
+            .uri("/auth/invitations")
+            .insert_header(("Authorization", "Bearer bob-token"))
+            .set_json(json!({"token": "unknown"}));
+
+        let response = test_request_at("/auth", configure, request, http::StatusCode::NOT_FOUND).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["code"], "InvitationNotFound");
+    }
+}
@@ -0,0 +1,76 @@
+use actix_web::{HttpResponse, web};
+use common::model::NamespaceRequest;
+use crate::domains::auth_domain::AuthDomainTrait;
+use crate::handlers::{ApiError, Caller};
+use super::user_response;
+
+/// own an empty namespace nobody owns yet, from then on only the user may access it
+pub async fn claim_namespace(
+    domain: web::Data<dyn AuthDomainTrait>,
+    caller: web::ReqData<Caller>,
+    body: web::Json<NamespaceRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let user = caller.user()?.clone();
+
+    domain.claim_namespace(&user, &body.namespace).await?;
+
+    Ok(HttpResponse::Ok().json(user_response(domain.as_ref(), user).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+    use serde_json::json;
+
+    use crate::handlers::auth_handler::configure;
+    use crate::tests::{json_body, test_request_at};
+
+    #[actix_web::test]
+    async fn test_claim_namespace() {
+        let request = test::TestRequest::post()
+            .uri("/auth/namespaces")
+            .insert_header(("Authorization", "Bearer bob-token"))
+            .set_json(json!({"namespace": "team"}));
+
+        let response = test_request_at("/auth", configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["namespaces"], json!(["@bob", "shared", "team"]));
+    }
+
+    #[actix_web::test]
+    async fn test_claim_namespace_already_owned() {
+        let request = test::TestRequest::post()
+            .uri("/auth/namespaces")
+            .insert_header(("Authorization", "Bearer bob-token"))
+            .set_json(json!({"namespace": "owned"}));
+
+        let response = test_request_at("/auth", configure, request, http::StatusCode::CONFLICT).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["code"], "NamespaceAlreadyOwned");
+    }
+
+    #[actix_web::test]
+    async fn test_claim_namespace_not_empty() {
+        let request = test::TestRequest::post()
+            .uri("/auth/namespaces")
+            .insert_header(("Authorization", "Bearer bob-token"))
+            .set_json(json!({"namespace": "used"}));
+
+        let response = test_request_at("/auth", configure, request, http::StatusCode::CONFLICT).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["code"], "NamespaceNotEmpty");
+    }
+
+    #[actix_web::test]
+    async fn test_claim_namespace_default() {
+        let request = test::TestRequest::post()
+            .uri("/auth/namespaces")
+            .insert_header(("Authorization", "Bearer bob-token"))
+            .set_json(json!({"namespace": "default"}));
+
+        test_request_at("/auth", configure, request, http::StatusCode::BAD_REQUEST).await;
+    }
+}
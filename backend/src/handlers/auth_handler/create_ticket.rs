@@ -0,0 +1,52 @@
+use actix_web::{HttpResponse, web};
+use common::model::TokenResponse;
+use crate::domains::auth_domain::AuthDomainTrait;
+use crate::handlers::{ApiError, Caller};
+
+/// a single-use ticket for `?access_token=` of `GET /todos/events` and `GET /todos/sync`
+pub async fn create_ticket(
+    domain: web::Data<dyn AuthDomainTrait>,
+    caller: web::ReqData<Caller>,
+) -> Result<HttpResponse, ApiError> {
+    let ticket = domain.issue_ticket(caller.user()?).await?;
+
+    Ok(HttpResponse::Created().json(TokenResponse {
+        token: ticket.token,
+        expires_at: ticket.expires_at,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+
+    use crate::handlers::auth_handler::configure;
+    use crate::tests::{json_body, test_request_at};
+
+    #[actix_web::test]
+    async fn test_create_ticket() {
+        let request = test::TestRequest::post()
+            .uri("/auth/tickets")
+            .insert_header(("Authorization", "Bearer alice-token"));
+
+        let response = test_request_at("/auth", configure, request, http::StatusCode::CREATED).await;
+        let body = json_body(response).await;
+
+        assert!(body["token"].as_str().is_some_and(|it| it.starts_with("alice-ticket-")));
+        assert!(body["expires_at"].is_string());
+    }
+
+    #[actix_web::test]
+    async fn test_create_ticket_anonymous() {
+        let request = test::TestRequest::post()
+            .uri("/auth/tickets");
+
+        test_request_at("/auth", configure, request, http::StatusCode::UNAUTHORIZED).await;
+
+        let request = test::TestRequest::post()
+            .uri("/auth/tickets")
+            .insert_header(("Authorization", "ApiKey tk_shared-editor"));
+
+        test_request_at("/auth", configure, request, http::StatusCode::UNAUTHORIZED).await;
+    }
+}
@@ -0,0 +1,44 @@
+use actix_web::{HttpResponse, web};
+use crate::domains::auth_domain::AuthDomainTrait;
+use crate::handlers::{ApiError, Caller};
+use super::user_response;
+
+pub async fn get_me(
+    domain: web::Data<dyn AuthDomainTrait>,
+    caller: web::ReqData<Caller>,
+) -> Result<HttpResponse, ApiError> {
+    let user = caller.user()?.clone();
+
+    Ok(HttpResponse::Ok().json(user_response(domain.as_ref(), user).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+    use serde_json::json;
+
+    use crate::handlers::auth_handler::configure;
+    use crate::tests::{json_body, test_request_at};
+
+    #[actix_web::test]
+    async fn test_get_me() {
+        let request = test::TestRequest::get()
+            .uri("/auth/me")
+            .insert_header(("Authorization", "Bearer alice-token"));
+
+        let response = test_request_at("/auth", configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["username"], "alice");
+        assert_eq!(body["namespaces"], json!(["@alice", "owned", "shared"]));
+    }
+
+    #[actix_web::test]
+    async fn test_get_me_invalid_token() {
+        let request = test::TestRequest::get()
+            .uri("/auth/me")
+            .insert_header(("Authorization", "Bearer expired-token"));
+
+        test_request_at("/auth", configure, request, http::StatusCode::UNAUTHORIZED).await;
+    }
+}
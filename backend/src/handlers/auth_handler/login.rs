@@ -0,0 +1,51 @@
+use actix_web::{HttpResponse, web};
+use common::model::{CredentialsRequest, TokenResponse};
+use crate::domains::auth_domain::AuthDomainTrait;
+use crate::handlers::ApiError;
+
+pub async fn login(
+    domain: web::Data<dyn AuthDomainTrait>,
+    body: web::Json<CredentialsRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let session = domain.login(&body.username, &body.password).await?;
+
+    Ok(HttpResponse::Ok().json(TokenResponse {
+        token: session.token,
+        expires_at: session.expires_at,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+    use serde_json::json;
+
+    use crate::handlers::auth_handler::configure;
+    use crate::tests::{json_body, test_request_at};
+
+    #[actix_web::test]
+    async fn test_login() {
+        let request = test::TestRequest::post()
+            .uri("/auth/login")
+            .set_json(json!({"username": "alice", "password": "alice-password"}));
+
+        let response = test_request_at("/auth", configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert!(body["token"].as_str().is_some_and(|it| !it.is_empty()));
+        assert!(body["expires_at"].is_string());
+    }
+
+    #[actix_web::test]
+    async fn test_login_wrong_password() {
+        let request = test::TestRequest::post()
+            .uri("/auth/login")
+            .set_json(json!({"username": "alice", "password": "bob-password"}));
+
+        let response = test_request_at("/auth", configure, request, http::StatusCode::UNAUTHORIZED).await;
+        assert_eq!(response.headers().get("www-authenticate").unwrap(), "Bearer");
+
+        let body = json_body(response).await;
+        assert_eq!(body["code"], "Unauthorized");
+    }
+}
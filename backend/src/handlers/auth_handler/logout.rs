@@ -0,0 +1,43 @@
+use actix_web::{HttpRequest, HttpResponse, web};
+use crate::domains::auth_domain::AuthDomainTrait;
+use crate::handlers::{ApiError, bearer_token, Caller};
+
+/// revoke the bearer token of the request
+pub async fn logout(
+    domain: web::Data<dyn AuthDomainTrait>,
+    caller: web::ReqData<Caller>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    caller.user()?;
+
+    if let Some(token) = bearer_token(&req) {
+        domain.logout(&token).await?;
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+
+    use crate::handlers::auth_handler::configure;
+    use crate::tests::test_request_at;
+
+    #[actix_web::test]
+    async fn test_logout() {
+        let request = test::TestRequest::post()
+            .uri("/auth/logout")
+            .insert_header(("Authorization", "Bearer alice-token"));
+
+        test_request_at("/auth", configure, request, http::StatusCode::OK).await;
+    }
+
+    #[actix_web::test]
+    async fn test_logout_anonymous() {
+        let request = test::TestRequest::post()
+            .uri("/auth/logout");
+
+        test_request_at("/auth", configure, request, http::StatusCode::UNAUTHORIZED).await;
+    }
+}
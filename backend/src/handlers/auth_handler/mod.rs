@@ -0,0 +1,45 @@
+use actix_web::web;
+
+pub use accept_invitation::*;
+pub use claim_namespace::*;
+pub use create_ticket::*;
+pub use get_me::*;
+pub use login::*;
+pub use logout::*;
+pub use register::*;
+use common::model::UserResponse;
+
+use crate::domains::auth_domain::{AuthDomainTrait, User};
+use crate::handlers::configure_extractors;
+
+mod accept_invitation;
+mod claim_namespace;
+mod create_ticket;
+mod get_me;
+mod login;
+mod logout;
+mod register;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    configure_extractors(cfg);
+
+    cfg.route("/register", web::post().to(register));
+    cfg.route("/login", web::post().to(login));
+    cfg.route("/logout", web::post().to(logout));
+    cfg.route("/me", web::get().to(get_me));
+    cfg.route("/tickets", web::post().to(create_ticket));
+    cfg.route("/namespaces", web::post().to(claim_namespace));
+    cfg.route("/invitations", web::post().to(accept_invitation));
+}
+
+/// the user along with the namespaces it is a member of
+pub async fn user_response(domain: &dyn AuthDomainTrait, user: User) -> anyhow::Result<UserResponse> {
+    let namespaces = domain.namespaces(&user).await?;
+
+    Ok(UserResponse {
+        id: user.id,
+        username: user.username,
+        namespaces,
+        created_at: user.created_at,
+    })
+}
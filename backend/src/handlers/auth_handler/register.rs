@@ -0,0 +1,57 @@
+use actix_web::{HttpResponse, web};
+use common::model::CredentialsRequest;
+use crate::domains::auth_domain::AuthDomainTrait;
+use crate::handlers::ApiError;
+use super::user_response;
+
+pub async fn register(
+    domain: web::Data<dyn AuthDomainTrait>,
+    body: web::Json<CredentialsRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let user = domain.register(&body.username, &body.password).await?;
+
+    Ok(HttpResponse::Created().json(user_response(domain.as_ref(), user).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+    use serde_json::json;
+
+    use crate::handlers::auth_handler::configure;
+    use crate::tests::{json_body, test_request_at};
+
+    #[actix_web::test]
+    async fn test_register() {
+        let request = test::TestRequest::post()
+            .uri("/auth/register")
+            .set_json(json!({"username": "carol", "password": "carol-password"}));
+
+        let response = test_request_at("/auth", configure, request, http::StatusCode::CREATED).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["username"], "carol");
+        assert_eq!(body["namespaces"], json!(["@carol"]));
+    }
+
+    #[actix_web::test]
+    async fn test_register_already_exists() {
+        let request = test::TestRequest::post()
+            .uri("/auth/register")
+            .set_json(json!({"username": "alice", "password": "alice-password"}));
+
+        let response = test_request_at("/auth", configure, request, http::StatusCode::CONFLICT).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["code"], "UserAlreadyExists");
+    }
+
+    #[actix_web::test]
+    async fn test_register_invalid_password() {
+        let request = test::TestRequest::post()
+            .uri("/auth/register")
+            .set_json(json!({"username": "carol", "password": "short"}));
+
+        test_request_at("/auth", configure, request, http::StatusCode::BAD_REQUEST).await;
+    }
+}
@@ -0,0 +1,70 @@
+use actix_web::{HttpResponse, web};
+use common::model::{ApiKeyRequest, ApiKeyResponse, NamespaceRole};
+use crate::domains::auth_domain::AuthDomainTrait;
+use crate::handlers::{ApiError, Caller, Namespace};
+
+/// owners only, the key is in the response alone
+pub async fn create_key(
+    domain: web::Data<dyn AuthDomainTrait>,
+    caller: web::ReqData<Caller>,
+    namespace: web::ReqData<Namespace>,
+    body: web::Json<ApiKeyRequest>,
+) -> Result<HttpResponse, ApiError> {
+    namespace.require(NamespaceRole::Owner)?;
+    let user = caller.user()?;
+
+    let res = domain.create_api_key(user, namespace.as_str(), &body.name, body.role).await?;
+
+    Ok(HttpResponse::Created().json(ApiKeyResponse::from(res)))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+    use serde_json::json;
+
+    use crate::handlers::key_handler::configure;
+    use crate::tests::{json_body, test_request_at};
+
+    #[actix_web::test]
+    async fn test_create_key() {
+        let request = test::TestRequest::post()
+            .uri("/keys")
+            .insert_header(("t-ns", "shared"))
+            .insert_header(("Authorization", "Bearer alice-token"))
+            .set_json(json!({"name": " nightly "}));
+
+        let response = test_request_at("/keys", configure, request, http::StatusCode::CREATED).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["namespace"], "shared");
+        assert_eq!(body["name"], "nightly");
+        assert_eq!(body["role"], "editor");
+        assert_eq!(body["last_used_at"], json!(null));
+        assert!(body["key"].as_str().is_some_and(|it| it.starts_with("tk_")));
+    }
+
+    #[actix_web::test]
+    async fn test_create_key_invalid() {
+        for body in [json!({"name": ""}), json!({"name": "ci", "role": "owner"})] {
+            let request = test::TestRequest::post()
+                .uri("/keys")
+                .insert_header(("t-ns", "shared"))
+                .insert_header(("Authorization", "Bearer alice-token"))
+                .set_json(body);
+
+            test_request_at("/keys", configure, request, http::StatusCode::BAD_REQUEST).await;
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_create_key_by_viewer() {
+        let request = test::TestRequest::post()
+            .uri("/keys")
+            .insert_header(("t-ns", "shared"))
+            .insert_header(("Authorization", "Bearer bob-token"))
+            .set_json(json!({"name": "ci"}));
+
+        test_request_at("/keys", configure, request, http::StatusCode::FORBIDDEN).await;
+    }
+}
@@ -0,0 +1,61 @@
+use actix_web::{HttpResponse, web};
+use common::model::{ApiKeyResponse, NamespaceRole};
+use crate::domains::auth_domain::AuthDomainTrait;
+use crate::handlers::{ApiError, Namespace};
+
+/// owners only, newest first, without the secrets
+pub async fn get_keys(
+    domain: web::Data<dyn AuthDomainTrait>,
+    namespace: web::ReqData<Namespace>,
+) -> Result<HttpResponse, ApiError> {
+    namespace.require(NamespaceRole::Owner)?;
+
+    let keys = domain.list_api_keys(namespace.as_str()).await?;
+
+    Ok(HttpResponse::Ok().json(keys.into_iter().map(ApiKeyResponse::from).collect::<Vec<_>>()))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+    use serde_json::json;
+
+    use crate::handlers::key_handler::configure;
+    use crate::tests::{json_body, test_request_at};
+
+    #[actix_web::test]
+    async fn test_get_keys() {
+        let request = test::TestRequest::get()
+            .uri("/keys")
+            .insert_header(("t-ns", "shared"))
+            .insert_header(("Authorization", "Bearer alice-token"));
+
+        let response = test_request_at("/keys", configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        let keys = body.as_array().unwrap().iter()
+            .map(|it| json!([it["id"], it["name"], it["role"]]))
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![json!([2, "viewer", "viewer"]), json!([1, "editor", "editor"])]);
+        assert!(body[0].get("key").is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_get_keys_by_viewer() {
+        let request = test::TestRequest::get()
+            .uri("/keys")
+            .insert_header(("t-ns", "shared"))
+            .insert_header(("Authorization", "Bearer bob-token"));
+
+        test_request_at("/keys", configure, request, http::StatusCode::FORBIDDEN).await;
+    }
+
+    #[actix_web::test]
+    async fn test_get_keys_by_api_key() {
+        let request = test::TestRequest::get()
+            .uri("/keys")
+            .insert_header(("Authorization", "ApiKey tk_shared-editor"));
+
+        test_request_at("/keys", configure, request, http::StatusCode::FORBIDDEN).await;
+    }
+}
@@ -0,0 +1,42 @@
+use actix_web::web;
+
+pub use create_key::*;
+pub use get_keys::*;
+pub use revoke_key::*;
+use common::model::ApiKeyResponse;
+
+use crate::domains::auth_domain::{ApiKey, NewApiKey};
+use crate::handlers::configure_extractors;
+
+mod create_key;
+mod get_keys;
+mod revoke_key;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    configure_extractors(cfg);
+
+    cfg.route("", web::get().to(get_keys));
+    cfg.route("", web::post().to(create_key));
+
+    cfg.route("/{id}", web::delete().to(revoke_key));
+}
+
+impl From<ApiKey> for ApiKeyResponse {
+    fn from(api_key: ApiKey) -> Self {
+        Self {
+            id: api_key.id,
+            namespace: api_key.namespace,
+            name: api_key.name,
+            role: api_key.role,
+            created_at: api_key.created_at,
+            last_used_at: api_key.last_used_at,
+            key: None,
+        }
+    }
+}
+
+impl From<NewApiKey> for ApiKeyResponse {
+    fn from(new: NewApiKey) -> Self {
+        Self { key: Some(new.key), ..Self::from(new.api_key) }
+    }
+}
@@ -0,0 +1,55 @@
+use actix_web::{HttpResponse, web};
+use common::model::NamespaceRole;
+use crate::domains::auth_domain::AuthDomainTrait;
+use crate::handlers::{ApiError, Namespace};
+
+/// owners only, the key is refused from now on
+pub async fn revoke_key(
+    domain: web::Data<dyn AuthDomainTrait>,
+    namespace: web::ReqData<Namespace>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    namespace.require(NamespaceRole::Owner)?;
+
+    domain.revoke_api_key(namespace.as_str(), path.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+
+    use crate::handlers::key_handler::configure;
+    use crate::tests::test_request_at;
+
+    #[actix_web::test]
+    async fn test_revoke_key() {
+        let request = test::TestRequest::delete()
+            .uri("/keys/1")
+            .insert_header(("t-ns", "shared"))
+            .insert_header(("Authorization", "Bearer alice-token"));
+
+        test_request_at("/keys", configure, request, http::StatusCode::OK).await;
+    }
+
+    #[actix_web::test]
+    async fn test_revoke_key_of_other_namespace() {
+        let request = test::TestRequest::delete()
+            .uri("/keys/1")
+            .insert_header(("t-ns", "owned"))
+            .insert_header(("Authorization", "Bearer alice-token"));
+
+        test_request_at("/keys", configure, request, http::StatusCode::NOT_FOUND).await;
+    }
+
+    #[actix_web::test]
+    async fn test_revoke_key_by_viewer() {
+        let request = test::TestRequest::delete()
+            .uri("/keys/1")
+            .insert_header(("t-ns", "shared"))
+            .insert_header(("Authorization", "Bearer bob-token"));
+
+        test_request_at("/keys", configure, request, http::StatusCode::FORBIDDEN).await;
+    }
+}
@@ -0,0 +1,55 @@
+use actix_web::{HttpResponse, web};
+use common::model::{InvitationResponse, NamespaceRole, RoleRequest};
+use crate::domains::auth_domain::AuthDomainTrait;
+use crate::handlers::{ApiError, Caller, Namespace};
+
+/// owners only, the invitee joins with `POST /auth/invitations`
+pub async fn create_invitation(
+    domain: web::Data<dyn AuthDomainTrait>,
+    caller: web::ReqData<Caller>,
+    namespace: web::ReqData<Namespace>,
+    body: web::Json<RoleRequest>,
+) -> Result<HttpResponse, ApiError> {
+    namespace.require(NamespaceRole::Owner)?;
+    let user = caller.user()?;
+
+    let res = domain.invite(user, namespace.as_str(), body.role).await?;
+
+    Ok(HttpResponse::Created().json(InvitationResponse::from(res)))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+    use serde_json::json;
+
+    use crate::handlers::member_handler::configure;
+    use crate::tests::{json_body, test_request_at};
+
+    #[actix_web::test]
+    async fn test_create_invitation() {
+        let request = test::TestRequest::post()
+            .uri("/members/invitations")
+            .insert_header(("t-ns", "shared"))
+            .insert_header(("Authorization", "Bearer alice-token"))
+            .set_json(json!({"role": "viewer"}));
+
+        let response = test_request_at("/members", configure, request, http::StatusCode::CREATED).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["namespace"], "shared");
+        assert_eq!(body["role"], "viewer");
+        assert!(body["token"].as_str().is_some_and(|it| !it.is_empty()));
+    }
+
+    #[actix_web::test]
+    async fn test_create_invitation_without_owners() {
+        let request = test::TestRequest::post()
+            .uri("/members/invitations")
+            .insert_header(("Authorization", "Bearer alice-token"))
+            .insert_header(("t-ns", "open"))
+            .set_json(json!({"role": "editor"}));
+
+        test_request_at("/members", configure, request, http::StatusCode::FORBIDDEN).await;
+    }
+}
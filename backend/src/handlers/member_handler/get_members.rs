@@ -0,0 +1,70 @@
+use actix_web::{HttpResponse, web};
+use common::model::{MemberResponse, MembersResponse};
+use crate::domains::auth_domain::AuthDomainTrait;
+use crate::handlers::{ApiError, Namespace};
+
+/// any role may see the members
+pub async fn get_members(
+    domain: web::Data<dyn AuthDomainTrait>,
+    namespace: web::ReqData<Namespace>,
+) -> Result<HttpResponse, ApiError> {
+    let members = domain.list_members(namespace.as_str()).await?;
+
+    Ok(HttpResponse::Ok().json(MembersResponse {
+        namespace: namespace.get(),
+        role: namespace.role(),
+        members: members.into_iter().map(MemberResponse::from).collect(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+    use serde_json::json;
+
+    use crate::handlers::member_handler::configure;
+    use crate::tests::{json_body, test_request_at};
+
+    #[actix_web::test]
+    async fn test_get_members() {
+        let request = test::TestRequest::get()
+            .uri("/members")
+            .insert_header(("t-ns", "shared"))
+            .insert_header(("Authorization", "Bearer bob-token"));
+
+        let response = test_request_at("/members", configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["role"], "viewer");
+        let members = body["members"].as_array().unwrap().iter()
+            .map(|it| json!([it["username"], it["role"]]))
+            .collect::<Vec<_>>();
+        assert_eq!(members, vec![json!(["alice", "owner"]), json!(["bob", "viewer"])]);
+    }
+
+    #[actix_web::test]
+    async fn test_get_members_by_alias() {
+        let request = test::TestRequest::get()
+            .uri("/members")
+            .insert_header(("t-ns", " shared TEAM "))
+            .insert_header(("Authorization", "Bearer bob-token"));
+
+        let response = test_request_at("/members", configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["namespace"], "shared");
+        assert_eq!(body["role"], "viewer");
+    }
+
+    #[actix_web::test]
+    async fn test_get_members_without_owners() {
+        let request = test::TestRequest::get()
+            .uri("/members");
+
+        let response = test_request_at("/members", configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["role"], "editor");
+        assert_eq!(body["members"], json!([]));
+    }
+}
@@ -0,0 +1,47 @@
+use actix_web::web;
+
+pub use create_invitation::*;
+pub use get_members::*;
+pub use remove_member::*;
+pub use update_member::*;
+use common::model::{InvitationResponse, MemberResponse};
+
+use crate::domains::auth_domain::{Invitation, Member};
+use crate::handlers::configure_extractors;
+
+mod create_invitation;
+mod get_members;
+mod remove_member;
+mod update_member;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    configure_extractors(cfg);
+
+    cfg.route("", web::get().to(get_members));
+    cfg.route("/invitations", web::post().to(create_invitation));
+
+    cfg.route("/{user_id}", web::patch().to(update_member));
+    cfg.route("/{user_id}", web::delete().to(remove_member));
+}
+
+impl From<Member> for MemberResponse {
+    fn from(member: Member) -> Self {
+        Self {
+            user_id: member.user_id,
+            username: member.username,
+            role: member.role,
+            created_at: member.created_at,
+        }
+    }
+}
+
+impl From<Invitation> for InvitationResponse {
+    fn from(invitation: Invitation) -> Self {
+        Self {
+            token: invitation.token,
+            namespace: invitation.namespace,
+            role: invitation.role,
+            expires_at: invitation.expires_at,
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use actix_web::{HttpResponse, web};
+use common::model::NamespaceRole;
+use crate::domains::auth_domain::AuthDomainTrait;
+use crate::handlers::{ApiError, Caller, Namespace};
+
+/// owners only, though any member may leave
+pub async fn remove_member(
+    domain: web::Data<dyn AuthDomainTrait>,
+    caller: web::ReqData<Caller>,
+    namespace: web::ReqData<Namespace>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    let user_id = path.into_inner();
+    if caller.user()?.id != user_id {
+        namespace.require(NamespaceRole::Owner)?;
+    }
+
+    domain.remove_member(namespace.as_str(), user_id).await?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+
+    use crate::handlers::member_handler::configure;
+    use crate::tests::test_request_at;
+
+    #[actix_web::test]
+    async fn test_remove_member() {
+        let request = test::TestRequest::delete()
+            .uri("/members/2")
+            .insert_header(("t-ns", "shared"))
+            .insert_header(("Authorization", "Bearer alice-token"));
+
+        test_request_at("/members", configure, request, http::StatusCode::OK).await;
+    }
+
+    #[actix_web::test]
+    async fn test_remove_member_leaving() {
+        let request = test::TestRequest::delete()
+            .uri("/members/2")
+            .insert_header(("t-ns", "shared"))
+            .insert_header(("Authorization", "Bearer bob-token"));
+
+        test_request_at("/members", configure, request, http::StatusCode::OK).await;
+    }
+
+    #[actix_web::test]
+    async fn test_remove_member_by_viewer() {
+        let request = test::TestRequest::delete()
+            .uri("/members/1")
+            .insert_header(("t-ns", "shared"))
+            .insert_header(("Authorization", "Bearer bob-token"));
+
+        test_request_at("/members", configure, request, http::StatusCode::FORBIDDEN).await;
+    }
+
+    #[actix_web::test]
+    async fn test_remove_member_not_found() {
+        let request = test::TestRequest::delete()
+            .uri("/members/999")
+            .insert_header(("t-ns", "shared"))
+            .insert_header(("Authorization", "Bearer alice-token"));
+
+        test_request_at("/members", configure, request, http::StatusCode::NOT_FOUND).await;
+    }
+}
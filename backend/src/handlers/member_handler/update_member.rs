@@ -0,0 +1,82 @@
+use actix_web::{HttpResponse, web};
+use common::model::{MemberResponse, NamespaceRole, RoleRequest};
+use crate::domains::auth_domain::AuthDomainTrait;
+use crate::handlers::{ApiError, Namespace};
+
+/// owners only
+pub async fn update_member(
+    domain: web::Data<dyn AuthDomainTrait>,
+    namespace: web::ReqData<Namespace>,
+    path: web::Path<i32>,
+    body: web::Json<RoleRequest>,
+) -> Result<HttpResponse, ApiError> {
+    namespace.require(NamespaceRole::Owner)?;
+    let user_id = path.into_inner();
+
+    let res = domain.update_member(namespace.as_str(), user_id, body.role).await?;
+
+    Ok(HttpResponse::Ok().json(MemberResponse::from(res)))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+    use serde_json::json;
+
+    use crate::handlers::member_handler::configure;
+    use crate::tests::{json_body, test_request_at};
+
+    #[actix_web::test]
+    async fn test_update_member() {
+        let request = test::TestRequest::patch()
+            .uri("/members/2")
+            .insert_header(("t-ns", "shared"))
+            .insert_header(("Authorization", "Bearer alice-token"))
+            .set_json(json!({"role": "editor"}));
+
+        let response = test_request_at("/members", configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["username"], "bob");
+        assert_eq!(body["role"], "editor");
+    }
+
+    #[actix_web::test]
+    async fn test_update_member_by_viewer() {
+        let request = test::TestRequest::patch()
+            .uri("/members/2")
+            .insert_header(("t-ns", "shared"))
+            .insert_header(("Authorization", "Bearer bob-token"))
+            .set_json(json!({"role": "owner"}));
+
+        let response = test_request_at("/members", configure, request, http::StatusCode::FORBIDDEN).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["code"], "Forbidden");
+    }
+
+    #[actix_web::test]
+    async fn test_update_member_last_owner() {
+        let request = test::TestRequest::patch()
+            .uri("/members/1")
+            .insert_header(("t-ns", "shared"))
+            .insert_header(("Authorization", "Bearer alice-token"))
+            .set_json(json!({"role": "viewer"}));
+
+        let response = test_request_at("/members", configure, request, http::StatusCode::CONFLICT).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["code"], "LastOwner");
+    }
+
+    #[actix_web::test]
+    async fn test_update_member_invalid_role() {
+        let request = test::TestRequest::patch()
+            .uri("/members/2")
+            .insert_header(("t-ns", "shared"))
+            .insert_header(("Authorization", "Bearer alice-token"))
+            .set_json(json!({"role": "admin"}));
+
+        test_request_at("/members", configure, request, http::StatusCode::BAD_REQUEST).await;
+    }
+}
@@ -0,0 +1,64 @@
+use std::fmt::Write;
+
+use actix_web::{HttpResponse, web};
+
+use crate::domains::purge_job::PurgeMetrics;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/metrics", web::get().to(metrics));
+}
+
+/// prometheus text exposition of the server metrics
+async fn metrics(purge: web::Data<PurgeMetrics>) -> HttpResponse {
+    let mut body = String::new();
+
+    body.push_str("# HELP todo_purged_total Deleted todos purged since the server started.\n");
+    body.push_str("# TYPE todo_purged_total counter\n");
+    for (namespace, rows) in purge.purged() {
+        let namespace = namespace.replace('\\', "\\\\").replace('"', "\\\"");
+        writeln!(body, "todo_purged_total{{namespace=\"{}\"}} {}", namespace, rows).unwrap();
+    }
+
+    if let Some(last_run) = purge.last_run() {
+        body.push_str("# HELP todo_purge_last_run_timestamp_seconds Last time the purge job ran.\n");
+        body.push_str("# TYPE todo_purge_last_run_timestamp_seconds gauge\n");
+        writeln!(body, "todo_purge_last_run_timestamp_seconds {}", last_run.timestamp()).unwrap();
+    }
+
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{App, http, test, web};
+    use actix_web::body::to_bytes;
+
+    use crate::domains::purge_job::PurgeMetrics;
+    use crate::handlers::routes;
+    use super::configure;
+
+    #[actix_web::test]
+    async fn metrics() {
+        let purge = web::Data::new(PurgeMetrics::default());
+        purge.record(&[("default".to_string(), 2)]);
+
+        let app = test::init_service(App::new().app_data(purge).configure(configure)).await;
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/metrics").to_request()).await;
+        assert!(resp.status().is_success());
+
+        let body = to_bytes(resp.into_body()).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("todo_purged_total{namespace=\"default\"} 2\n"));
+        assert!(body.contains("todo_purge_last_run_timestamp_seconds "));
+    }
+
+    #[actix_web::test]
+    async fn metrics_apart_from_api() {
+        let app = test::init_service(App::new().configure(routes)).await;
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/metrics").to_request()).await;
+
+        assert_eq!(resp.status(), http::StatusCode::NOT_FOUND);
+    }
+}
@@ -1,29 +1,322 @@
-use actix_web::{HttpMessage, web};
-use actix_web::dev::{ServiceRequest};
+use std::sync::Arc;
+
+use actix_web::{HttpMessage, HttpRequest, web};
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::middleware::{from_fn, Next};
+use serde::Deserialize;
+
+use common::model::NamespaceRole;
+
+use crate::domains::auth_domain::{ApiKey, AuthConfig, AuthDomainTrait, AuthError, check_access, check_api_key, check_role, DEFAULT_NAMESPACE, User};
 
 pub mod todo_handler;
+pub mod tag_handler;
+pub mod auth_handler;
+pub mod member_handler;
+pub mod key_handler;
 pub mod ping_handler;
+pub mod metrics_handler;
+mod api_error;
+
+pub use api_error::ApiError;
 
 pub fn routes(cfg: &mut web::ServiceConfig) {
     cfg.configure(ping_handler::configure);
 
+    cfg.service(web::scope("/auth")
+        .configure(auth_handler::configure));
+
+    cfg.service(web::resource("/todos.ics")
+        .wrap(from_fn(authorize))
+        .wrap(from_fn(authenticate_key_query))
+        .route(web::get().to(todo_handler::get_todos_ics)));
+    cfg.service(web::resource("/todos/events")
+        .wrap(from_fn(authorize))
+        .wrap(from_fn(authenticate_ticket))
+        .route(web::get().to(todo_handler::get_todo_events)));
+    cfg.service(web::resource("/todos/sync")
+        .wrap(from_fn(authorize))
+        .wrap(from_fn(authenticate_ticket))
+        .route(web::get().to(todo_handler::sync_todos)));
     cfg.service(web::scope("/todos")
+        .wrap(from_fn(authorize))
         .configure(todo_handler::configure));
+    cfg.service(web::scope("/tags")
+        .wrap(from_fn(authorize))
+        .configure(tag_handler::configure));
+    cfg.service(web::scope("/members")
+        .wrap(from_fn(authorize))
+        .configure(member_handler::configure));
+    cfg.service(web::scope("/keys")
+        .wrap(from_fn(authorize))
+        .configure(key_handler::configure));
 }
 
+/// extractor errors respond as `ErrorCode::ValidationFailed`
+pub fn configure_extractors(cfg: &mut web::ServiceConfig) {
+    cfg.app_data(web::JsonConfig::default()
+        .error_handler(|err, _| ApiError::validation(err).into()));
+    cfg.app_data(web::PathConfig::default()
+        .error_handler(|err, _| ApiError::validation(err).into()));
+    cfg.app_data(web::QueryConfig::default()
+        .error_handler(|err, _| ApiError::validation(err).into()));
+}
+
+/// who calls, by the `Authorization` header
 #[derive(Clone)]
-pub struct Namespace(String);
+pub enum Caller {
+    Anonymous,
+    /// of a bearer token
+    User(User),
+    /// of an `ApiKey` header, for scripts and CI
+    ApiKey(ApiKey),
+}
+
+impl Caller {
+    /// the user, `AuthError::Unauthenticated` for an anonymous caller or an API key
+    pub fn user(&self) -> anyhow::Result<&User> {
+        match self {
+            Caller::User(user) => Ok(user),
+            _ => Err(AuthError::Unauthenticated.into()),
+        }
+    }
+
+    fn as_user(&self) -> Option<&User> {
+        match self {
+            Caller::User(user) => Some(user),
+            _ => None,
+        }
+    }
+}
+
+/// the bearer token of the `Authorization` header, it is never taken from the url
+pub fn bearer_token(req: &HttpRequest) -> Option<String> {
+    req.headers().get(AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string())
+}
+
+/// the key of an `Authorization: ApiKey tk_...` header
+pub fn api_key(req: &HttpRequest) -> Option<String> {
+    req.headers().get(AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("ApiKey "))
+        .map(|key| key.trim().to_string())
+}
+
+/// inject the `Caller`, a token or a key which is invalid or expired responds `ErrorCode::Unauthorized` right away
+pub async fn authenticate(req: ServiceRequest, next: Next<impl MessageBody + 'static>) -> Result<ServiceResponse<BoxBody>, actix_web::Error> {
+    let caller = match caller_of(&req).await {
+        Ok(caller) => caller,
+        Err(err) => return Ok(req.error_response(ApiError::from(err))),
+    };
+    req.extensions_mut().insert(caller);
+
+    next.call(req).await.map(ServiceResponse::map_into_boxed_body)
+}
+
+/// `?access_token=` of a ticket of `POST /auth/tickets` stands for the `Authorization` header,
+/// which an `EventSource` or a `WebSocket` cannot send. Wraps the streams only, a used or expired ticket is `401`
+pub async fn authenticate_ticket(req: ServiceRequest, next: Next<impl MessageBody + 'static>) -> Result<ServiceResponse<BoxBody>, actix_web::Error> {
+    if let Some(ticket) = query_credentials(&req).access_token {
+        let caller = match ticket_caller(&req, &ticket).await {
+            Ok(caller) => caller,
+            Err(err) => return Ok(req.error_response(ApiError::from(err))),
+        };
+        req.extensions_mut().insert(caller);
+    }
+
+    next.call(req).await.map(ServiceResponse::map_into_boxed_body)
+}
+
+/// `?api_key=` stands for the `Authorization: ApiKey` header, which a calendar app cannot send.
+/// Wraps `/todos.ics` only, an invalid key is `401`
+pub async fn authenticate_key_query(req: ServiceRequest, next: Next<impl MessageBody + 'static>) -> Result<ServiceResponse<BoxBody>, actix_web::Error> {
+    if let Some(key) = query_credentials(&req).api_key {
+        let caller = match key_caller(&req, &key).await {
+            Ok(caller) => caller,
+            Err(err) => return Ok(req.error_response(ApiError::from(err))),
+        };
+        req.extensions_mut().insert(caller);
+    }
+
+    next.call(req).await.map(ServiceResponse::map_into_boxed_body)
+}
+
+#[derive(Deserialize, Default)]
+struct CredentialsQuery {
+    access_token: Option<String>,
+    api_key: Option<String>,
+}
+
+fn query_credentials(req: &ServiceRequest) -> CredentialsQuery {
+    web::Query::<CredentialsQuery>::from_query(req.query_string()).map(|it| it.into_inner()).unwrap_or_default()
+}
+
+/// inject the `Namespace` of the request, else respond why the `Caller` may not access it
+pub async fn authorize(req: ServiceRequest, next: Next<impl MessageBody + 'static>) -> Result<ServiceResponse<BoxBody>, actix_web::Error> {
+    let namespace = match namespace_of(&req).await {
+        Ok(namespace) => namespace,
+        Err(err) => return Ok(req.error_response(ApiError::from(err))),
+    };
+    req.extensions_mut().insert(namespace);
+
+    next.call(req).await.map(ServiceResponse::map_into_boxed_body)
+}
+
+async fn caller_of(req: &ServiceRequest) -> anyhow::Result<Caller> {
+    let domain = req.app_data::<web::Data<dyn AuthDomainTrait>>().expect("AuthDomainTrait is registered");
+
+    if let Some(key) = api_key(req.request()) {
+        let api_key = domain.authenticate_api_key(&key).await?.ok_or(AuthError::Unauthenticated)?;
+        return Ok(Caller::ApiKey(api_key));
+    }
+    let Some(token) = bearer_token(req.request()) else {
+        return Ok(Caller::Anonymous);
+    };
+
+    let user = domain.authenticate(&token).await?.ok_or(AuthError::Unauthenticated)?;
+
+    Ok(Caller::User(user))
+}
+
+async fn ticket_caller(req: &ServiceRequest, ticket: &str) -> anyhow::Result<Caller> {
+    let domain = req.app_data::<web::Data<dyn AuthDomainTrait>>().expect("AuthDomainTrait is registered");
+
+    let user = domain.authenticate_ticket(ticket).await?.ok_or(AuthError::Unauthenticated)?;
+
+    Ok(Caller::User(user))
+}
+
+async fn key_caller(req: &ServiceRequest, key: &str) -> anyhow::Result<Caller> {
+    let domain = req.app_data::<web::Data<dyn AuthDomainTrait>>().expect("AuthDomainTrait is registered");
+
+    let api_key = domain.authenticate_api_key(key).await?.ok_or(AuthError::Unauthenticated)?;
+
+    Ok(Caller::ApiKey(api_key))
+}
+
+async fn namespace_of(req: &ServiceRequest) -> anyhow::Result<Namespace> {
+    let default = AuthConfig::default();
+    let config = req.app_data::<web::Data<AuthConfig>>().map_or(&default, |config| config.get_ref());
+    let caller = req.extensions().get::<Caller>().cloned().unwrap_or(Caller::Anonymous);
+    let name = Namespace::name_of(req, &caller, config)?;
+    let domain = req.app_data::<web::Data<dyn AuthDomainTrait>>().expect("AuthDomainTrait is registered");
+
+    namespace_for(domain.get_ref(), config, &caller, name).await
+}
+
+/// the role of the `Caller` in the namespace `name`
+async fn namespace_for(domain: &dyn AuthDomainTrait, config: &AuthConfig, caller: &Caller, name: String) -> anyhow::Result<Namespace> {
+    if let Caller::ApiKey(api_key) = caller {
+        let role = check_api_key(&name, api_key)?;
+        return Ok(Namespace { name, role });
+    }
+
+    let membership = domain.membership(&name, caller.as_user()).await?;
+    let role = check_access(&name, &membership, caller.as_user(), config.anonymous)?;
+
+    Ok(Namespace { name, role })
+}
+
+/// the `Caller` of a connection which outlives its request, to authorize each of its writes again:
+/// a member demoted or removed since the connect, or a revoked API key, loses the access it had
+#[derive(Clone)]
+pub struct Access {
+    domain: Arc<dyn AuthDomainTrait>,
+    config: AuthConfig,
+    caller: Caller,
+    /// the secret of an `ApiKey` caller, to authenticate it again
+    api_key: Option<String>,
+}
+
+impl Access {
+    pub fn of(req: &HttpRequest) -> Self {
+        let domain = req.app_data::<web::Data<dyn AuthDomainTrait>>().expect("AuthDomainTrait is registered");
+        let caller = req.extensions().get::<Caller>().cloned().unwrap_or(Caller::Anonymous);
+
+        Self {
+            domain: domain.clone().into_inner(),
+            config: req.app_data::<web::Data<AuthConfig>>().map(|it| it.get_ref().clone()).unwrap_or_default(),
+            api_key: matches!(caller, Caller::ApiKey(_)).then(|| api_key(req)).flatten(),
+            caller,
+        }
+    }
+
+    /// the role of the caller in the namespace as of now
+    pub async fn namespace(&self, name: &str) -> anyhow::Result<Namespace> {
+        let caller = match (&self.caller, &self.api_key) {
+            (Caller::ApiKey(_), Some(key)) => {
+                let api_key = self.domain.authenticate_api_key(key).await?.ok_or(AuthError::Unauthenticated)?;
+                Caller::ApiKey(api_key)
+            }
+            (caller, _) => caller.clone(),
+        };
+
+        namespace_for(&*self.domain, &self.config, &caller, name.to_string()).await
+    }
+}
+
+/// the namespace of the request along with the role of the `Caller` in it
+#[derive(Clone)]
+pub struct Namespace {
+    name: String,
+    role: NamespaceRole,
+}
 
 impl Namespace {
-    pub fn inject(req: &ServiceRequest) {
-        let namespace = req.headers().get("t-ns")
-            .map(|h| h.to_str().unwrap())
-            .unwrap_or_else(|| "default")
-            .to_string();
-        req.extensions_mut().insert(Namespace(namespace));
+    pub fn new(name: &str, role: NamespaceRole) -> Self {
+        Self { name: name.to_string(), role }
+    }
+
+    /// `?ns=` for `EventSource`, `WebSocket` and calendar apps, which cannot send the `t-ns` header.
+    /// without either, the namespace of an API key, the personal namespace of a user, or else the default one.
+    /// `AuthError::MalformedNamespace` for what is neither an alias nor fits the namespace columns
+    fn name_of(req: &ServiceRequest, caller: &Caller, config: &AuthConfig) -> Result<String, AuthError> {
+        #[derive(Deserialize)]
+        struct NamespaceQuery {
+            ns: Option<String>,
+        }
+
+        let query = web::Query::<NamespaceQuery>::from_query(req.query_string()).ok()
+            .and_then(|query| query.into_inner().ns);
+        // an alias may be any utf-8, which `HeaderValue::to_str` refuses
+        let header = || req.headers().get("t-ns").map(|h| String::from_utf8(h.as_bytes().to_vec())
+            .map_err(|err| AuthError::MalformedNamespace(String::from_utf8_lossy(err.as_bytes()).to_string())));
+
+        match query.map(Ok).or_else(header).transpose()? {
+            Some(namespace) => config.resolve_namespace(&namespace),
+            None => Ok(match caller {
+                Caller::ApiKey(api_key) => api_key.namespace.clone(),
+                Caller::User(user) => user.namespace(),
+                Caller::Anonymous => DEFAULT_NAMESPACE.to_string(),
+            }),
+        }
     }
 
+    /// to read the namespace, any role will do
     pub fn get(&self) -> String {
-        self.0.clone()
+        self.name.clone()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+
+    pub fn role(&self) -> NamespaceRole {
+        self.role
     }
-}
\ No newline at end of file
+
+    /// to write the namespace, `AuthError::RoleRequired` for a viewer
+    pub fn writable(&self) -> anyhow::Result<String> {
+        self.require(NamespaceRole::Editor)?;
+        Ok(self.get())
+    }
+
+    pub fn require(&self, role: NamespaceRole) -> anyhow::Result<()> {
+        Ok(check_role(&self.name, self.role, role)?)
+    }
+}
@@ -10,6 +10,8 @@ async fn ping() -> String {
 
 #[cfg(test)]
 mod tests {
+    use super::ping;
+
     #[actix_web::test]
     async fn test_index() {
         let string = ping().await;
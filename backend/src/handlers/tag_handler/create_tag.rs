@@ -0,0 +1,74 @@
+use actix_web::{HttpResponse, web};
+use common::model::{TagRequest, TagResponse};
+use crate::domains::tag_domain::TagDomainTrait;
+use crate::handlers::{ApiError, Namespace};
+
+pub async fn create_tag(
+    domain: web::Data<dyn TagDomainTrait>,
+    namespace: web::ReqData<Namespace>,
+    body: web::Json<TagRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let res = domain.create_tag(namespace.writable()?, &body.name).await?;
+
+    Ok(HttpResponse::Created().json(TagResponse::from(res)))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+    use serde_json::json;
+
+    use crate::handlers::tag_handler::configure;
+    use crate::tests::{json_body, test_request_at};
+
+    #[actix_web::test]
+    async fn test_create_tag() {
+        let request = test::TestRequest::post()
+            .uri("/tags")
+            .set_json(json!({"name": " frontend "}));
+
+        let response = test_request_at("/tags", configure, request, http::StatusCode::CREATED).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["id"], 3);
+        assert_eq!(body["name"], "frontend");
+    }
+
+    #[actix_web::test]
+    async fn test_create_tag_already_exists() {
+        let request = test::TestRequest::post()
+            .uri("/tags")
+            .set_json(json!({"name": "backend"}));
+
+        let response = test_request_at("/tags", configure, request, http::StatusCode::CONFLICT).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body, json!({
+            "code": "TagAlreadyExists",
+            "message": "Tag [name: backend] already exists.",
+        }));
+    }
+
+    #[actix_web::test]
+    async fn test_create_tag_invalid_name() {
+        let request = test::TestRequest::post()
+            .uri("/tags")
+            .set_json(json!({"name": "two words"}));
+
+        let response = test_request_at("/tags", configure, request, http::StatusCode::BAD_REQUEST).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["code"], "ValidationFailed");
+    }
+
+    #[actix_web::test]
+    async fn test_create_tag_by_viewer() {
+        let request = test::TestRequest::post()
+            .uri("/tags")
+            .insert_header(("t-ns", "shared"))
+            .insert_header(("Authorization", "Bearer bob-token"))
+            .set_json(json!({"name": "frontend"}));
+
+        test_request_at("/tags", configure, request, http::StatusCode::FORBIDDEN).await;
+    }
+}
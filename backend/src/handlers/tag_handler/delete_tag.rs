@@ -0,0 +1,39 @@
+use actix_web::{HttpResponse, web};
+use crate::domains::tag_domain::TagDomainTrait;
+use crate::handlers::{ApiError, Namespace};
+
+pub async fn delete_tag(
+    domain: web::Data<dyn TagDomainTrait>,
+    namespace: web::ReqData<Namespace>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    let id = path.into_inner();
+
+    domain.delete_tag((namespace.writable()?, id)).await?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+
+    use crate::handlers::tag_handler::configure;
+    use crate::tests::test_request_at;
+
+    #[actix_web::test]
+    async fn test_delete_tag() {
+        let request = test::TestRequest::delete()
+            .uri("/tags/1");
+
+        test_request_at("/tags", configure, request, http::StatusCode::OK).await;
+    }
+
+    #[actix_web::test]
+    async fn test_delete_tag_not_found() {
+        let request = test::TestRequest::delete()
+            .uri("/tags/999");
+
+        test_request_at("/tags", configure, request, http::StatusCode::NOT_FOUND).await;
+    }
+}
@@ -0,0 +1,46 @@
+use actix_web::{HttpResponse, web};
+use common::model::TagResponse;
+use crate::domains::tag_domain::TagDomainTrait;
+use crate::handlers::{ApiError, Namespace};
+
+pub async fn get_tags(
+    domain: web::Data<dyn TagDomainTrait>,
+    namespace: web::ReqData<Namespace>,
+) -> Result<HttpResponse, ApiError> {
+    let tags = domain.list_tags(namespace.get()).await?;
+
+    Ok(HttpResponse::Ok().json(tags.into_iter().map(TagResponse::from).collect::<Vec<TagResponse>>()))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+    use serde_json::json;
+
+    use crate::handlers::tag_handler::configure;
+    use crate::tests::{json_body, test_request_at};
+
+    #[actix_web::test]
+    async fn test_get_tags() {
+        let request = test::TestRequest::get()
+            .uri("/tags");
+
+        let response = test_request_at("/tags", configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        let names = body.as_array().unwrap().iter().map(|it| it["name"].clone()).collect::<Vec<_>>();
+        assert_eq!(names, vec![json!("backend"), json!("release-1.2")]);
+    }
+
+    #[actix_web::test]
+    async fn test_get_tags_in_other_namespace() {
+        let request = test::TestRequest::get()
+            .uri("/tags")
+            .insert_header(("t-ns", "other"));
+
+        let response = test_request_at("/tags", configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body, json!([]));
+    }
+}
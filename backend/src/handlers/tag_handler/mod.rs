@@ -0,0 +1,36 @@
+use actix_web::web;
+
+pub use create_tag::*;
+pub use delete_tag::*;
+pub use get_tags::*;
+pub use rename_tag::*;
+use common::model::TagResponse;
+
+use crate::domains::tag_domain::Tag;
+use crate::handlers::configure_extractors;
+
+mod create_tag;
+mod delete_tag;
+mod get_tags;
+mod rename_tag;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    configure_extractors(cfg);
+
+    cfg.route("", web::get().to(get_tags));
+    cfg.route("", web::post().to(create_tag));
+
+    cfg.route("/{id}", web::patch().to(rename_tag));
+    cfg.route("/{id}", web::delete().to(delete_tag));
+}
+
+impl From<Tag> for TagResponse {
+    fn from(tag: Tag) -> Self {
+        Self {
+            namespace: tag.id.0,
+            id: tag.id.1,
+            name: tag.name,
+            created_at: tag.created_at,
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use actix_web::{HttpResponse, web};
+use common::model::{TagRequest, TagResponse};
+use crate::domains::tag_domain::TagDomainTrait;
+use crate::handlers::{ApiError, Namespace};
+
+pub async fn rename_tag(
+    domain: web::Data<dyn TagDomainTrait>,
+    namespace: web::ReqData<Namespace>,
+    path: web::Path<i32>,
+    body: web::Json<TagRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let id = path.into_inner();
+
+    let res = domain.rename_tag((namespace.writable()?, id), &body.name).await?;
+
+    Ok(HttpResponse::Ok().json(TagResponse::from(res)))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+    use serde_json::json;
+
+    use crate::handlers::tag_handler::configure;
+    use crate::tests::{json_body, test_request_at};
+
+    #[actix_web::test]
+    async fn test_rename_tag() {
+        let request = test::TestRequest::patch()
+            .uri("/tags/1")
+            .set_json(json!({"name": "server"}));
+
+        let response = test_request_at("/tags", configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["id"], 1);
+        assert_eq!(body["name"], "server");
+    }
+
+    #[actix_web::test]
+    async fn test_rename_tag_not_found() {
+        let request = test::TestRequest::patch()
+            .uri("/tags/999")
+            .set_json(json!({"name": "server"}));
+
+        let response = test_request_at("/tags", configure, request, http::StatusCode::NOT_FOUND).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["code"], "TagNotFound");
+    }
+}
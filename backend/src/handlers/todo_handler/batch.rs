@@ -0,0 +1,137 @@
+use actix_web::{HttpResponse, web};
+use common::model::{BatchOperation, BatchRequest, BatchResponse, BatchResult, ErrorResponse, TodoResponse};
+use crate::domains::todo_domain::{BatchOp, CreateTodo, MAX_BATCH_SIZE, UpdateTodo};
+use crate::handlers::ApiError;
+use crate::{Namespace, TodoDomainTrait};
+use super::update_todo::validate_update;
+
+pub async fn batch(
+    domain: web::Data<dyn TodoDomainTrait>,
+    namespace: web::ReqData<Namespace>,
+    body: web::Json<BatchRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let namespace = namespace.writable()?;
+    let operations = body.into_inner().operations;
+
+    if operations.is_empty() || operations.len() > MAX_BATCH_SIZE {
+        return Err(ApiError::validation(format!("expect 1 to {} operations", MAX_BATCH_SIZE)));
+    }
+
+    // an invalid operation fails alone, the valid ones are applied
+    let mut ops = vec![];
+    let mut invalid = vec![];
+    for operation in operations {
+        match BatchOp::try_from(operation) {
+            Ok(op) => {
+                ops.push(op);
+                invalid.push(None);
+            }
+            Err(err) => invalid.push(Some(err)),
+        }
+    }
+
+    let mut applied = domain.batch(namespace, ops).await?.into_iter();
+    let results = invalid.into_iter()
+        .map(|invalid| match invalid {
+            Some(err) => BatchResult::Error(ErrorResponse::from(err)),
+            None => match applied.next().expect("a result per operation") {
+                Ok(todo) => BatchResult::Ok(TodoResponse::from(todo)),
+                Err(err) => BatchResult::Error(ErrorResponse::from(ApiError::from(err))),
+            },
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(BatchResponse { results }))
+}
+
+impl TryFrom<BatchOperation> for BatchOp {
+    type Error = ApiError;
+
+    fn try_from(operation: BatchOperation) -> Result<Self, Self::Error> {
+        Ok(match operation {
+            BatchOperation::Create(body) => BatchOp::Create(CreateTodo::from(body)),
+            BatchOperation::Update { id, body } => {
+                validate_update(&body)?;
+                BatchOp::Update(id, UpdateTodo::from(body))
+            }
+            BatchOperation::Status { id, status, cascade } => BatchOp::Update(id, UpdateTodo {
+                status: Some(status),
+                cascade,
+                ..Default::default()
+            }),
+            BatchOperation::Delete { id } => BatchOp::Delete(id),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+    use serde_json::json;
+
+    use crate::handlers::todo_handler::configure;
+    use crate::tests::{json_body, test_request};
+
+    #[actix_web::test]
+    async fn test_batch() {
+        let request = test::TestRequest::post()
+            .uri("/todos/batch")
+            .set_json(json!({"operations": [
+                {"op": "create", "content": "created in batch", "tags": ["backend"]},
+                {"op": "update", "id": 3, "content": "updated in batch", "due_at": null},
+                {"op": "status", "id": 2, "status": "archived"},
+                {"op": "status", "id": 999, "status": "done"},
+                {"op": "update", "id": 3},
+                {"op": "delete", "id": 1},
+            ]}));
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+        let results = body["results"].as_array().unwrap();
+
+        assert_eq!(results.len(), 6);
+        assert_eq!(results[0]["ok"]["content"], "created in batch");
+        assert_eq!(results[0]["ok"]["tags"], json!(["backend"]));
+        assert_eq!(results[1]["ok"]["content"], "updated in batch");
+        assert_eq!(results[1]["ok"]["due_at"], json!(null));
+        assert_eq!(results[2]["ok"]["status"], "archived");
+        assert_eq!(results[3]["error"], json!({
+            "code": "TodoNotFound",
+            "message": "Todo [id: 999] not found.",
+        }));
+        assert_eq!(results[4]["error"]["code"], "ValidationFailed");
+        assert_eq!(results[5]["ok"]["id"], 1);
+    }
+
+    #[actix_web::test]
+    async fn test_batch_size() {
+        let request = test::TestRequest::post()
+            .uri("/todos/batch")
+            .set_json(json!({"operations": []}));
+
+        let response = test_request(configure, request, http::StatusCode::BAD_REQUEST).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["code"], "ValidationFailed");
+        assert_eq!(body["message"], "expect 1 to 100 operations");
+
+        let operations = vec![json!({"op": "delete", "id": 1}); 101];
+        let request = test::TestRequest::post()
+            .uri("/todos/batch")
+            .set_json(json!({"operations": operations}));
+
+        test_request(configure, request, http::StatusCode::BAD_REQUEST).await;
+    }
+
+    #[actix_web::test]
+    async fn test_batch_unknown_operation() {
+        let request = test::TestRequest::post()
+            .uri("/todos/batch")
+            .set_json(json!({"operations": [{"op": "archive", "id": 1}]}));
+
+        let response = test_request(configure, request, http::StatusCode::BAD_REQUEST).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["code"], "ValidationFailed");
+    }
+}
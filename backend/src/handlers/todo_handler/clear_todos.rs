@@ -1,18 +1,18 @@
 use actix_web::{HttpResponse, web};
 use common::model::IdsRequest;
 use crate::{Namespace, TodoDomainTrait};
-use crate::todo_handler::WrappedAnyhowError;
+use crate::handlers::ApiError;
 
 pub async fn clear_todos(
     domain: web::Data<dyn TodoDomainTrait>,
     ids: web::Json<IdsRequest<i32>>,
     namespace: web::ReqData<Namespace>,
-) -> Result<HttpResponse, WrappedAnyhowError> {
-    let namespace = namespace.get();
+) -> Result<HttpResponse, ApiError> {
+    let namespace = namespace.writable()?;
     let ids = ids.into_inner().ids;
 
     if !ids.is_empty() {
-        let _ = domain.clear_todos(namespace, ids).await;
+        domain.clear_todos(namespace, ids).await?;
     }
 
     Ok(HttpResponse::Ok().finish())
@@ -20,12 +20,17 @@ pub async fn clear_todos(
 
 #[cfg(test)]
 mod tests {
-    use actix_web::test;
+    use actix_web::{http, test};
+    use serde_json::json;
+
+    use crate::handlers::todo_handler::configure;
+    use crate::tests::test_request;
 
     #[actix_web::test]
-    async fn test_update_todo_status() {
-        let request = test::TestRequest::patch()
-            .uri("/3");
+    async fn test_clear_todos() {
+        let request = test::TestRequest::delete()
+            .uri("/todos")
+            .set_json(json!({"ids": [3]}));
 
         test_request(configure, request, http::StatusCode::OK).await;
     }
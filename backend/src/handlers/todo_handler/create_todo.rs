@@ -1,34 +1,155 @@
 use actix_web::{web, HttpResponse};
 use common::model::{CreateTodoRequest, TodoResponse};
-use crate::todo_handler::WrappedAnyhowError;
-use crate::{Namespace, TodoDomain};
+use crate::domains::todo_domain::CreateTodo;
+use crate::handlers::ApiError;
+use crate::handlers::todo_handler::etag;
+use crate::{Namespace, TodoDomainTrait};
 
 pub async fn create_todo(
-    domain: web::Data<TodoDomain>,
+    domain: web::Data<dyn TodoDomainTrait>,
     namespace: web::ReqData<Namespace>,
     body: web::Json<CreateTodoRequest>,
-) -> Result<HttpResponse, WrappedAnyhowError> {
-    let namespace = namespace.get();
+) -> Result<HttpResponse, ApiError> {
+    let namespace = namespace.writable()?;
     let todo = body.into_inner();
 
-    let res = domain.create_todo(&namespace, &todo.content).await?;
+    let res = domain.create_todo(namespace, CreateTodo::from(todo)).await?;
 
-    Ok(HttpResponse::Created().json(TodoResponse::from(res)))
+    Ok(HttpResponse::Created().insert_header(etag(&res)).json(TodoResponse::from(res)))
+}
+
+impl From<CreateTodoRequest> for CreateTodo {
+    fn from(req: CreateTodoRequest) -> Self {
+        Self {
+            content: req.content,
+            due_at: req.due_at,
+            priority: req.priority.unwrap_or_default(),
+            tags: req.tags,
+            parent_id: req.parent_id,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use actix_web::test;
+    use actix_web::{http, test};
+    use serde_json::{json, Value};
+
+    use crate::handlers::todo_handler::configure;
+    use crate::tests::{json_body, test_request};
 
     #[actix_web::test]
     async fn test_create_todo() {
         let request = test::TestRequest::post()
-            .uri("/")
+            .uri("/todos")
             .set_json(json!({"content": "create a todo"}));
 
         let response = test_request(configure, request, http::StatusCode::CREATED).await;
-        let body_bytes = to_bytes(response.into_body()).await.unwrap();
+        let body = json_body(response).await;
+
+        assert_eq!(body["id"], 4);
+        assert_eq!(body["content"], "create a todo");
+        assert_eq!(body["status"], "todo");
+        assert_eq!(body["due_at"], Value::Null);
+        assert_eq!(body["priority"], "normal");
+    }
+
+    #[actix_web::test]
+    async fn test_create_todo_with_priority() {
+        let request = test::TestRequest::post()
+            .uri("/todos")
+            .set_json(json!({"content": "create a todo", "priority": "high"}));
+
+        let response = test_request(configure, request, http::StatusCode::CREATED).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["priority"], "high");
+    }
+
+    #[actix_web::test]
+    async fn test_create_todo_with_due_at() {
+        let request = test::TestRequest::post()
+            .uri("/todos")
+            .set_json(json!({"content": "create a todo", "due_at": "2024-01-01T08:00:00+08:00"}));
+
+        let response = test_request(configure, request, http::StatusCode::CREATED).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["due_at"], "2024-01-01T00:00:00Z");
+    }
+
+    #[actix_web::test]
+    async fn test_create_todo_with_tags() {
+        let request = test::TestRequest::post()
+            .uri("/todos")
+            .set_json(json!({"content": "create a todo", "tags": ["backend", "release-1.2"]}));
+
+        let response = test_request(configure, request, http::StatusCode::CREATED).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["tags"], json!(["backend", "release-1.2"]));
+    }
+
+    #[actix_web::test]
+    async fn test_create_subtask() {
+        let request = test::TestRequest::post()
+            .uri("/todos")
+            .set_json(json!({"content": "create a subtask", "parent_id": 1}));
+
+        let response = test_request(configure, request, http::StatusCode::CREATED).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["parent_id"], 1);
+    }
+
+    #[actix_web::test]
+    async fn test_create_todo_invalid_due_at() {
+        let request = test::TestRequest::post()
+            .uri("/todos")
+            .set_json(json!({"content": "create a todo", "due_at": "tomorrow"}));
+
+        let response = test_request(configure, request, http::StatusCode::BAD_REQUEST).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["code"], "ValidationFailed");
+    }
+
+    #[actix_web::test]
+    async fn test_create_todo_by_viewer() {
+        let request = test::TestRequest::post()
+            .uri("/todos")
+            .insert_header(("t-ns", "shared"))
+            .insert_header(("Authorization", "Bearer bob-token"))
+            .set_json(json!({"content": "create a todo"}));
+
+        let response = test_request(configure, request, http::StatusCode::FORBIDDEN).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["code"], "Forbidden");
+    }
+
+    #[actix_web::test]
+    async fn test_create_todo_by_api_key() {
+        let request = test::TestRequest::post()
+            .uri("/todos")
+            .insert_header(("Authorization", "ApiKey tk_shared-editor"))
+            .set_json(json!({"content": "create a todo"}));
+
+        test_request(configure, request, http::StatusCode::CREATED).await;
+
+        let cases = [
+            ("tk_shared-viewer", "shared", http::StatusCode::FORBIDDEN),
+            ("tk_shared-editor", "owned", http::StatusCode::FORBIDDEN),
+            ("tk_revoked", "shared", http::StatusCode::UNAUTHORIZED),
+        ];
+        for (key, namespace, status) in cases {
+            let request = test::TestRequest::post()
+                .uri("/todos")
+                .insert_header(("t-ns", namespace))
+                .insert_header(("Authorization", format!("ApiKey {}", key)))
+                .set_json(json!({"content": "create a todo"}));
 
-        assert_eq!(body_bytes, r##"{"id":2,"content":"create a todo","status":"Todo"}"##, );
+            test_request(configure, request, status).await;
+        }
     }
 }
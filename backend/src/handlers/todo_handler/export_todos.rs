@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use actix_web::{HttpResponse, web};
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::web::Bytes;
+use futures_util::{stream, Stream};
+
+use common::model::{ExportFormat, FormatQuery, TodoResponse, TodoSort};
+
+use crate::{Namespace, TodoDomainTrait};
+use crate::domains::todo_domain::{Cursor, ListTodo, MAX_PAGE_SIZE};
+use crate::handlers::ApiError;
+
+/// the parts of an export, the todos are read page by page
+enum Part {
+    Begin,
+    Page { cursor: Option<Cursor>, index: usize },
+    End,
+}
+
+/// the whole namespace in the manual order as a document of the format, streamed page by page
+pub async fn export_todos(
+    domain: web::Data<dyn TodoDomainTrait>,
+    namespace: web::ReqData<Namespace>,
+    query: web::Query<FormatQuery>,
+) -> HttpResponse {
+    let format = query.into_inner().format;
+    let filename = format!("todos.{}", format.extension());
+
+    HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(filename)],
+        })
+        .streaming(export_document(domain, namespace.get(), format))
+}
+
+/// the chunks of the document, a failed page ends the stream with its error
+pub fn export_document(
+    domain: web::Data<dyn TodoDomainTrait>,
+    namespace: String,
+    format: ExportFormat,
+) -> impl Stream<Item = Result<Bytes, actix_web::Error>> {
+    stream::unfold(Some(Part::Begin), move |part| {
+        let domain = domain.clone().into_inner();
+        let namespace = namespace.clone();
+        async move {
+            let (chunk, next) = match part? {
+                Part::Begin => (Ok(format.begin(&namespace)), Some(Part::Page { cursor: None, index: 0 })),
+                Part::Page { cursor, index } => match export_page(domain, namespace, format, cursor, index).await {
+                    Ok((chunk, next)) => (Ok(chunk), Some(next)),
+                    Err(err) => (Err(actix_web::Error::from(err)), None),
+                },
+                Part::End => (Ok(format.end()), None),
+            };
+
+            Some((chunk.map(Bytes::from), next))
+        }
+    })
+}
+
+/// the todos of the page after `cursor`, with the next part
+async fn export_page(
+    domain: Arc<dyn TodoDomainTrait>,
+    namespace: String,
+    format: ExportFormat,
+    cursor: Option<Cursor>,
+    index: usize,
+) -> Result<(String, Part), ApiError> {
+    let page = domain.list_todo(namespace, ListTodo {
+        cursor,
+        limit: MAX_PAGE_SIZE,
+        sort: TodoSort::Manual,
+        ..Default::default()
+    }).await?;
+
+    let count = page.items.len();
+    let chunk = page.items.into_iter()
+        .enumerate()
+        .map(|(offset, todo)| format.todo(&TodoResponse::from(todo), index + offset))
+        .collect::<String>();
+
+    let next = match page.next_cursor {
+        Some(cursor) => Part::Page { cursor: Some(cursor), index: index + count },
+        None => Part::End,
+    };
+
+    Ok((chunk, next))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+    use actix_web::body::to_bytes;
+
+    use chrono::{TimeZone, Utc};
+    use common::model::{ExportFormat, ImportTodo, TodoResponse};
+
+    use crate::domains::todo_domain::Todo;
+
+    use crate::handlers::todo_handler::configure;
+    use crate::tests::{MockTodoDomain, test_request};
+
+    /// the fixtures in the manual order, as they are imported
+    fn fixtures() -> Vec<ImportTodo> {
+        let mut todos = MockTodoDomain::fixtures();
+        todos.sort_by_key(|it| (it.position, it.id.1));
+        todos.into_iter().map(|it| ImportTodo::from(TodoResponse::from(it))).collect()
+    }
+
+    #[actix_web::test]
+    async fn test_export_todos_round_trip() {
+        for format in [ExportFormat::Json, ExportFormat::Csv, ExportFormat::Md, ExportFormat::Txt] {
+            let request = test::TestRequest::get()
+                .uri(&format!("/todos/export?format={}", format.extension()));
+
+            let response = test_request(configure, request, http::StatusCode::OK).await;
+            assert_eq!(response.headers().get("content-type").unwrap(), format.content_type());
+            assert_eq!(
+                response.headers().get("content-disposition").unwrap(),
+                &format!("attachment; filename=\"todos.{}\"", format.extension()),
+            );
+
+            let body = to_bytes(response.into_body()).await.unwrap();
+            let document = String::from_utf8(body.to_vec()).unwrap();
+
+            assert_eq!(fixtures(), format.parse(&document).unwrap(), "{:?}:\n{}", format, document);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_export_todos_txt_content_as_metadata() {
+        let contents = [
+            "pay rent due:friday",
+            "status:done id:x parent: pri:Z",
+            "+word @home see https://example.com",
+            "\\+escaped \\due:x back\\slash",
+            "a\nb:c",
+        ];
+        for (index, content) in contents.into_iter().enumerate() {
+            let todo = TodoResponse::from(Todo {
+                id: ("default".to_string(), index as i32 + 1),
+                content: content.to_string(),
+                created_at: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+                tags: vec!["@office".to_string(), "travel".to_string()],
+                ..Default::default()
+            });
+
+            let line = ExportFormat::Txt.todo(&todo, index);
+
+            assert_eq!(vec![ImportTodo::from(todo)], ExportFormat::Txt.parse(&line).unwrap(), "{}", line);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_export_todos_empty() {
+        let request = test::TestRequest::get()
+            .uri("/todos/export")
+            .insert_header(("t-ns", "empty"));
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = to_bytes(response.into_body()).await.unwrap();
+
+        assert_eq!(body, r#"{"todos":[]}"#);
+    }
+
+    #[actix_web::test]
+    async fn test_export_todos_invalid_format() {
+        let request = test::TestRequest::get()
+            .uri("/todos/export?format=xml");
+
+        test_request(configure, request, http::StatusCode::BAD_REQUEST).await;
+    }
+}
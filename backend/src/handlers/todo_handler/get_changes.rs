@@ -0,0 +1,64 @@
+use actix_web::{HttpResponse, web};
+
+use common::model::{GetChangesQuery, TodoChanges, TodoResponse};
+
+use crate::{Namespace, TodoDomainTrait};
+use crate::handlers::ApiError;
+
+/// what changed in the namespace since `since`, for the offline clients to catch up
+pub async fn get_changes(
+    domain: web::Data<dyn TodoDomainTrait>,
+    namespace: web::ReqData<Namespace>,
+    query: web::Query<GetChangesQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let changes = domain.list_changes(namespace.get(), query.into_inner().since).await?;
+
+    Ok(HttpResponse::Ok().json(TodoChanges {
+        todos: changes.todos.into_iter().map(TodoResponse::from).collect(),
+        deleted: changes.deleted,
+        until: changes.until,
+        full: changes.full,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+
+    use crate::handlers::todo_handler::configure;
+    use crate::tests::{json_body, test_request};
+
+    #[actix_web::test]
+    async fn test_get_changes() {
+        let request = test::TestRequest::get()
+            .uri("/todos/changes");
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["todos"].as_array().unwrap().len(), 3);
+        assert_eq!(body["deleted"].as_array().unwrap().len(), 0);
+        assert!(body["until"].is_string());
+        assert_eq!(body["full"], true);
+
+        let request = test::TestRequest::get()
+            .uri("/todos/changes?since=2100-01-01T00:00:00Z");
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["todos"].as_array().unwrap().len(), 0);
+        assert_eq!(body["full"], false);
+    }
+
+    #[actix_web::test]
+    async fn test_get_changes_invalid() {
+        let request = test::TestRequest::get()
+            .uri("/todos/changes?since=yesterday");
+
+        let response = test_request(configure, request, http::StatusCode::BAD_REQUEST).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["code"], "ValidationFailed");
+    }
+}
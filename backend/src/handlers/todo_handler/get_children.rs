@@ -0,0 +1,57 @@
+use actix_web::{HttpResponse, web};
+use crate::{Namespace, TodoDomainTrait};
+use crate::handlers::ApiError;
+use crate::todo_handler::TodoResponse;
+
+pub async fn get_children(
+    domain: web::Data<dyn TodoDomainTrait>,
+    namespace: web::ReqData<Namespace>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, ApiError> {
+    let id = path.into_inner();
+
+    let res = domain.list_children((namespace.get(), id)).await?;
+
+    Ok(HttpResponse::Ok().json(res.into_iter().map(TodoResponse::from).collect::<Vec<TodoResponse>>()))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+    use serde_json::json;
+
+    use crate::handlers::todo_handler::configure;
+    use crate::tests::{json_body, test_request};
+
+    #[actix_web::test]
+    async fn test_get_children() {
+        let request = test::TestRequest::get()
+            .uri("/todos/1/children");
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body.as_array().unwrap().len(), 1);
+        assert_eq!(body[0]["id"], 3);
+        assert_eq!(body[0]["parent_id"], 1);
+
+        let request = test::TestRequest::get()
+            .uri("/todos/3/children");
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body, json!([]));
+    }
+
+    #[actix_web::test]
+    async fn test_get_children_not_found() {
+        let request = test::TestRequest::get()
+            .uri("/todos/999/children");
+
+        let response = test_request(configure, request, http::StatusCode::NOT_FOUND).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["code"], "TodoNotFound");
+    }
+}
@@ -1,25 +1,62 @@
 use actix_web::{HttpResponse, web};
 use crate::{Namespace, TodoDomainTrait};
-use crate::infra::db::RecordNotFound;
-use crate::todo_handler::{TodoResponse, WrappedAnyhowError};
+use crate::handlers::ApiError;
+use crate::todo_handler::{etag, TodoResponse};
 
 pub async fn get_todo_by_id(
     domain: web::Data<dyn TodoDomainTrait>,
     namespace: web::ReqData<Namespace>,
     path: web::Path<i32>,
-) -> Result<HttpResponse, WrappedAnyhowError> {
+) -> Result<HttpResponse, ApiError> {
     let id = path.into_inner();
 
-    match domain.get_todo_by_id((namespace.get(), id)).await {
-        Ok(res) => Ok(HttpResponse::Ok().json(TodoResponse::from(res))),
-        Err(e) => {
-            if e.is::<RecordNotFound>() {
-                Ok(HttpResponse::NotFound().finish())
-            } else {
-                Err(e.into())
-            }
-        }
-    }
+    let res = domain.get_todo_by_id((namespace.get(), id)).await?;
+
+    Ok(HttpResponse::Ok().insert_header(etag(&res)).json(TodoResponse::from(res)))
 }
 
-// TODO unit tests with error handling
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+    use serde_json::json;
+
+    use crate::handlers::todo_handler::configure;
+    use crate::tests::{json_body, test_request};
+
+    #[actix_web::test]
+    async fn test_get_todo_by_id() {
+        let request = test::TestRequest::get()
+            .uri("/todos/1");
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        assert_eq!(response.headers().get("ETag").unwrap(), "\"1\"");
+        let body = json_body(response).await;
+
+        assert_eq!(body["id"], 1);
+        assert_eq!(body["content"], "first thing");
+        assert_eq!(body["version"], 1);
+    }
+
+    #[actix_web::test]
+    async fn test_get_todo_by_id_not_found() {
+        let request = test::TestRequest::get()
+            .uri("/todos/999");
+
+        let response = test_request(configure, request, http::StatusCode::NOT_FOUND).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body, json!({
+            "code": "TodoNotFound",
+            "message": "Todo [id: 999] not found.",
+        }));
+    }
+
+    #[actix_web::test]
+    async fn test_get_todo_by_id_in_other_namespace() {
+        let request = test::TestRequest::get()
+            .uri("/todos/1")
+            .insert_header(("t-ns", "other"));
+
+        test_request(configure, request, http::StatusCode::NOT_FOUND).await;
+    }
+}
@@ -0,0 +1,125 @@
+use std::time::Duration;
+
+use actix_web::{HttpResponse, web};
+use actix_web::http::header::{CacheControl, CacheDirective};
+use actix_web::web::Bytes;
+use futures_util::stream;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{interval_at, Instant};
+
+use common::model::TodoEvent;
+
+use crate::domains::todo_events::TodoEvents;
+use crate::handlers::Namespace;
+
+/// a comment sent on idle streams, so that proxies keep them open
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// `text/event-stream` of the `TodoEvent`s in the namespace, from now on
+pub async fn get_todo_events(
+    events: web::Data<TodoEvents>,
+    namespace: web::ReqData<Namespace>,
+) -> HttpResponse {
+    let namespace = namespace.get();
+    let keep_alive = interval_at(Instant::now() + KEEP_ALIVE, KEEP_ALIVE);
+
+    let frames = stream::unfold((events.subscribe(), keep_alive, namespace), |(mut receiver, mut keep_alive, namespace)| async move {
+        let frame = loop {
+            tokio::select! {
+                _ = keep_alive.tick() => break Bytes::from_static(b": keep-alive\n\n"),
+                change = receiver.recv() => match change {
+                    Ok(change) if change.namespace == namespace => break frame_of(change.event),
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(_)) => break frame_of(TodoEvent::Lagged),
+                    Err(RecvError::Closed) => return None,
+                },
+            }
+        };
+
+        Some((Ok::<_, actix_web::Error>(frame), (receiver, keep_alive, namespace)))
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(CacheControl(vec![CacheDirective::NoCache]))
+        .streaming(frames)
+}
+
+fn frame_of(event: TodoEvent) -> Bytes {
+    Bytes::from(format!("data: {}\n\n", serde_json::to_string(&event).unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::poll_fn;
+    use std::pin::pin;
+
+    use std::sync::Arc;
+
+    use actix_web::{App, http, test, web};
+    use actix_web::body::MessageBody;
+    use actix_web::middleware::from_fn;
+
+    use common::model::TodoEvent;
+
+    use crate::domains::todo_events::{TodoChange, TodoEvents};
+    use crate::domains::auth_domain::AuthDomainTrait;
+    use crate::handlers::{authenticate, authenticate_ticket, authorize};
+    use crate::tests::MockAuthDomain;
+    use super::get_todo_events;
+
+    #[actix_web::test]
+    async fn test_get_todo_events() {
+        let events = TodoEvents::default();
+        let auth_domain = Arc::new(MockAuthDomain::default()) as Arc<dyn AuthDomainTrait>;
+        let app = test::init_service(App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(web::Data::new(events.clone()))
+            .app_data(web::Data::from(auth_domain))
+            .service(web::resource("/todos/events")
+                .wrap(from_fn(authorize))
+                .wrap(from_fn(authenticate_ticket))
+                .route(web::get().to(get_todo_events)))).await;
+
+        let request = test::TestRequest::get()
+            .uri("/todos/events")
+            .insert_header(("t-ns", "events"))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+
+        assert_eq!(response.status(), http::StatusCode::OK);
+        assert_eq!(response.headers().get("content-type").unwrap(), "text/event-stream");
+
+        events.publish(TodoChange::new("other", TodoEvent::Created { id: 1 }));
+        events.publish(TodoChange::new("events", TodoEvent::Updated { id: 2 }));
+
+        let mut body = pin!(response.into_body());
+        let frame = poll_fn(|cx| body.as_mut().poll_next(cx)).await.unwrap().unwrap();
+
+        assert_eq!(frame, "data: {\"type\":\"updated\",\"id\":2}\n\n");
+    }
+
+    #[actix_web::test]
+    async fn test_get_todo_events_ticket() {
+        let auth_domain = Arc::new(MockAuthDomain::default()) as Arc<dyn AuthDomainTrait>;
+        let app = test::init_service(App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(web::Data::new(TodoEvents::default()))
+            .app_data(web::Data::from(auth_domain))
+            .service(web::resource("/todos/events")
+                .wrap(from_fn(authorize))
+                .wrap(from_fn(authenticate_ticket))
+                .route(web::get().to(get_todo_events)))).await;
+
+        let request = test::TestRequest::get().uri("/todos/events?ns=owned&access_token=alice-ticket").to_request();
+        assert_eq!(test::call_service(&app, request).await.status(), http::StatusCode::OK);
+
+        // used up
+        let request = test::TestRequest::get().uri("/todos/events?ns=owned&access_token=alice-ticket").to_request();
+        assert_eq!(test::call_service(&app, request).await.status(), http::StatusCode::UNAUTHORIZED);
+
+        // a bearer token is no ticket
+        let request = test::TestRequest::get().uri("/todos/events?ns=owned&access_token=alice-token").to_request();
+        assert_eq!(test::call_service(&app, request).await.status(), http::StatusCode::UNAUTHORIZED);
+    }
+}
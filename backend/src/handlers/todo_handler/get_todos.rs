@@ -1,100 +1,283 @@
 use actix_web::{web, HttpResponse};
-use serde::Deserialize;
-use common::model::{TodoResponse, TodoStatus};
-use crate::domains::todo_domain::TodoDomainTrait;
+use common::model::{GetTodosQuery, TodoPage, TodoResponse};
+use crate::domains::todo_domain::{Cursor, ListTodo, TodoDomainTrait};
 use crate::Namespace;
-use crate::todo_handler::WrappedAnyhowError;
-
-#[derive(Deserialize)]
-pub struct GetTodosQuery {
-    status: Option<TodoStatus>,
-}
+use crate::handlers::ApiError;
 
 pub async fn get_todos(
     domain: web::Data<dyn TodoDomainTrait>,
     namespace: web::ReqData<Namespace>,
     query: web::Query<GetTodosQuery>,
-) -> Result<HttpResponse, WrappedAnyhowError> {
-    let res: Vec<TodoResponse> = domain
-        .list_todo(namespace.get(), query.into_inner().status).await
-        .map_err(|err| WrappedAnyhowError { err })?
-        .into_iter()
-        .map(TodoResponse::from)
-        .collect();
-
-    Ok(HttpResponse::Ok().json(res))
+) -> Result<HttpResponse, ApiError> {
+    let page = domain
+        .list_todo(namespace.get(), ListTodo::try_from(query.into_inner())?).await?;
+
+    Ok(HttpResponse::Ok().json(TodoPage {
+        items: page.items.into_iter().map(TodoResponse::from).collect(),
+        next_cursor: page.next_cursor.map(|it| it.to_string()),
+    }))
+}
+
+impl TryFrom<GetTodosQuery> for ListTodo {
+    type Error = anyhow::Error;
+
+    /// the cursor is one of the order of the query
+    fn try_from(query: GetTodosQuery) -> Result<Self, Self::Error> {
+        let mut list = Self {
+            status: query.status,
+            cursor: None,
+            limit: ListTodo::page_size(query.limit),
+            q: query.q,
+            due_before: query.due_before,
+            overdue: query.overdue,
+            sort: query.sort.unwrap_or_default(),
+            tag: query.tag,
+        };
+        list.cursor = query.cursor
+            .map(|cursor| Cursor::parse(&cursor, list.sort, list.search().is_some()))
+            .transpose()?;
+
+        Ok(list)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use actix_web::test;
-    use crate::domains::todo_domain::{Todo, TodoDomainTrait, TodoID, UpdateTodo};
-    use common::model::TodoStatus;
+    use actix_web::{http, test};
+    use actix_web::body::to_bytes;
+    use actix_web::http::header::HeaderValue;
+    use serde_json::Value;
 
-    struct Mock {}
+    use crate::handlers::todo_handler::configure;
+    use crate::tests::{json_body, test_request};
 
-    impl Mock {
-        fn new() -> Self {
-            Mock {}
-        }
+    #[actix_web::test]
+    async fn test_get_todos() {
+        let request = test::TestRequest::get()
+            .uri("/todos")
+            .insert_header(("t-ns", "empty"));
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body_bytes = to_bytes(response.into_body()).await.unwrap();
+
+        assert_eq!(body_bytes, r##"{"items":[],"next_cursor":null}"##, );
     }
 
-    #[async_trait::async_trait]
-    impl TodoDomainTrait for Mock {
-        async fn get_todo_by_id(&self, _id: TodoID) -> anyhow::Result<Todo> {
-            Ok(Todo::default())
-        }
+    #[actix_web::test]
+    async fn test_get_todos_of_owner() {
+        let request = test::TestRequest::get()
+            .uri("/todos")
+            .insert_header(("t-ns", "owned"))
+            .insert_header(("Authorization", "Bearer alice-token"));
 
-        async fn list_todo(&self, _namespace: String, _status: Option<TodoStatus>) -> anyhow::Result<Vec<Todo>> {
-            Ok(vec![])
-        }
+        test_request(configure, request, http::StatusCode::OK).await;
+    }
 
-        async fn create_todo(&self, _namespace: String, _content: &str) -> anyhow::Result<Todo> {
-            Ok(Todo::default())
-        }
+    #[actix_web::test]
+    async fn test_get_todos_of_other_user() {
+        let request = test::TestRequest::get()
+            .uri("/todos")
+            .insert_header(("t-ns", "owned"))
+            .insert_header(("Authorization", "Bearer bob-token"));
 
-        async fn update_todo(&self, _id: TodoID, _to_update: UpdateTodo) -> anyhow::Result<Todo> {
-            Ok(Todo::default())
-        }
+        let response = test_request(configure, request, http::StatusCode::FORBIDDEN).await;
+        let body = json_body(response).await;
 
-        async fn clear_todos(&self, _namespace: String, _ids: Vec<i32>) -> anyhow::Result<()> {
-            Ok(())
-        }
+        assert_eq!(body["code"], "Forbidden");
     }
 
     #[actix_web::test]
-    async fn test_get_todos() {
-        let service = Arc::new(Mock::new()) as Arc<dyn TodoDomainTrait>;
+    async fn test_get_todos_of_owner_anonymous() {
+        let request = test::TestRequest::get()
+            .uri("/todos")
+            .insert_header(("t-ns", "owned"));
 
-        let app = App::new()
-            .wrap_fn(|req, srv| {
-                Namespace::inject(&req);
-                srv.call(req)
-            })
-            .app_data(web::Data::from(service))
-            .configure(routes);
+        test_request(configure, request, http::StatusCode::UNAUTHORIZED).await;
+    }
 
-        let app = test::init_service(app).await;
-        let request = TestRequest::get().uri("/todos");
-        let response = request.send_request(&app).await;
-        assert_eq!(response.status().as_u16(), 200);
+    #[actix_web::test]
+    async fn test_get_todos_of_viewer() {
+        let request = test::TestRequest::get()
+            .uri("/todos")
+            .insert_header(("t-ns", "shared"))
+            .insert_header(("Authorization", "Bearer bob-token"));
 
-        let body_bytes = to_bytes(response.into_body()).await.unwrap();
+        test_request(configure, request, http::StatusCode::OK).await;
+    }
+
+    #[actix_web::test]
+    async fn test_get_todos_personal_namespace() {
+        let request = test::TestRequest::get()
+            .uri("/todos")
+            .insert_header(("Authorization", "Bearer alice-token"));
 
-        assert_eq!(body_bytes, r##"[]"##, );
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+        assert_eq!(body["items"], serde_json::json!([]));
+
+        // a token is never taken from the url
+        let request = test::TestRequest::get()
+            .uri("/todos?ns=@alice&access_token=alice-token");
+
+        test_request(configure, request, http::StatusCode::UNAUTHORIZED).await;
+
+        let request = test::TestRequest::get()
+            .uri("/todos")
+            .insert_header(("t-ns", "@carol"));
+
+        test_request(configure, request, http::StatusCode::FORBIDDEN).await;
     }
 
     #[actix_web::test]
     async fn test_get_todos_by_status() {
-        // let request = TestRequest::default().uri("?status=todo").to_http_request();
-        // let query = Query::from_query(&request.query_string()).unwrap();
-        // let resp = get_todos(query).await;
-        //
-        // assert_eq!(resp.status(), http::StatusCode::OK);
-        // let body_bytes = to_bytes(resp.into_body()).await.unwrap();
-        // assert_eq!(
-        //     body_bytes,
-        //     r##"[{"id":1,"content":"first thing","status":"Todo"}]"##
-        // );
+        let request = test::TestRequest::get()
+            .uri("/todos?status=todo");
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(ids_of(&body), vec![3, 1]);
+    }
+
+    #[actix_web::test]
+    async fn test_get_todos_by_cursor() {
+        let request = test::TestRequest::get()
+            .uri("/todos?limit=2");
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(ids_of(&body), vec![3, 2]);
+        assert_eq!(body["next_cursor"], "2");
+
+        let request = test::TestRequest::get()
+            .uri("/todos?limit=2&cursor=2");
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(ids_of(&body), vec![1]);
+        assert_eq!(body["next_cursor"], Value::Null);
+    }
+
+    #[actix_web::test]
+    async fn test_get_todos_by_search() {
+        let request = test::TestRequest::get()
+            .uri("/todos?q=thing");
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(ids_of(&body), vec![2, 1]);
+        assert_eq!(body["items"][0]["snippet"], "second <mark>thing</mark>");
+    }
+
+    #[actix_web::test]
+    async fn test_get_todos_by_due_at() {
+        let request = test::TestRequest::get()
+            .uri("/todos?due_before=2000-03-01T00:00:00Z");
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(ids_of(&body), vec![2, 1]);
+        assert_eq!(body["items"][1]["due_at"], "2000-01-01T00:00:00Z");
+
+        let request = test::TestRequest::get()
+            .uri("/todos?overdue=true");
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(ids_of(&body), vec![3, 1]);
+    }
+
+    #[actix_web::test]
+    async fn test_get_todos_sort() {
+        let request = test::TestRequest::get()
+            .uri("/todos?sort=priority");
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(ids_of(&body), vec![2, 3, 1]);
+
+        let request = test::TestRequest::get()
+            .uri("/todos?sort=priority&limit=1&cursor=2:2");
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(ids_of(&body), vec![3]);
+        assert_eq!(body["next_cursor"], "1:3");
+
+        let request = test::TestRequest::get()
+            .uri("/todos?sort=priority&cursor=2");
+
+        test_request(configure, request, http::StatusCode::BAD_REQUEST).await;
+
+        let request = test::TestRequest::get()
+            .uri("/todos?sort=manual");
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(ids_of(&body), vec![3, 2, 1]);
+    }
+
+    #[actix_web::test]
+    async fn test_get_todos_by_tag() {
+        let request = test::TestRequest::get()
+            .uri("/todos?tag=backend");
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(ids_of(&body), vec![1]);
+        assert_eq!(body["items"][0]["tags"], serde_json::json!(["backend", "release-1.2"]));
+    }
+
+    #[actix_web::test]
+    async fn test_get_todos_invalid_due_before() {
+        let request = test::TestRequest::get()
+            .uri("/todos?due_before=tomorrow");
+
+        let response = test_request(configure, request, http::StatusCode::BAD_REQUEST).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["code"], "ValidationFailed");
+    }
+
+    #[actix_web::test]
+    async fn test_get_todos_malformed_namespace() {
+        let cases = [
+            HeaderValue::from_bytes("wörk".as_bytes()).unwrap(),
+            HeaderValue::from_bytes(&[0xff, 0xfe]).unwrap(),
+            HeaderValue::from_static("a b"),
+            HeaderValue::from_static(""),
+            HeaderValue::from_str(&"n".repeat(37)).unwrap(),
+        ];
+        for namespace in cases {
+            let request = test::TestRequest::get()
+                .uri("/todos")
+                .insert_header(("t-ns", namespace));
+
+            let response = test_request(configure, request, http::StatusCode::BAD_REQUEST).await;
+            let body = json_body(response).await;
+
+            assert_eq!(body["code"], "ValidationFailed");
+        }
+
+        let request = test::TestRequest::get()
+            .uri("/todos?ns=%3Cscript%3E");
+
+        test_request(configure, request, http::StatusCode::BAD_REQUEST).await;
+    }
+
+    fn ids_of(page: &Value) -> Vec<i64> {
+        page["items"].as_array().unwrap()
+            .iter()
+            .map(|it| it["id"].as_i64().unwrap())
+            .collect()
     }
 }
@@ -0,0 +1,109 @@
+use actix_web::{HttpResponse, web};
+
+use common::model::ExportFormat;
+
+use crate::{Namespace, TodoDomainTrait};
+use super::export_document;
+
+/// read-only iCalendar feed of the namespace, a `VTODO` per todo in the manual order.
+/// `?ns=` and `?access_token=` stand for the headers, which a calendar app cannot send
+pub async fn get_todos_ics(
+    domain: web::Data<dyn TodoDomainTrait>,
+    namespace: web::ReqData<Namespace>,
+) -> HttpResponse {
+    let format = ExportFormat::Ics;
+
+    HttpResponse::Ok()
+        .content_type(format.content_type())
+        .streaming(export_document(domain, namespace.get(), format))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{App, http, test, web};
+    use actix_web::body::to_bytes;
+    use actix_web::middleware::from_fn;
+
+    use crate::domains::auth_domain::AuthDomainTrait;
+    use crate::domains::todo_domain::TodoDomainTrait;
+    use crate::handlers::{authenticate, authenticate_key_query, authorize};
+    use crate::tests::{MockAuthDomain, MockTodoDomain, test_request_at};
+    use super::get_todos_ics;
+
+    async fn get_ics(uri: &str) -> String {
+        let request = test::TestRequest::get()
+            .uri(uri);
+
+        let response = test_request_at("", |cfg| {
+            cfg.route("/todos.ics", web::get().to(get_todos_ics));
+        }, request, http::StatusCode::OK).await;
+        assert_eq!(response.headers().get("content-type").unwrap(), "text/calendar; charset=utf-8");
+
+        let body = to_bytes(response.into_body()).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_get_todos_ics() {
+        let calendar = get_ics("/todos.ics?ns=default").await;
+
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"), "{}", calendar);
+        assert!(calendar.ends_with("END:VTODO\r\nEND:VCALENDAR\r\n"), "{}", calendar);
+        assert_eq!(3, calendar.matches("BEGIN:VTODO\r\n").count());
+        assert!(calendar.split("\r\n").all(|it| it.len() <= 75), "{}", calendar);
+
+        let first = calendar.split("BEGIN:VTODO\r\n").find(|it| it.contains("UID:default/1@")).unwrap();
+        assert!(first.contains("SUMMARY:first thing\r\n"), "{}", first);
+        assert!(first.contains("STATUS:NEEDS-ACTION\r\n"), "{}", first);
+        assert!(first.contains("CREATED:19991201T000000Z\r\n"), "{}", first);
+        assert!(first.contains("DUE:20000101T000000Z\r\n"), "{}", first);
+        assert!(first.contains("PRIORITY:9\r\n"), "{}", first);
+        assert!(first.contains("CATEGORIES:backend,release-1.2\r\n"), "{}", first);
+
+        let second = calendar.split("BEGIN:VTODO\r\n").find(|it| it.contains("UID:default/2@")).unwrap();
+        assert!(second.contains("STATUS:COMPLETED\r\n"), "{}", second);
+        assert!(second.contains("COMPLETED:"), "{}", second);
+
+        let third = calendar.split("BEGIN:VTODO\r\n").find(|it| it.contains("UID:default/3@")).unwrap();
+        assert!(third.contains("RELATED-TO:default/1@rust-fullstack-todo\r\n"), "{}", third);
+    }
+
+    #[actix_web::test]
+    async fn test_get_todos_ics_empty() {
+        let calendar = get_ics("/todos.ics?ns=empty").await;
+
+        assert_eq!(
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//rust-fullstack-todo//todos//EN\r\nX-WR-CALNAME:empty\r\nEND:VCALENDAR\r\n",
+            calendar,
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_get_todos_ics_api_key() {
+        let domain = Arc::new(MockTodoDomain::default()) as Arc<dyn TodoDomainTrait>;
+        let auth_domain = Arc::new(MockAuthDomain::default()) as Arc<dyn AuthDomainTrait>;
+        let app = test::init_service(App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(web::Data::from(domain))
+            .app_data(web::Data::from(auth_domain))
+            .service(web::resource("/todos.ics")
+                .wrap(from_fn(authorize))
+                .wrap(from_fn(authenticate_key_query))
+                .route(web::get().to(get_todos_ics)))).await;
+
+        let request = test::TestRequest::get().uri("/todos.ics?api_key=tk_shared-viewer").to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), http::StatusCode::OK);
+
+        let body = to_bytes(response.into_body()).await.unwrap();
+        assert!(String::from_utf8(body.to_vec()).unwrap().contains("X-WR-CALNAME:shared\r\n"));
+
+        let request = test::TestRequest::get().uri("/todos.ics?ns=shared").to_request();
+        assert_eq!(test::call_service(&app, request).await.status(), http::StatusCode::UNAUTHORIZED);
+
+        let request = test::TestRequest::get().uri("/todos.ics?api_key=tk_revoked").to_request();
+        assert_eq!(test::call_service(&app, request).await.status(), http::StatusCode::UNAUTHORIZED);
+    }
+}
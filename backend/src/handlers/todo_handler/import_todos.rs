@@ -0,0 +1,125 @@
+use actix_web::{HttpResponse, web};
+
+use common::model::{ErrorResponse, FormatQuery, ImportResponse, ImportResult, ImportTodo, TodoResponse};
+
+use crate::{Namespace, TodoDomainTrait};
+use crate::domains::todo_domain::{self, Imported, MAX_IMPORT_SIZE};
+use crate::handlers::ApiError;
+
+/// room for a document of `MAX_IMPORT_SIZE` todos
+pub const MAX_IMPORT_BYTES: usize = 4 * 1024 * 1024;
+
+/// create the todos of a document keeping their status and `created_at`, the duplicates are reported and skipped
+pub async fn import_todos(
+    domain: web::Data<dyn TodoDomainTrait>,
+    namespace: web::ReqData<Namespace>,
+    query: web::Query<FormatQuery>,
+    body: web::Bytes,
+) -> Result<HttpResponse, ApiError> {
+    let namespace = namespace.writable()?;
+    let document = std::str::from_utf8(&body).map_err(ApiError::validation)?;
+    let todos = query.into_inner().format.parse(document).map_err(ApiError::validation)?;
+
+    if todos.is_empty() || todos.len() > MAX_IMPORT_SIZE {
+        return Err(ApiError::validation(format!("expect 1 to {} todos", MAX_IMPORT_SIZE)));
+    }
+
+    let results = domain.import_todos(namespace, todos.into_iter().map(todo_domain::ImportTodo::from).collect()).await?
+        .into_iter()
+        .map(|result| match result {
+            Ok(Imported::Created(todo)) => ImportResult::Created(TodoResponse::from(todo)),
+            Ok(Imported::Duplicate(todo)) => ImportResult::Duplicate(TodoResponse::from(todo)),
+            Err(err) => ImportResult::Failed(ErrorResponse::from(ApiError::from(err))),
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(ImportResponse { results }))
+}
+
+impl From<ImportTodo> for todo_domain::ImportTodo {
+    fn from(todo: ImportTodo) -> Self {
+        Self {
+            source_id: todo.id,
+            content: todo.content,
+            status: todo.status,
+            created_at: todo.created_at,
+            due_at: todo.due_at,
+            priority: todo.priority,
+            tags: todo.tags,
+            parent_id: todo.parent_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+    use serde_json::json;
+
+    use crate::handlers::todo_handler::configure;
+    use crate::tests::{json_body, test_request};
+
+    #[actix_web::test]
+    async fn test_import_todos() {
+        let request = test::TestRequest::post()
+            .uri("/todos/import")
+            .set_json(json!({"todos": [
+                {"id": 1, "content": "imported", "status": "done", "created_at": "2020-01-01T00:00:00Z", "tags": ["local"]},
+                {"id": 2, "content": "imported subtask", "status": "todo", "created_at": "2020-01-02T00:00:00Z", "parent_id": 1},
+                {"id": 3, "content": "orphan", "status": "todo", "created_at": "2020-01-03T00:00:00Z", "parent_id": 9},
+                {"id": 4, "content": "imported", "status": "done", "created_at": "2020-01-01T00:00:00Z"},
+            ]}));
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+        let results = body["results"].as_array().unwrap();
+
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0]["created"]["status"], "done");
+        assert_eq!(results[0]["created"]["created_at"], "2020-01-01T00:00:00Z");
+        assert_eq!(results[0]["created"]["tags"], json!(["local"]));
+        assert_eq!(results[1]["created"]["parent_id"], results[0]["created"]["id"]);
+        assert_eq!(results[2]["created"]["parent_id"], json!(null));
+        assert_eq!(results[3]["created"]["status"], "done");
+    }
+
+    #[actix_web::test]
+    async fn test_import_todos_markdown() {
+        let request = test::TestRequest::post()
+            .uri("/todos/import?format=md")
+            .set_payload("# plans\n\n- [ ] plain task\n- [x] done task\nnot a task\n- [ ] hand-written <!-- note -->\n");
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["results"][0]["created"]["content"], "plain task");
+        assert_eq!(body["results"][1]["created"]["status"], "done");
+        // a comment which is not the meta is kept in the content
+        assert_eq!(body["results"][2]["created"]["content"], "hand-written <!-- note -->");
+        assert_eq!(body["results"].as_array().unwrap().len(), 3);
+    }
+
+    #[actix_web::test]
+    async fn test_import_todos_invalid() {
+        let request = test::TestRequest::post()
+            .uri("/todos/import?format=csv")
+            .set_payload("id,content\nnot a number,invalid");
+
+        let response = test_request(configure, request, http::StatusCode::BAD_REQUEST).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["code"], "ValidationFailed");
+    }
+
+    #[actix_web::test]
+    async fn test_import_todos_empty() {
+        let request = test::TestRequest::post()
+            .uri("/todos/import")
+            .set_json(json!({"todos": []}));
+
+        let response = test_request(configure, request, http::StatusCode::BAD_REQUEST).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["code"], "ValidationFailed");
+    }
+}
@@ -1,47 +1,74 @@
-use std::fmt::{Debug, Display, Formatter};
-
+use actix_web::http::header::{ETag, EntityTag, IfMatch};
 use actix_web::web;
 
+pub use batch::*;
 pub use clear_todos::*;
 use common::model::TodoResponse;
 pub use create_todo::*;
+pub use export_todos::*;
+pub use get_changes::*;
+pub use get_children::*;
 pub use get_todo_by_id::*;
+pub use get_todo_events::*;
 pub use get_todos::*;
+pub use get_todos_ics::*;
+pub use import_todos::*;
+pub use move_todo::*;
+pub use sync_todos::*;
 pub use update_todo::*;
 
 use crate::domains::todo_domain::Todo;
+use crate::handlers::{ApiError, configure_extractors};
 
 mod create_todo;
 mod get_todos;
 mod update_todo;
 mod clear_todos;
 mod get_todo_by_id;
+mod move_todo;
+mod get_children;
+mod batch;
+mod get_todo_events;
+mod sync_todos;
+mod get_changes;
+mod import_todos;
+mod export_todos;
+mod get_todos_ics;
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
+    configure_extractors(cfg);
+
     cfg.route("", web::get().to(get_todos));
     cfg.route("", web::post().to(create_todo));
     cfg.route("", web::delete().to(clear_todos));
+    cfg.route("/batch", web::post().to(batch));
+    cfg.route("/changes", web::get().to(get_changes));
+    cfg.service(web::resource("/import")
+        .app_data(web::PayloadConfig::new(MAX_IMPORT_BYTES))
+        .route(web::post().to(import_todos)));
+    cfg.route("/export", web::get().to(export_todos));
 
     cfg.route("/{id}", web::get().to(get_todo_by_id));
     cfg.route("/{id}", web::patch().to(update_todo));
+    cfg.route("/{id}/move", web::post().to(move_todo));
+    cfg.route("/{id}/children", web::get().to(get_children));
+    cfg.route("/{id}/{status}", web::patch().to(update_todo_status));
 }
 
-#[derive(thiserror::Error, Debug)]
-pub struct WrappedAnyhowError {
-    err: anyhow::Error,
-}
-
-impl Display for WrappedAnyhowError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.err)
-    }
+/// the version of the todo as a strong `ETag`, e.g. `"3"`
+pub fn etag(todo: &Todo) -> ETag {
+    ETag(EntityTag::new_strong(todo.version.to_string()))
 }
 
-impl actix_web::error::ResponseError for WrappedAnyhowError {}
-
-impl From<anyhow::Error> for WrappedAnyhowError {
-    fn from(err: anyhow::Error) -> Self {
-        Self { err }
+/// the version expected by `If-Match`, `None` for `*` or without the header
+pub fn if_match(header: Option<web::Header<IfMatch>>) -> Result<Option<i32>, ApiError> {
+    match header.map(web::Header::into_inner) {
+        None | Some(IfMatch::Any) => Ok(None),
+        Some(IfMatch::Items(tags)) if tags.is_empty() => Ok(None),
+        Some(IfMatch::Items(tags)) => match tags.as_slice() {
+            [tag] if !tag.weak => tag.tag().parse().ok(),
+            _ => None,
+        }.map(Some).ok_or_else(|| ApiError::validation("expect a version as `If-Match`, e.g. `\"3\"`")),
     }
 }
 
@@ -54,6 +81,13 @@ impl From<Todo> for TodoResponse {
             status: todo.status,
             created_at: todo.created_at,
             updated_at: todo.updated_at,
+            due_at: todo.due_at,
+            priority: todo.priority,
+            tags: todo.tags,
+            parent_id: todo.parent_id,
+            version: todo.version,
+            rank: todo.hit.as_ref().map(|it| it.rank),
+            snippet: todo.hit.map(|it| it.snippet),
         }
     }
 }
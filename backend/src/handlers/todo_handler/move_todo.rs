@@ -0,0 +1,87 @@
+use actix_web::{HttpResponse, web};
+use actix_web::http::header::IfMatch;
+use common::model::{MoveTodoRequest, TodoResponse};
+use crate::domains::todo_domain::MoveTo;
+use crate::handlers::ApiError;
+use crate::handlers::todo_handler::{etag, if_match};
+use crate::{Namespace, TodoDomainTrait};
+
+pub async fn move_todo(
+    domain: web::Data<dyn TodoDomainTrait>,
+    namespace: web::ReqData<Namespace>,
+    path: web::Path<i32>,
+    body: web::Json<MoveTodoRequest>,
+    header: Option<web::Header<IfMatch>>,
+) -> Result<HttpResponse, ApiError> {
+    let id = path.into_inner();
+
+    let to = match body.into_inner() {
+        MoveTodoRequest { before: Some(target), after: None } => MoveTo::Before(target),
+        MoveTodoRequest { before: None, after: Some(target) } => MoveTo::After(target),
+        _ => return Err(ApiError::validation("expect exactly one of `before` or `after`")),
+    };
+
+    let res = domain.move_todo((namespace.writable()?, id), to, if_match(header)?).await?;
+
+    Ok(HttpResponse::Ok().insert_header(etag(&res)).json(TodoResponse::from(res)))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http, test};
+    use serde_json::json;
+
+    use crate::handlers::todo_handler::configure;
+    use crate::tests::{json_body, test_request};
+
+    #[actix_web::test]
+    async fn test_move_todo() {
+        let request = test::TestRequest::post()
+            .uri("/todos/1/move")
+            .set_json(json!({"before": 3}));
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["id"], 1);
+    }
+
+    #[actix_web::test]
+    async fn test_move_todo_target_not_found() {
+        let request = test::TestRequest::post()
+            .uri("/todos/1/move")
+            .set_json(json!({"after": 999}));
+
+        let response = test_request(configure, request, http::StatusCode::NOT_FOUND).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["code"], "TodoNotFound");
+        assert_eq!(body["message"], "Todo [id: 999] not found.");
+    }
+
+    #[actix_web::test]
+    async fn test_move_todo_if_match_invalid() {
+        let request = test::TestRequest::post()
+            .uri("/todos/1/move")
+            .insert_header(("If-Match", "W/\"1\""))
+            .set_json(json!({"before": 3}));
+
+        let response = test_request(configure, request, http::StatusCode::BAD_REQUEST).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["code"], "ValidationFailed");
+    }
+
+    #[actix_web::test]
+    async fn test_move_todo_ambiguous() {
+        let request = test::TestRequest::post()
+            .uri("/todos/1/move")
+            .set_json(json!({"before": 2, "after": 3}));
+
+        let response = test_request(configure, request, http::StatusCode::BAD_REQUEST).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["code"], "ValidationFailed");
+        assert_eq!(body["message"], "expect exactly one of `before` or `after`");
+    }
+}
@@ -0,0 +1,235 @@
+use std::sync::Arc;
+
+use actix_web::{HttpRequest, HttpResponse, web};
+use actix_ws::{Message, MessageStream, Session};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::Receiver;
+
+use common::model::{BatchOperation, BatchResult, SyncRequest, SyncResponse, TodoEvent, TodoResponse};
+
+use crate::{Namespace, TodoDomainTrait};
+use crate::domains::todo_domain::BatchOp;
+use crate::domains::todo_events::{TodoChange, TodoEvents};
+use crate::handlers::{Access, ApiError};
+
+/// a WebSocket applying the `SyncRequest`s of the client and pushing the changes in the namespace,
+/// the role of the caller is checked again for each mutation
+pub async fn sync_todos(
+    req: HttpRequest,
+    body: web::Payload,
+    domain: web::Data<dyn TodoDomainTrait>,
+    events: web::Data<TodoEvents>,
+    namespace: web::ReqData<Namespace>,
+) -> actix_web::Result<HttpResponse> {
+    let namespace = namespace.into_inner();
+    let access = Access::of(&req);
+    let (response, session, messages) = actix_ws::handle(&req, body)?;
+
+    actix_web::rt::spawn(sync(session, messages, domain.into_inner(), events.subscribe(), namespace, access));
+
+    Ok(response)
+}
+
+async fn sync(
+    mut session: Session,
+    mut messages: MessageStream,
+    domain: Arc<dyn TodoDomainTrait>,
+    mut changes: Receiver<TodoChange>,
+    namespace: Namespace,
+    access: Access,
+) {
+    loop {
+        let response = tokio::select! {
+            message = messages.recv() => match message {
+                Some(Ok(Message::Text(text))) => Some(apply(&*domain, &access, &namespace, &text).await),
+                Some(Ok(Message::Ping(bytes))) => {
+                    if session.pong(&bytes).await.is_err() {
+                        return;
+                    }
+                    None
+                }
+                Some(Ok(Message::Close(reason))) => {
+                    let _ = session.close(reason).await;
+                    return;
+                }
+                Some(Ok(_)) => None,
+                Some(Err(_)) | None => break,
+            },
+            change = changes.recv() => match change {
+                Ok(change) if change.namespace == namespace.as_str() => Some(SyncResponse::Change { event: change.event }),
+                Ok(_) => None,
+                Err(RecvError::Lagged(_)) => Some(SyncResponse::Change { event: TodoEvent::Lagged }),
+                Err(RecvError::Closed) => break,
+            },
+        };
+
+        if let Some(response) = response {
+            if session.text(serde_json::to_string(&response).unwrap()).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    let _ = session.close(None).await;
+}
+
+/// the response to a text message of the client, a mutation of a viewer fails with `ErrorCode::Forbidden`
+async fn apply(domain: &dyn TodoDomainTrait, access: &Access, namespace: &Namespace, text: &str) -> SyncResponse {
+    match serde_json::from_str::<SyncRequest>(text) {
+        Ok(SyncRequest::Mutate { seq, op, if_match }) => SyncResponse::Ack {
+            seq,
+            result: match mutate(domain, access, namespace, op, if_match).await {
+                Ok(todo) => BatchResult::Ok(todo),
+                Err(err) => BatchResult::Error(err.into()),
+            },
+        },
+        Err(err) => SyncResponse::Error { error: ApiError::validation(err).into() },
+    }
+}
+
+/// a batch of one operation, with the role the caller has now rather than at the connect
+async fn mutate(domain: &dyn TodoDomainTrait, access: &Access, namespace: &Namespace, op: BatchOperation, if_match: Option<i32>) -> Result<TodoResponse, ApiError> {
+    let namespace = access.namespace(namespace.as_str()).await?.writable()?;
+    let mut op = BatchOp::try_from(op)?;
+    if let BatchOp::Update(_, to_update) = &mut op {
+        to_update.if_match = if_match;
+    }
+
+    let todo = domain.batch(namespace, vec![op]).await?
+        .pop()
+        .expect("a result per operation")?;
+
+    Ok(TodoResponse::from(todo))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http, test, web};
+    use serde_json::{json, Value};
+
+    use common::model::{BatchResult, ErrorCode, NamespaceRole, SyncResponse};
+
+    use crate::domains::auth_domain::{AuthConfig, AuthDomainTrait};
+    use crate::handlers::{Access, Caller, Namespace};
+    use crate::tests::{MockAuthDomain, MockTodoDomain, test_request_at};
+    use super::{apply, sync_todos};
+
+    fn configure(cfg: &mut web::ServiceConfig) {
+        cfg.route("/todos/sync", web::get().to(sync_todos));
+    }
+
+    fn access(domain: Arc<MockAuthDomain>, caller: Caller) -> Access {
+        Access { domain, config: AuthConfig::default(), caller, api_key: None }
+    }
+
+    /// bob, who views `shared`
+    fn bob() -> Caller {
+        Caller::User(MockAuthDomain::fixtures()[1].clone())
+    }
+
+    async fn apply_json(request: Value) -> SyncResponse {
+        let access = access(Arc::new(MockAuthDomain::default()), Caller::Anonymous);
+        apply(&MockTodoDomain::default(), &access, &Namespace::new("default", NamespaceRole::Editor), &request.to_string()).await
+    }
+
+    fn code_of(response: SyncResponse) -> Option<ErrorCode> {
+        match response {
+            SyncResponse::Ack { result: BatchResult::Error(error), .. } => Some(error.code),
+            SyncResponse::Ack { result: BatchResult::Ok(_), .. } => None,
+            other => panic!("expect an ack, got {:?}", other),
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_apply() {
+        let response = apply_json(json!({
+            "type": "mutate",
+            "seq": 1,
+            "op": {"op": "create", "content": "created in sync"}
+        })).await;
+
+        match response {
+            SyncResponse::Ack { seq: 1, result: BatchResult::Ok(todo) } => assert_eq!(todo.content, "created in sync"),
+            other => panic!("expect an ack, got {:?}", other),
+        }
+
+        let response = apply_json(json!({
+            "type": "mutate",
+            "seq": 2,
+            "op": {"op": "update", "id": 3, "content": "updated in sync"},
+            "if_match": 1
+        })).await;
+
+        match response {
+            SyncResponse::Ack { seq: 2, result: BatchResult::Ok(todo) } => assert_eq!(todo.version, 2),
+            other => panic!("expect an ack, got {:?}", other),
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_apply_failed() {
+        let response = apply_json(json!({
+            "type": "mutate",
+            "seq": 1,
+            "op": {"op": "status", "id": 999, "status": "done"}
+        })).await;
+
+        match response {
+            SyncResponse::Ack { seq: 1, result: BatchResult::Error(error) } => assert_eq!(error.code, ErrorCode::TodoNotFound),
+            other => panic!("expect an ack, got {:?}", other),
+        }
+
+        let response = apply_json(json!({"type": "mutate", "seq": 2})).await;
+
+        match response {
+            SyncResponse::Error { error } => assert_eq!(error.code, ErrorCode::ValidationFailed),
+            other => panic!("expect an error, got {:?}", other),
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_apply_viewer() {
+        let request = json!({"type": "mutate", "seq": 1, "op": {"op": "create", "content": "viewed only"}});
+        let access = access(Arc::new(MockAuthDomain::default()), bob());
+        let response = apply(&MockTodoDomain::default(), &access, &Namespace::new("shared", NamespaceRole::Viewer), &request.to_string()).await;
+
+        assert_eq!(Some(ErrorCode::Forbidden), code_of(response));
+    }
+
+    #[actix_web::test]
+    async fn test_apply_demoted() {
+        let request = json!({"type": "mutate", "seq": 1, "op": {"op": "create", "content": "created in sync"}}).to_string();
+        let domain = Arc::new(MockAuthDomain::default());
+        domain.update_member("shared", 2, NamespaceRole::Editor).await.unwrap();
+        let todos = MockTodoDomain::default();
+        // the socket is opened as an editor
+        let (access, namespace) = (access(domain.clone(), bob()), Namespace::new("shared", NamespaceRole::Editor));
+
+        assert_eq!(None, code_of(apply(&todos, &access, &namespace, &request).await));
+
+        domain.update_member("shared", 2, NamespaceRole::Viewer).await.unwrap();
+        assert_eq!(Some(ErrorCode::Forbidden), code_of(apply(&todos, &access, &namespace, &request).await));
+
+        domain.remove_member("shared", 2).await.unwrap();
+        assert_eq!(Some(ErrorCode::Forbidden), code_of(apply(&todos, &access, &namespace, &request).await));
+    }
+
+    #[actix_web::test]
+    async fn test_sync_todos_handshake() {
+        let request = test::TestRequest::get()
+            .uri("/todos/sync?ns=default")
+            .insert_header(("upgrade", "websocket"))
+            .insert_header(("connection", "upgrade"))
+            .insert_header(("sec-websocket-version", "13"))
+            .insert_header(("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ=="));
+
+        test_request_at("", configure, request, http::StatusCode::SWITCHING_PROTOCOLS).await;
+
+        let request = test::TestRequest::get()
+            .uri("/todos/sync");
+
+        test_request_at("", configure, request, http::StatusCode::BAD_REQUEST).await;
+    }
+}
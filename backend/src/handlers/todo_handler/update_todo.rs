@@ -1,9 +1,10 @@
 use actix_web::{HttpResponse, web};
-use serde_json::json;
-use common::model::UpdateTodoRequest;
+use actix_web::http::header::IfMatch;
+use serde::Deserialize;
+use common::model::{TodoStatus, UpdateTodoRequest};
 use crate::domains::todo_domain::UpdateTodo;
-use crate::handlers::todo_handler::TodoResponse;
-use crate::todo_handler::WrappedAnyhowError;
+use crate::handlers::todo_handler::{etag, if_match, TodoResponse};
+use crate::handlers::ApiError;
 use crate::{Namespace, TodoDomainTrait};
 
 pub async fn update_todo(
@@ -11,20 +12,55 @@ pub async fn update_todo(
     namespace: web::ReqData<Namespace>,
     path: web::Path<i32>,
     body: web::Json<UpdateTodoRequest>,
-) -> Result<HttpResponse, WrappedAnyhowError> {
+    header: Option<web::Header<IfMatch>>,
+) -> Result<HttpResponse, ApiError> {
     let id = path.into_inner();
-    let namespace = namespace.get();
+    let namespace = namespace.writable()?;
     let body = body.into_inner();
 
-    if body.status.is_none() && body.content.is_none() {
-        return Ok(HttpResponse::BadRequest().json(json!({
-            "error": "expect one of `status` or `content`"
-        })));
+    validate_update(&body)?;
+
+    let res = domain.update_todo((namespace, id), UpdateTodo {
+        if_match: if_match(header)?,
+        ..UpdateTodo::from(body)
+    }).await?;
+
+    Ok(HttpResponse::Ok().insert_header(etag(&res)).json(TodoResponse::from(res)))
+}
+
+/// an update changes at least one field
+pub(super) fn validate_update(body: &UpdateTodoRequest) -> Result<(), ApiError> {
+    if let UpdateTodoRequest { content: None, status: None, due_at: None, priority: None, tags: None, .. } = body {
+        return Err(ApiError::validation("expect one of `status`, `content`, `due_at`, `priority` or `tags`"));
     }
 
-    let res = domain.update_todo((namespace, id), UpdateTodo::from(body)).await?;
+    Ok(())
+}
+
+#[derive(Deserialize)]
+pub struct UpdateTodoStatusQuery {
+    /// `?cascade=true` closes the open subtasks of a todo marked `done`
+    #[serde(default)]
+    cascade: bool,
+}
 
-    Ok(HttpResponse::Ok().json(TodoResponse::from(res)))
+pub async fn update_todo_status(
+    domain: web::Data<dyn TodoDomainTrait>,
+    namespace: web::ReqData<Namespace>,
+    path: web::Path<(i32, TodoStatus)>,
+    query: web::Query<UpdateTodoStatusQuery>,
+    header: Option<web::Header<IfMatch>>,
+) -> Result<HttpResponse, ApiError> {
+    let (id, status) = path.into_inner();
+
+    let res = domain.update_todo((namespace.writable()?, id), UpdateTodo {
+        status: Some(status),
+        cascade: query.cascade,
+        if_match: if_match(header)?,
+        ..Default::default()
+    }).await?;
+
+    Ok(HttpResponse::Ok().insert_header(etag(&res)).json(TodoResponse::from(res)))
 }
 
 impl From<UpdateTodoRequest> for UpdateTodo {
@@ -32,75 +68,180 @@ impl From<UpdateTodoRequest> for UpdateTodo {
         Self {
             content: req.content,
             status: req.status,
+            due_at: req.due_at,
+            priority: req.priority,
+            tags: req.tags,
+            cascade: req.cascade,
+            if_match: None,
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use actix_web::test;
+    use actix_web::{http, test};
+    use actix_web::body::to_bytes;
+    use serde_json::{json, Value};
+
+    use crate::handlers::todo_handler::configure;
+    use crate::tests::{json_body, test_request};
 
     #[actix_web::test]
     async fn test_update_todo() {
         let request = test::TestRequest::patch()
-            .uri("/3")
+            .uri("/todos/3")
             .set_json(json!({"content": "updated a todo"}));
 
         let response = test_request(configure, request, http::StatusCode::OK).await;
-        let body_bytes = to_bytes(response.into_body()).await.unwrap();
+        let body = json_body(response).await;
 
-        assert_eq!(body_bytes, r##"{"id":3,"content":"updated a todo","status":"Todo"}"##, );
+        assert_eq!(body["id"], 3);
+        assert_eq!(body["content"], "updated a todo");
+        assert_eq!(body["status"], "todo");
     }
 
     #[actix_web::test]
     async fn test_update_todo_not_found() {
         let request = test::TestRequest::patch()
-            .uri("/999")
+            .uri("/todos/999")
             .set_json(json!({"content": "updated a todo"}));
 
         let response = test_request(configure, request, http::StatusCode::NOT_FOUND).await;
         let body_bytes = to_bytes(response.into_body()).await.unwrap();
 
-        assert_eq!(body_bytes, r##"{"code":"TodoNotFound","message":"Todo [id: 999] not found.}"##, );
+        assert_eq!(body_bytes, r##"{"code":"TodoNotFound","message":"Todo [id: 999] not found."}"##, );
+    }
+
+    #[actix_web::test]
+    async fn test_update_todo_nothing_to_update() {
+        let request = test::TestRequest::patch()
+            .uri("/todos/3")
+            .set_json(json!({}));
+
+        let response = test_request(configure, request, http::StatusCode::BAD_REQUEST).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["code"], "ValidationFailed");
+        assert_eq!(body["message"], "expect one of `status`, `content`, `due_at`, `priority` or `tags`");
+    }
+
+    #[actix_web::test]
+    async fn test_update_todo_due_at() {
+        let request = test::TestRequest::patch()
+            .uri("/todos/3")
+            .set_json(json!({"due_at": "2024-01-01T08:00:00+08:00"}));
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["due_at"], "2024-01-01T00:00:00Z");
+        assert_eq!(body["content"], "todo content");
+
+        let request = test::TestRequest::patch()
+            .uri("/todos/1")
+            .set_json(json!({"due_at": null}));
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["due_at"], Value::Null);
+        assert_eq!(body["content"], "first thing");
+    }
+
+    #[actix_web::test]
+    async fn test_update_todo_tags() {
+        let request = test::TestRequest::patch()
+            .uri("/todos/1")
+            .set_json(json!({"tags": []}));
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["tags"], json!([]));
+    }
+
+    #[actix_web::test]
+    async fn test_update_todo_invalid_body() {
+        let request = test::TestRequest::patch()
+            .uri("/todos/3")
+            .set_json(json!({"status": "unknown"}));
+
+        let response = test_request(configure, request, http::StatusCode::BAD_REQUEST).await;
+        let body = json_body(response).await;
+
+        assert_eq!(body["code"], "ValidationFailed");
     }
 
     #[actix_web::test]
     async fn test_update_todo_invalid_id_not_number() {
         let request = test::TestRequest::patch()
-            .uri("/nan")
+            .uri("/todos/nan")
             .set_json(json!({"content": "updated a todo"}));
 
-        let response = test_request(configure, request, http::StatusCode::NOT_FOUND).await;
-        let body_bytes = to_bytes(response.into_body()).await.unwrap();
+        let response = test_request(configure, request, http::StatusCode::BAD_REQUEST).await;
+        let body = json_body(response).await;
 
-        assert_eq!(body_bytes, r##"{"id":3,"content":"updated a todo","status":"Todo"}"##, );
+        assert_eq!(body["code"], "ValidationFailed");
     }
 
     #[actix_web::test]
     async fn test_update_todo_status() {
         let request = test::TestRequest::patch()
-            .uri("/3/done");
+            .uri("/todos/3/done");
 
         let response = test_request(configure, request, http::StatusCode::OK).await;
-        let body_bytes = to_bytes(response.into_body()).await.unwrap();
+        let body = json_body(response).await;
 
-        assert_eq!(body_bytes, r##"{"id":3,"content":"todo content","status":"Done"}"##, );
+        assert_eq!(body["id"], 3);
+        assert_eq!(body["content"], "todo content");
+        assert_eq!(body["status"], "done");
     }
 
     #[actix_web::test]
     async fn test_update_todo_status_invalid_input_status() {
         let request = test::TestRequest::patch()
-            .uri("/3/no-status");
+            .uri("/todos/3/no-status");
 
-        let response = test_request(configure, request, http::StatusCode::NOT_FOUND).await;
-        let body_bytes = to_bytes(response.into_body()).await.unwrap();
+        let response = test_request(configure, request, http::StatusCode::BAD_REQUEST).await;
+        let body = json_body(response).await;
 
-        assert_eq!(body_bytes, "unknown variant `no-status`, expected one of `todo`, `done`, `archive`, `deleted`");
+        assert_eq!(body["code"], "ValidationFailed");
+        assert_eq!(body["message"], "Path deserialize error: unknown variant `no-status`, expected one of `todo`, `done`, `archived`, `deleted`");
     }
 
     #[actix_web::test]
-    async fn test_update_todo_status_invalid_tranform_status() {
-        let _request = test::TestRequest::patch()
-            .uri("/3/done");
+    async fn test_update_todo_if_match() {
+        let request = test::TestRequest::patch()
+            .uri("/todos/3")
+            .insert_header(("If-Match", "\"1\""))
+            .set_json(json!({"content": "updated a todo"}));
+
+        let response = test_request(configure, request, http::StatusCode::OK).await;
+        assert_eq!(response.headers().get("ETag").unwrap(), "\"2\"");
+        let body = json_body(response).await;
+
+        assert_eq!(body["version"], 2);
+    }
+
+    #[actix_web::test]
+    async fn test_update_todo_if_match_invalid() {
+        for if_match in ["W/\"1\"", "\"one\"", "\"1\", \"2\""] {
+            let request = test::TestRequest::patch()
+                .uri("/todos/3")
+                .insert_header(("If-Match", if_match))
+                .set_json(json!({"content": "updated a todo"}));
+
+            let response = test_request(configure, request, http::StatusCode::BAD_REQUEST).await;
+            let body = json_body(response).await;
+
+            assert_eq!(body["code"], "ValidationFailed", "{}", if_match);
+        }
+
+        let request = test::TestRequest::patch()
+            .uri("/todos/3")
+            .insert_header(("If-Match", "*"))
+            .set_json(json!({"content": "updated a todo"}));
+
+        test_request(configure, request, http::StatusCode::OK).await;
     }
 }
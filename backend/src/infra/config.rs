@@ -3,15 +3,28 @@ use std::net::TcpListener;
 use config::{ConfigError, Environment, File, FileFormat};
 use serde::Deserialize;
 
+use crate::domains::auth_domain::AuthConfig;
+use crate::domains::purge_job::PurgeConfig;
+use crate::infra::migration::MigrationConfig;
+
 #[derive(Deserialize, Debug)]
 pub struct Server {
     pub port: u16,
+    /// where `GET /metrics` is served apart from the api, e.g. `127.0.0.1:9100`, not at all without it
+    #[serde(default)]
+    pub metrics_address: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
 pub struct Config {
     pub server: Server,
     pub db: deadpool_postgres::Config,
+    #[serde(default)]
+    pub migration: MigrationConfig,
+    #[serde(default)]
+    pub purge: PurgeConfig,
+    #[serde(default)]
+    pub auth: AuthConfig,
 }
 
 impl Config {
@@ -19,6 +32,11 @@ impl Config {
         TcpListener::bind(format!("0.0.0.0:{}", self.server.port))
             .expect("Failed to bind port")
     }
+
+    pub fn metrics_address(&self) -> Option<TcpListener> {
+        self.server.metrics_address.as_ref()
+            .map(|address| TcpListener::bind(address).expect("Failed to bind metrics address"))
+    }
 }
 
 pub fn get() -> Result<Config, ConfigError> {
@@ -0,0 +1,233 @@
+use anyhow::{bail, Result};
+use deadpool_postgres::{Client, Pool};
+use serde::Deserialize;
+
+/// a versioned schema change, `up` applies it and `down` reverts it
+pub struct Migration {
+    pub version: i32,
+    pub name: &'static str,
+    pub up: &'static str,
+    pub down: &'static str,
+}
+
+/// embeds `schemas/{name}.up.sql` and `schemas/{name}.down.sql`
+macro_rules! migration {
+    ($version:literal, $name:literal) => {
+        Migration {
+            version: $version,
+            name: $name,
+            up: include_str!(concat!("../../schemas/", $name, ".up.sql")),
+            down: include_str!(concat!("../../schemas/", $name, ".down.sql")),
+        }
+    };
+}
+
+/// ordered by version, append only
+pub const MIGRATIONS: &[Migration] = &[
+    migration!(1, "0001_init_tables"),
+    migration!(2, "0002_todo_due_at"),
+    migration!(3, "0003_todo_priority_position"),
+    migration!(4, "0004_tags"),
+    migration!(5, "0005_todo_parent"),
+    migration!(6, "0006_todo_version"),
+    migration!(7, "0007_todo_tombstones"),
+    migration!(8, "0008_users"),
+    migration!(9, "0009_members"),
+    migration!(10, "0010_api_keys"),
+    migration!(11, "0011_tickets"),
+    migration!(12, "0012_todo_parent_set_null"),
+    migration!(13, "0013_todo_tombstone_horizons"),
+];
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct MigrationConfig {
+    /// apply the pending migrations on startup
+    pub auto: bool,
+}
+
+impl Default for MigrationConfig {
+    fn default() -> Self {
+        Self { auto: true }
+    }
+}
+
+/// serializes the migrations of concurrent backends
+const LOCK_KEY: i64 = 0x746f646f;
+
+pub fn latest_version() -> i32 {
+    MIGRATIONS.last().map(|it| it.version).unwrap_or_default()
+}
+
+/// the versions applied to the db, ascending
+pub async fn applied_versions(pool: &Pool) -> Result<Vec<i32>> {
+    let client = pool.get().await?;
+
+    query_applied(&client).await
+}
+
+/// refuse a db migrated by a newer binary
+pub async fn check(pool: &Pool) -> Result<()> {
+    let client = pool.get().await?;
+
+    check_applied(&query_applied(&client).await?)
+}
+
+/// apply the pending migrations up to `target`, all of them by default
+pub async fn up(pool: &Pool, target: Option<i32>) -> Result<Vec<i32>> {
+    let target = target.unwrap_or_else(latest_version);
+
+    let mut client = pool.get().await?;
+    client.execute("SELECT pg_advisory_lock($1)", &[&LOCK_KEY]).await?;
+
+    let result = async {
+        create_tracking_table(&client).await?;
+        let applied = query_applied(&client).await?;
+        check_applied(&applied)?;
+
+        let mut migrated = vec![];
+        for migration in MIGRATIONS.iter()
+            .filter(|it| it.version <= target && !applied.contains(&it.version)) {
+            log::info!("applying migration {} {}", migration.version, migration.name);
+
+            let tx = client.transaction().await?;
+            tx.batch_execute(migration.up).await?;
+            tx.execute(
+                "INSERT INTO schema_migrations (version, name, applied_at) VALUES ($1, $2, now())",
+                &[&migration.version, &migration.name],
+            ).await?;
+            tx.commit().await?;
+
+            migrated.push(migration.version);
+        }
+
+        Ok(migrated)
+    }.await;
+
+    unlock(&client).await;
+
+    result
+}
+
+/// revert the applied migrations newer than `target`, newest first
+pub async fn down(pool: &Pool, target: i32) -> Result<Vec<i32>> {
+    let mut client = pool.get().await?;
+    client.execute("SELECT pg_advisory_lock($1)", &[&LOCK_KEY]).await?;
+
+    let result = async {
+        let applied = query_applied(&client).await?;
+        check_applied(&applied)?;
+
+        let mut reverted = vec![];
+        for migration in MIGRATIONS.iter().rev()
+            .filter(|it| it.version > target && applied.contains(&it.version)) {
+            log::info!("reverting migration {} {}", migration.version, migration.name);
+
+            let tx = client.transaction().await?;
+            tx.batch_execute(migration.down).await?;
+            tx.execute("DELETE FROM schema_migrations WHERE version = $1", &[&migration.version]).await?;
+            tx.commit().await?;
+
+            reverted.push(migration.version);
+        }
+
+        Ok(reverted)
+    }.await;
+
+    unlock(&client).await;
+
+    result
+}
+
+/// the lock goes with the session anyway, a failure to unlock must not hide the result of the migrations
+async fn unlock(client: &Client) {
+    if let Err(err) = client.execute("SELECT pg_advisory_unlock($1)", &[&LOCK_KEY]).await {
+        log::error!("failed to release the migration lock: {:?}", err);
+    }
+}
+
+fn check_applied(applied: &[i32]) -> Result<()> {
+    let current = applied.last().copied().unwrap_or_default();
+    let latest = latest_version();
+
+    if current > latest {
+        bail!("db schema version {} is newer than the latest known version {}", current, latest);
+    }
+
+    Ok(())
+}
+
+async fn query_applied(client: &Client) -> Result<Vec<i32>> {
+    let exists: bool = client
+        .query_one("SELECT to_regclass('schema_migrations') IS NOT NULL", &[]).await?
+        .get(0);
+    if !exists {
+        return Ok(vec![]);
+    }
+
+    let rows = client
+        .query("SELECT version FROM schema_migrations ORDER BY version", &[]).await?;
+
+    Ok(rows.iter().map(|r| r.get(0)).collect())
+}
+
+async fn create_tracking_table(client: &Client) -> Result<()> {
+    client.batch_execute(r#"
+        CREATE TABLE IF NOT EXISTS schema_migrations
+        (
+            version    INT          NOT NULL PRIMARY KEY,
+            name       VARCHAR(128) NOT NULL,
+            applied_at TIMESTAMP    NOT NULL
+        );
+    "#).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ::config::{File, FileFormat};
+
+    use crate::infra::{config, db};
+    use super::{MIGRATIONS, MigrationConfig, applied_versions, check, check_applied, latest_version, up};
+
+    #[test]
+    fn ordered_versions() {
+        let versions = MIGRATIONS.iter().map(|it| it.version).collect::<Vec<i32>>();
+        let mut sorted = versions.clone();
+        sorted.sort();
+        sorted.dedup();
+
+        assert_eq!(sorted, versions);
+        assert_eq!(latest_version(), *versions.last().unwrap());
+    }
+
+    #[test]
+    fn partial_config() {
+        // a `[migration]` section without keys, like the other sections
+        let config = ::config::Config::builder()
+            .add_source(File::from_str("", FileFormat::Toml))
+            .build().unwrap()
+            .try_deserialize::<MigrationConfig>().unwrap();
+
+        assert!(config.auto);
+    }
+
+    #[test]
+    fn refuse_newer_schema() {
+        assert!(check_applied(&[]).is_ok());
+        assert!(check_applied(&[latest_version()]).is_ok());
+        assert!(check_applied(&[latest_version(), latest_version() + 1]).is_err());
+    }
+
+    #[actix_web::test]
+    async fn up_to_latest() {
+        let pool = db::must_init(&config::must_get().db);
+
+        up(&pool, None).await.unwrap();
+
+        let applied = applied_versions(&pool).await.unwrap();
+        assert_eq!(Some(&latest_version()), applied.last());
+        assert!(check(&pool).await.is_ok());
+    }
+}
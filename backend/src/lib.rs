@@ -4,34 +4,65 @@ use actix_web::{middleware, App, HttpServer, web};
 use std::net::TcpListener;
 use std::sync::Arc;
 use actix_cors::Cors;
-use actix_web::dev::{Server, Service};
+use actix_web::dev::Server;
+use actix_web::middleware::from_fn;
 use deadpool_postgres::Pool;
 use handlers::Namespace;
+use crate::domains::auth_domain::{AuthConfig, AuthDomain, AuthDomainTrait};
+use crate::domains::purge_job::{PurgeConfig, PurgeJob, PurgeMetrics};
+use crate::domains::tag_domain::{TagDomain, TagDomainTrait};
 use crate::domains::todo_domain::{TodoDomain, TodoDomainTrait};
+use crate::domains::todo_events::TodoEvents;
 use crate::handlers::todo_handler;
 
 pub mod handlers;
 pub mod domains;
 pub mod infra;
 
-pub fn start_server(listener: TcpListener, db_pool: Pool) -> Server {
+/// `pg_config` is for the dedicated connection listening to the `TodoEvents`.
+/// `metrics` serves `GET /metrics` alone, its namespaces are not for the clients of the api
+pub fn start_server(listener: TcpListener, metrics: Option<TcpListener>, db_pool: Pool, pg_config: tokio_postgres::Config, purge: PurgeConfig, auth: AuthConfig) -> Server {
     let address = listener.local_addr().unwrap();
     log::info!("starting HTTP server at {}", address);
 
+    let todo_events = TodoEvents::default();
+    tokio::spawn(todo_events.clone().listen(pg_config));
+
+    let purge_metrics = Arc::new(PurgeMetrics::default());
+    if purge.enabled {
+        tokio::spawn(PurgeJob::new(db_pool.clone(), purge, purge_metrics.clone()).run());
+    }
+    if let Some(metrics) = metrics {
+        log::info!("serving metrics at {}", metrics.local_addr().unwrap());
+        let purge_metrics = purge_metrics.clone();
+        tokio::spawn(HttpServer::new(move || App::new()
+            .app_data(web::Data::from(purge_metrics.clone()))
+            .configure(handlers::metrics_handler::configure))
+            .workers(1)
+            .listen(metrics)
+            .expect("Address is already in use")
+            .run());
+    }
+
     HttpServer::new(move || {
         let todo_domain = TodoDomain::new(db_pool.clone());
         let todo_domain_trait = Arc::new(todo_domain.clone())
             as Arc<dyn TodoDomainTrait>;
+        let tag_domain = Arc::new(TagDomain::new(db_pool.clone()))
+            as Arc<dyn TagDomainTrait>;
+        let auth_domain = Arc::new(AuthDomain::new(db_pool.clone(), auth.clone()))
+            as Arc<dyn AuthDomainTrait>;
 
         App::new()
-            .wrap_fn(|req, srv| {
-                Namespace::inject(&req);
-                srv.call(req)
-            })
-            .wrap(middleware::Logger::default())
+            .wrap(from_fn(handlers::authenticate))
+            .wrap(access_log())
             .wrap(Cors::permissive())
             .app_data(web::Data::from(todo_domain_trait.clone()))
+            .app_data(web::Data::from(tag_domain))
+            .app_data(web::Data::from(auth_domain))
+            .app_data(web::Data::new(auth.clone()))
             .app_data(web::Data::new(todo_domain.clone()))
+            .app_data(web::Data::new(todo_events.clone()))
             .configure(handlers::routes)
     })
         .listen(listener)
@@ -39,16 +70,646 @@ pub fn start_server(listener: TcpListener, db_pool: Pool) -> Server {
         .run()
 }
 
+/// the default format of `Logger` but the request line goes without its query, which may carry `?access_token=`
+fn access_log() -> middleware::Logger {
+    middleware::Logger::new(r#"%a "%{request}xi" %s %b "%{Referer}i" "%{User-Agent}i" %T"#)
+        .custom_request_replace("request", |req| format!("{} {} {:?}", req.method(), req.path(), req.version()))
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
-    use actix_web::{App, test};
+    use std::cmp::Reverse;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use actix_web::{App, test, web};
     use actix_web::body::{BoxBody, to_bytes};
     use actix_web::dev::ServiceResponse;
+    use actix_web::middleware::from_fn;
     use actix_web::http::StatusCode;
     use actix_web::test::TestRequest;
     use actix_web::web::ServiceConfig;
+    use async_trait::async_trait;
+    use chrono::{DateTime, TimeZone, Utc};
+
+    use common::model::{NamespaceRole, TodoPriority, TodoSort, TodoStatus};
+
+    use crate::domains::auth_domain::{API_KEY_PREFIX, ApiKey, AuthConfig, AuthDomainTrait, AuthError, check_api_key_request, check_namespace, check_password, check_username, Invitation, Member, Membership, NewApiKey, Session, Ticket, User};
+    use crate::domains::tag_domain::{normalize_tag_name, Tag, TagDomainTrait, TagError, TagID};
+    use crate::domains::todo_domain::{BatchOp, Changes, CreateTodo, Imported, ImportTodo, ListTodo, MoveTo, Page, POSITION_GAP, SearchHit, Todo, TodoDomainTrait, TodoError, TodoID, UpdateTodo};
+    use crate::domains::todo_events::TodoEvents;
+    use crate::handlers::{authenticate, authorize};
+
+    /// in memory `TodoDomainTrait` seeded with [`MockTodoDomain::fixtures`], a plain store for the handlers:
+    /// the rules of `TodoDomain` are not repeated here but tested against Postgres in `todo_domain::tests`
+    pub struct MockTodoDomain {
+        todos: Mutex<Vec<Todo>>,
+    }
+
+    impl MockTodoDomain {
+        pub fn fixtures() -> Vec<Todo> {
+            vec![
+                (1, "first thing", TodoStatus::Todo, TodoPriority::Low),
+                (2, "second thing", TodoStatus::Done, TodoPriority::High),
+                (3, "todo content", TodoStatus::Todo, TodoPriority::Normal),
+            ].into_iter().map(|(id, content, status, priority)| Todo {
+                id: ("default".to_string(), id),
+                content: content.to_string(),
+                status,
+                created_at: Utc.with_ymd_and_hms(1999, 12, id as u32, 0, 0, 0).unwrap(),
+                due_at: Some(Utc.with_ymd_and_hms(2000, id as u32, 1, 0, 0, 0).unwrap()),
+                priority,
+                position: -(id as i64) * POSITION_GAP,
+                tags: if id == 1 { vec!["backend".to_string(), "release-1.2".to_string()] } else { vec![] },
+                parent_id: if id == 3 { Some(1) } else { None },
+                version: 1,
+                ..Default::default()
+            }).collect()
+        }
+
+        fn find(&self, (namespace, id): &TodoID) -> anyhow::Result<Todo> {
+            self.todos.lock().unwrap()
+                .iter()
+                .find(|it| it.id.0 == *namespace && it.id.1 == *id)
+                .cloned()
+                .ok_or_else(|| TodoError::TodoNotFound(*id).into())
+        }
+
+    }
+
+    impl Default for MockTodoDomain {
+        fn default() -> Self {
+            Self { todos: Mutex::new(Self::fixtures()) }
+        }
+    }
+
+    #[async_trait]
+    impl TodoDomainTrait for MockTodoDomain {
+        async fn get_todo_by_id(&self, id: TodoID) -> anyhow::Result<Todo> {
+            self.find(&id)
+        }
+
+        async fn list_todo(&self, namespace: String, query: ListTodo) -> anyhow::Result<Page<Todo>> {
+            let mut sorted = self.todos.lock().unwrap()
+                .iter()
+                .filter(|it| it.id.0 == namespace)
+                .cloned()
+                .collect::<Vec<Todo>>();
+            match query.sort {
+                TodoSort::Newest => sorted.sort_by_key(|it| Reverse(it.id.1)),
+                TodoSort::Priority => sorted.sort_by_key(|it| Reverse((it.priority, it.id.1))),
+                TodoSort::Manual => sorted.sort_by_key(|it| (it.position, it.id.1)),
+            }
+            let after_cursor = query.cursor
+                .and_then(|cursor| sorted.iter().position(|it| it.id.1 == cursor.id()))
+                .map_or(0, |index| index + 1);
+
+            let rows = sorted.into_iter()
+                .skip(after_cursor)
+                .filter(|it| query.status.is_none_or(|s| it.status == s))
+                .filter(|it| query.q.as_ref().is_none_or(|q| it.content.contains(q.as_str())))
+                .filter(|it| query.due_before.is_none_or(|before| it.due_at.is_some_and(|due_at| due_at < before)))
+                .filter(|it| query.overdue.is_none_or(|overdue| it.is_overdue(Utc::now()) == overdue))
+                .filter(|it| query.tag.as_ref().is_none_or(|tag| it.tags.contains(tag)))
+                .take(query.limit as usize + 1)
+                .map(|mut it| {
+                    if let Some(q) = &query.q {
+                        let snippet = it.content.replace(q.as_str(), &format!("<mark>{}</mark>", q));
+                        it.hit = Some(SearchHit { rank: 1.0, snippet });
+                    }
+                    it
+                })
+                .collect();
+
+            Ok(Page::of(rows, query.limit, query.sort))
+        }
+
+        async fn create_todo(&self, namespace: String, to_create: CreateTodo) -> anyhow::Result<Todo> {
+            let mut todos = self.todos.lock().unwrap();
+            let mut todo = Todo::create(&namespace, &to_create.content);
+            todo.due_at = to_create.due_at;
+            todo.priority = to_create.priority;
+            todo.tags = to_create.tags;
+            todo.parent_id = to_create.parent_id;
+            todo.position = todos.iter().map(|it| it.position).min().unwrap_or_default() - POSITION_GAP;
+            todo.id.1 = todos.iter().map(|it| it.id.1).max().unwrap_or_default() + 1;
+            todo.version = 1;
+            todos.push(todo.clone());
+
+            Ok(todo)
+        }
+
+        async fn update_todo(&self, id: TodoID, to_update: UpdateTodo) -> anyhow::Result<Todo> {
+            let mut todos = self.todos.lock().unwrap();
+            let todo = todos.iter_mut()
+                .find(|it| it.id == id)
+                .ok_or(TodoError::TodoNotFound(id.1))?;
+            if let Some(content) = to_update.content {
+                todo.content = content;
+            }
+            if let Some(status) = to_update.status {
+                todo.status = status;
+            }
+            if let Some(due_at) = to_update.due_at {
+                todo.due_at = due_at;
+            }
+            if let Some(priority) = to_update.priority {
+                todo.priority = priority;
+            }
+            if let Some(tags) = to_update.tags {
+                todo.tags = tags;
+            }
+            todo.version += 1;
+
+            Ok(todo.clone())
+        }
+
+        async fn move_todo(&self, id: TodoID, to: MoveTo, _if_match: Option<i32>) -> anyhow::Result<Todo> {
+            self.find(&id)?;
+            self.find(&(id.0.clone(), to.target()))?;
+
+            let mut todos = self.todos.lock().unwrap();
+            todos.sort_by_key(|it| (it.position, it.id.1));
+            let index = todos.iter().position(|it| it.id == id).unwrap();
+            let moving = todos.remove(index);
+            let target = todos.iter().position(|it| it.id.0 == id.0 && it.id.1 == to.target()).unwrap();
+            let index = match to {
+                MoveTo::Before(_) => target,
+                MoveTo::After(_) => target + 1,
+            };
+            todos.insert(index, moving);
+            for (index, todo) in todos.iter_mut().enumerate() {
+                todo.position = index as i64 * POSITION_GAP;
+            }
+            drop(todos);
+
+            self.find(&id)
+        }
+
+        async fn list_children(&self, id: TodoID) -> anyhow::Result<Vec<Todo>> {
+            self.find(&id)?;
+
+            let mut children = self.todos.lock().unwrap()
+                .iter()
+                .filter(|it| it.id.0 == id.0 && it.parent_id == Some(id.1))
+                .cloned()
+                .collect::<Vec<Todo>>();
+            children.sort_by_key(|it| (it.position, it.id.1));
+
+            Ok(children)
+        }
+
+        async fn clear_todos(&self, namespace: String, ids: Vec<i32>) -> anyhow::Result<()> {
+            self.todos.lock().unwrap()
+                .retain(|it| it.id.0 != namespace || !ids.contains(&it.id.1));
+            Ok(())
+        }
+
+        /// in the order of the todos, all created, a subtask listed before its parent is left top-level
+        async fn import_todos(&self, namespace: String, todos: Vec<ImportTodo>) -> anyhow::Result<Vec<anyhow::Result<Imported>>> {
+            let mut ids = HashMap::new();
+            let mut results = vec![];
+            for to_import in todos {
+                let created = self.create_todo(namespace.clone(), CreateTodo {
+                    content: to_import.content,
+                    due_at: to_import.due_at,
+                    priority: to_import.priority,
+                    tags: to_import.tags,
+                    parent_id: to_import.parent_id.and_then(|parent_id| ids.get(&parent_id).copied()),
+                }).await?;
+                let mut todos = self.todos.lock().unwrap();
+                let todo = todos.iter_mut().find(|it| it.id == created.id).unwrap();
+                todo.status = to_import.status;
+                todo.created_at = to_import.created_at;
+
+                ids.insert(to_import.source_id, todo.id.1);
+                results.push(Ok(Imported::Created(todo.clone())));
+            }
+
+            Ok(results)
+        }
+
+        /// without tombstones, hard-deleted todos are never in the changes
+        async fn list_changes(&self, namespace: String, since: Option<DateTime<Utc>>) -> anyhow::Result<Changes> {
+            let until = Utc::now();
+            let todos = self.todos.lock().unwrap()
+                .iter()
+                .filter(|it| it.id.0 == namespace && since.is_none_or(|since| it.updated_at > since))
+                .cloned()
+                .collect();
+
+            Ok(Changes { todos, deleted: vec![], until, full: since.is_none() })
+        }
+
+        /// the operations one by one, a failed one writes nothing here, there is no savepoint to roll back
+        async fn batch(&self, namespace: String, ops: Vec<BatchOp>) -> anyhow::Result<Vec<anyhow::Result<Todo>>> {
+            let mut results = vec![];
+            for op in ops {
+                results.push(match op {
+                    BatchOp::Create(to_create) => self.create_todo(namespace.clone(), to_create).await,
+                    BatchOp::Update(id, to_update) => self.update_todo((namespace.clone(), id), to_update).await,
+                    BatchOp::Delete(id) => match self.find(&(namespace.clone(), id)) {
+                        Ok(found) => self.clear_todos(namespace.clone(), vec![id]).await.map(|_| found),
+                        Err(err) => Err(err),
+                    },
+                });
+            }
+            Ok(results)
+        }
+    }
+
+    /// in memory `TagDomainTrait` seeded with [`MockTagDomain::fixtures`]
+    pub struct MockTagDomain {
+        tags: Mutex<Vec<Tag>>,
+    }
+
+    impl MockTagDomain {
+        pub fn fixtures() -> Vec<Tag> {
+            vec![(1, "backend"), (2, "release-1.2")]
+                .into_iter()
+                .map(|(id, name)| Tag {
+                    id: ("default".to_string(), id),
+                    name: name.to_string(),
+                    created_at: Utc::now(),
+                })
+                .collect()
+        }
+
+        fn check_unique(&self, namespace: &str, name: &str) -> anyhow::Result<()> {
+            if self.tags.lock().unwrap().iter().any(|it| it.id.0 == namespace && it.name == name) {
+                return Err(TagError::TagAlreadyExists(name.to_string()).into());
+            }
+            Ok(())
+        }
+    }
+
+    impl Default for MockTagDomain {
+        fn default() -> Self {
+            Self { tags: Mutex::new(Self::fixtures()) }
+        }
+    }
+
+    #[async_trait]
+    impl TagDomainTrait for MockTagDomain {
+        async fn list_tags(&self, namespace: String) -> anyhow::Result<Vec<Tag>> {
+            Ok(self.tags.lock().unwrap()
+                .iter()
+                .filter(|it| it.id.0 == namespace)
+                .cloned()
+                .collect())
+        }
+
+        async fn create_tag(&self, namespace: String, name: &str) -> anyhow::Result<Tag> {
+            let name = normalize_tag_name(name)?;
+            self.check_unique(&namespace, &name)?;
+
+            let mut tags = self.tags.lock().unwrap();
+            let tag = Tag {
+                id: (namespace, tags.iter().map(|it| it.id.1).max().unwrap_or_default() + 1),
+                name,
+                created_at: Utc::now(),
+            };
+            tags.push(tag.clone());
+
+            Ok(tag)
+        }
+
+        async fn rename_tag(&self, id: TagID, name: &str) -> anyhow::Result<Tag> {
+            let name = normalize_tag_name(name)?;
+            self.check_unique(&id.0, &name)?;
+
+            let mut tags = self.tags.lock().unwrap();
+            let tag = tags.iter_mut()
+                .find(|it| it.id == id)
+                .ok_or(TagError::TagNotFound(id.1))?;
+            tag.name = name;
+
+            Ok(tag.clone())
+        }
+
+        async fn delete_tag(&self, id: TagID) -> anyhow::Result<()> {
+            let mut tags = self.tags.lock().unwrap();
+            let index = tags.iter()
+                .position(|it| it.id == id)
+                .ok_or(TagError::TagNotFound(id.1))?;
+            tags.remove(index);
+
+            Ok(())
+        }
+    }
+
+    /// in memory `AuthDomainTrait` of the users alice and bob, see [`MockAuthDomain::fixtures`]
+    pub struct MockAuthDomain {
+        users: Mutex<Vec<(User, String)>>,
+        sessions: Mutex<Vec<(String, i32)>>,
+        tickets: Mutex<Vec<(String, i32)>>,
+        members: Mutex<Vec<(String, i32, NamespaceRole)>>,
+        invitations: Mutex<Vec<Invitation>>,
+        api_keys: Mutex<Vec<(String, ApiKey)>>,
+    }
+
+    impl MockAuthDomain {
+        /// the users with their passwords `{username}-password`, logged in as `{username}-token`,
+        /// each with the ticket `{username}-ticket`
+        pub fn fixtures() -> Vec<User> {
+            vec![(1, "alice"), (2, "bob")]
+                .into_iter()
+                .map(|(id, username)| User {
+                    id,
+                    username: username.to_string(),
+                    created_at: Utc.with_ymd_and_hms(1999, 12, 1, 0, 0, 0).unwrap(),
+                })
+                .collect()
+        }
+
+        fn member(&self, namespace: &str, user_id: i32) -> anyhow::Result<Member> {
+            let role = self.members.lock().unwrap()
+                .iter()
+                .find(|(it, id, _)| it == namespace && *id == user_id)
+                .map(|(_, _, role)| *role)
+                .ok_or(AuthError::MemberNotFound(user_id))?;
+            let username = self.users.lock().unwrap()
+                .iter()
+                .find(|(it, _)| it.id == user_id)
+                .map(|(it, _)| it.username.clone())
+                .unwrap();
+
+            Ok(Member { user_id, username, role, created_at: Utc.with_ymd_and_hms(1999, 12, 1, 0, 0, 0).unwrap() })
+        }
+
+        /// as `AuthRepository`, fails if the user is the only owner of the namespace
+        fn check_last_owner(&self, namespace: &str, user_id: i32) -> anyhow::Result<()> {
+            let owners = self.members.lock().unwrap()
+                .iter()
+                .filter(|(it, _, role)| it == namespace && *role == NamespaceRole::Owner)
+                .map(|(_, id, _)| *id)
+                .collect::<Vec<i32>>();
+            if owners == vec![user_id] {
+                return Err(AuthError::LastOwner(namespace.to_string()).into());
+            }
+            Ok(())
+        }
+    }
+
+    impl Default for MockAuthDomain {
+        /// alice owns `owned` and `shared` besides the personal namespaces, bob views `shared`,
+        /// nobody owns `used`, which holds todos,
+        /// the token `owned-invitation` lets an editor into `owned`,
+        /// the keys `tk_shared-editor` and `tk_shared-viewer` of alice use `shared`
+        fn default() -> Self {
+            let users = Self::fixtures();
+            let mut members = users.iter()
+                .map(|it| (it.namespace(), it.id, NamespaceRole::Owner))
+                .collect::<Vec<(String, i32, NamespaceRole)>>();
+            members.push(("owned".to_string(), 1, NamespaceRole::Owner));
+            members.push(("shared".to_string(), 1, NamespaceRole::Owner));
+            members.push(("shared".to_string(), 2, NamespaceRole::Viewer));
+
+            Self {
+                sessions: Mutex::new(users.iter().map(|it| (format!("{}-token", it.username), it.id)).collect()),
+                tickets: Mutex::new(users.iter().map(|it| (format!("{}-ticket", it.username), it.id)).collect()),
+                users: Mutex::new(users.into_iter().map(|it| {
+                    let password = format!("{}-password", it.username);
+                    (it, password)
+                }).collect()),
+                members: Mutex::new(members),
+                invitations: Mutex::new(vec![Invitation {
+                    token: "owned-invitation".to_string(),
+                    namespace: "owned".to_string(),
+                    role: NamespaceRole::Editor,
+                    expires_at: Utc::now() + chrono::Duration::days(7),
+                }]),
+                api_keys: Mutex::new([(1, NamespaceRole::Editor), (2, NamespaceRole::Viewer)].into_iter().map(|(id, role)| {
+                    let name = role.to_string().to_lowercase();
+                    (format!("{}shared-{}", API_KEY_PREFIX, name), ApiKey {
+                        id,
+                        namespace: "shared".to_string(),
+                        name,
+                        role,
+                        created_by: 1,
+                        created_at: Utc.with_ymd_and_hms(1999, 12, 1, 0, 0, 0).unwrap(),
+                        last_used_at: None,
+                    })
+                }).collect()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl AuthDomainTrait for MockAuthDomain {
+        async fn register(&self, username: &str, password: &str) -> anyhow::Result<User> {
+            check_username(username)?;
+            check_password(password)?;
+
+            let mut users = self.users.lock().unwrap();
+            if users.iter().any(|(it, _)| it.username == username) {
+                return Err(AuthError::UserAlreadyExists(username.to_string()).into());
+            }
+            let user = User {
+                id: users.iter().map(|(it, _)| it.id).max().unwrap_or_default() + 1,
+                username: username.to_string(),
+                created_at: Utc::now(),
+            };
+            users.push((user.clone(), password.to_string()));
+            self.members.lock().unwrap().push((user.namespace(), user.id, NamespaceRole::Owner));
+
+            Ok(user)
+        }
+
+        async fn login(&self, username: &str, password: &str) -> anyhow::Result<Session> {
+            let user = self.users.lock().unwrap()
+                .iter()
+                .find(|(it, p)| it.username == username && p == password)
+                .map(|(it, _)| it.clone())
+                .ok_or(AuthError::InvalidCredentials)?;
+            let token = format!("{}-token-{}", user.username, Utc::now().timestamp_micros());
+            self.sessions.lock().unwrap().push((token.clone(), user.id));
+
+            Ok(Session { token, expires_at: Utc::now() + chrono::Duration::days(30) })
+        }
+
+        async fn logout(&self, token: &str) -> anyhow::Result<()> {
+            self.sessions.lock().unwrap().retain(|(it, _)| it != token);
+            Ok(())
+        }
+
+        async fn authenticate(&self, token: &str) -> anyhow::Result<Option<User>> {
+            let user_id = self.sessions.lock().unwrap()
+                .iter()
+                .find(|(it, _)| it == token)
+                .map(|(_, user_id)| *user_id);
+
+            Ok(user_id.and_then(|user_id| self.users.lock().unwrap()
+                .iter()
+                .find(|(it, _)| it.id == user_id)
+                .map(|(it, _)| it.clone())))
+        }
+
+        async fn issue_ticket(&self, user: &User) -> anyhow::Result<Ticket> {
+            let token = format!("{}-ticket-{}", user.username, Utc::now().timestamp_micros());
+            self.tickets.lock().unwrap().push((token.clone(), user.id));
+
+            Ok(Ticket { token, expires_at: Utc::now() + chrono::Duration::seconds(60) })
+        }
+
+        async fn authenticate_ticket(&self, ticket: &str) -> anyhow::Result<Option<User>> {
+            let mut tickets = self.tickets.lock().unwrap();
+            let Some(index) = tickets.iter().position(|(it, _)| it == ticket) else {
+                return Ok(None);
+            };
+            let (_, user_id) = tickets.remove(index);
+
+            Ok(self.users.lock().unwrap()
+                .iter()
+                .find(|(it, _)| it.id == user_id)
+                .map(|(it, _)| it.clone()))
+        }
+
+        async fn membership(&self, namespace: &str, user: Option<&User>) -> anyhow::Result<Membership> {
+            let members = self.members.lock().unwrap();
+
+            Ok(Membership {
+                owned: members.iter().any(|(it, _, _)| it == namespace),
+                role: members.iter()
+                    .find(|(it, user_id, _)| it == namespace && user.is_some_and(|user| user.id == *user_id))
+                    .map(|(_, _, role)| *role),
+            })
+        }
+
+        async fn namespaces(&self, user: &User) -> anyhow::Result<Vec<String>> {
+            Ok(self.members.lock().unwrap()
+                .iter()
+                .filter(|(_, user_id, _)| *user_id == user.id)
+                .map(|(it, _, _)| it.clone())
+                .collect())
+        }
+
+        async fn claim_namespace(&self, user: &User, namespace: &str) -> anyhow::Result<()> {
+            check_namespace(namespace)?;
+
+            match self.membership(namespace, Some(user)).await? {
+                Membership { role: Some(NamespaceRole::Owner), .. } => Ok(()),
+                Membership { owned: true, .. } => Err(AuthError::NamespaceAlreadyOwned(namespace.to_string()).into()),
+                Membership { owned: false, .. } if namespace == "used" => Err(AuthError::NamespaceNotEmpty(namespace.to_string()).into()),
+                Membership { owned: false, .. } => {
+                    self.members.lock().unwrap().push((namespace.to_string(), user.id, NamespaceRole::Owner));
+                    Ok(())
+                }
+            }
+        }
+
+        async fn list_members(&self, namespace: &str) -> anyhow::Result<Vec<Member>> {
+            let user_ids = self.members.lock().unwrap()
+                .iter()
+                .filter(|(it, _, _)| it == namespace)
+                .map(|(_, user_id, _)| *user_id)
+                .collect::<Vec<i32>>();
+            let mut members = user_ids.into_iter()
+                .map(|user_id| self.member(namespace, user_id))
+                .collect::<anyhow::Result<Vec<Member>>>()?;
+            members.sort_by_key(|it| (Reverse(it.role), it.user_id));
+
+            Ok(members)
+        }
+
+        async fn update_member(&self, namespace: &str, user_id: i32, role: NamespaceRole) -> anyhow::Result<Member> {
+            self.member(namespace, user_id)?;
+            if role != NamespaceRole::Owner {
+                self.check_last_owner(namespace, user_id)?;
+            }
+            self.members.lock().unwrap()
+                .iter_mut()
+                .filter(|(it, id, _)| it == namespace && *id == user_id)
+                .for_each(|it| it.2 = role);
+
+            self.member(namespace, user_id)
+        }
+
+        async fn remove_member(&self, namespace: &str, user_id: i32) -> anyhow::Result<()> {
+            self.member(namespace, user_id)?;
+            self.check_last_owner(namespace, user_id)?;
+            self.members.lock().unwrap().retain(|(it, id, _)| it != namespace || *id != user_id);
+
+            Ok(())
+        }
+
+        async fn invite(&self, _user: &User, namespace: &str, role: NamespaceRole) -> anyhow::Result<Invitation> {
+            let invitation = Invitation {
+                token: format!("{}-invitation-{}", namespace, Utc::now().timestamp_micros()),
+                namespace: namespace.to_string(),
+                role,
+                expires_at: Utc::now() + chrono::Duration::days(7),
+            };
+            self.invitations.lock().unwrap().push(invitation.clone());
+
+            Ok(invitation)
+        }
+
+        async fn accept_invitation(&self, user: &User, token: &str) -> anyhow::Result<String> {
+            let mut invitations = self.invitations.lock().unwrap();
+            let index = invitations.iter()
+                .position(|it| it.token == token && it.expires_at > Utc::now())
+                .ok_or(AuthError::InvitationNotFound)?;
+            let invitation = invitations.remove(index);
+
+            let mut members = self.members.lock().unwrap();
+            match members.iter_mut().find(|(it, id, _)| *it == invitation.namespace && *id == user.id) {
+                Some(member) => member.2 = member.2.max(invitation.role),
+                None => members.push((invitation.namespace.clone(), user.id, invitation.role)),
+            }
+
+            Ok(invitation.namespace)
+        }
+
+        async fn create_api_key(&self, user: &User, namespace: &str, name: &str, role: NamespaceRole) -> anyhow::Result<NewApiKey> {
+            check_api_key_request(name, role)?;
+
+            let mut api_keys = self.api_keys.lock().unwrap();
+            let api_key = ApiKey {
+                id: api_keys.iter().map(|(_, it)| it.id).max().unwrap_or_default() + 1,
+                namespace: namespace.to_string(),
+                name: name.trim().to_string(),
+                role,
+                created_by: user.id,
+                created_at: Utc::now(),
+                last_used_at: None,
+            };
+            let key = format!("{}{}-{}", API_KEY_PREFIX, namespace, api_key.id);
+            api_keys.push((key.clone(), api_key.clone()));
+
+            Ok(NewApiKey { key, api_key })
+        }
+
+        async fn list_api_keys(&self, namespace: &str) -> anyhow::Result<Vec<ApiKey>> {
+            Ok(self.api_keys.lock().unwrap()
+                .iter()
+                .rev()
+                .filter(|(_, it)| it.namespace == namespace)
+                .map(|(_, it)| it.clone())
+                .collect())
+        }
+
+        async fn revoke_api_key(&self, namespace: &str, id: i32) -> anyhow::Result<()> {
+            let mut api_keys = self.api_keys.lock().unwrap();
+            let index = api_keys.iter()
+                .position(|(_, it)| it.namespace == namespace && it.id == id)
+                .ok_or(AuthError::ApiKeyNotFound(id))?;
+            api_keys.remove(index);
+
+            Ok(())
+        }
+
+        async fn authenticate_api_key(&self, key: &str) -> anyhow::Result<Option<ApiKey>> {
+            Ok(self.api_keys.lock().unwrap()
+                .iter_mut()
+                .find(|(it, _)| it == key)
+                .map(|(_, it)| {
+                    it.last_used_at = Some(Utc::now());
+                    it.clone()
+                }))
+        }
+    }
 
-    #[cfg(test)]
     pub async fn test_request<C>(
         configure: C,
         req: TestRequest,
@@ -56,13 +717,44 @@ pub(crate) mod tests {
     ) -> ServiceResponse<BoxBody>
         where C: FnOnce(&mut ServiceConfig)
     {
-        let app = test::init_service(App::new().configure(configure)).await;
+        test_request_at("/todos", configure, req, expect_status_code).await
+    }
+
+    /// request the routes of `configure` mounted at `scope`, backed by the mock domains.
+    /// the namespaces are authorized as in `handlers::routes`, `Shared Team` is an alias of `shared`
+    pub async fn test_request_at<C>(
+        scope: &str,
+        configure: C,
+        req: TestRequest,
+        expect_status_code: StatusCode,
+    ) -> ServiceResponse<BoxBody>
+        where C: FnOnce(&mut ServiceConfig)
+    {
+        let domain = Arc::new(MockTodoDomain::default()) as Arc<dyn TodoDomainTrait>;
+        let tag_domain = Arc::new(MockTagDomain::default()) as Arc<dyn TagDomainTrait>;
+        let auth_domain = Arc::new(MockAuthDomain::default()) as Arc<dyn AuthDomainTrait>;
+        let app = test::init_service(App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(web::Data::from(domain))
+            .app_data(web::Data::from(tag_domain))
+            .app_data(web::Data::from(auth_domain))
+            .app_data(web::Data::new(AuthConfig {
+                aliases: HashMap::from([("Shared Team".to_string(), "shared".to_string())]),
+                ..Default::default()
+            }))
+            .app_data(web::Data::new(TodoEvents::default()))
+            .service(web::scope(scope).wrap(from_fn(authorize)).configure(configure))).await;
         let resp = req.send_request(&app).await;
         if resp.status() != expect_status_code {
             let body_bytes = to_bytes(resp.into_body()).await.unwrap();
             panic!("response body is {:?}", body_bytes)
         }
         assert_eq!(resp.status(), expect_status_code);
-        return resp;
+        resp
+    }
+
+    pub async fn json_body(resp: ServiceResponse<BoxBody>) -> serde_json::Value {
+        let body_bytes = to_bytes(resp.into_body()).await.unwrap();
+        serde_json::from_slice(&body_bytes).unwrap()
     }
 }
@@ -1,6 +1,13 @@
 use env_logger::Env;
 use backend::start_server;
-use backend::infra::{db, config};
+use backend::infra::{db, config, migration};
+
+const USAGE: &str = r#"usage:
+    backend                      start the server
+    backend migrate [up]         apply all the pending migrations
+    backend migrate up <v>       apply the pending migrations up to version <v>
+    backend migrate down <v>     revert the migrations newer than version <v>
+    backend migrate status       list the applied migrations"#;
 
 #[tokio::main]
 async fn main() -> std::io::Result<()> {
@@ -12,5 +19,48 @@ async fn main() -> std::io::Result<()> {
 
     let db_pool = db::must_init(&config.db);
 
-    start_server(config.address(), db_pool).await
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<&str>>();
+
+    match args.as_slice() {
+        [] => {}
+        ["migrate", command @ ..] => {
+            return migrate(&db_pool, command).await
+                .map_err(|e| std::io::Error::other(e.to_string()));
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    }
+
+    if config.migration.auto {
+        migration::up(&db_pool, None).await.expect("Failed to migrate db");
+    }
+    migration::check(&db_pool).await.expect("Failed to check db schema");
+
+    let pg_config = config.db.get_pg_config().expect("Failed to get db config");
+
+    start_server(config.address(), config.metrics_address(), db_pool, pg_config, config.purge, config.auth).await
+}
+
+async fn migrate(db_pool: &deadpool_postgres::Pool, command: &[&str]) -> anyhow::Result<()> {
+    let version = |v: &str| v.parse::<i32>()
+        .map_err(|_| anyhow::anyhow!("invalid version `{}`\n{}", v, USAGE));
+
+    match command {
+        [] | ["up"] => log::info!("applied {:?}", migration::up(db_pool, None).await?),
+        ["up", v] => log::info!("applied {:?}", migration::up(db_pool, Some(version(v)?)).await?),
+        ["down", v] => log::info!("reverted {:?}", migration::down(db_pool, version(v)?).await?),
+        ["status"] => {
+            let applied = migration::applied_versions(db_pool).await?;
+            for it in migration::MIGRATIONS {
+                let mark = if applied.contains(&it.version) { "applied" } else { "pending" };
+                println!("{:<32} {}", it.name, mark);
+            }
+        }
+        _ => anyhow::bail!("unknown migrate command\n{}", USAGE),
+    }
+
+    Ok(())
 }
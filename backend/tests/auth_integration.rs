@@ -0,0 +1,163 @@
+use chrono::Utc;
+use common::client::{ClientError, ScopeClient};
+use common::model::{CreateTodoRequest, ErrorCode, NamespaceRole};
+use crate::helper::spawn_server;
+
+mod helper;
+
+#[tokio::test]
+async fn register_login_and_own() -> anyhow::Result<()> {
+    let base_url = spawn_server();
+    let micros = Utc::now().timestamp_micros();
+    let (alice, bob) = (format!("it-a{}", micros), format!("it-b{}", micros));
+    let namespace = format!("it-owned-{}", micros);
+
+    let client = ScopeClient::default().endpoint(&base_url);
+    let auth_client = client.auth_client();
+
+    let registered = auth_client.register(&alice, "alice-password").await?;
+    assert_eq!(vec![format!("@{}", alice)], registered.namespaces);
+    auth_client.register(&bob, "bob-password").await?;
+
+    let error = auth_client.register(&alice, "alice-password").await.err().unwrap();
+    assert_eq!(Some(ErrorCode::UserAlreadyExists), error.code());
+    let error = auth_client.login(&alice, "wrong-password").await.err().unwrap();
+    assert!(matches!(error, ClientError::Unauthorized(_)), "{:?}", error);
+
+    let token = auth_client.login(&alice, "alice-password").await?.token;
+    let alice_client = client.clone().credentials(&token)?;
+    let bob_client = client.clone().credentials(&auth_client.login(&bob, "bob-password").await?.token)?;
+
+    // the personal namespace without a `t-ns` header
+    let created = alice_client.todo_client().create_todo(CreateTodoRequest {
+        content: "personal".to_string(),
+        ..Default::default()
+    }).await?;
+    assert_eq!(format!("@{}", alice), created.namespace);
+
+    let me = alice_client.auth_client().claim_namespace(&namespace).await?;
+    assert_eq!(vec![format!("@{}", alice), namespace.clone()], me.namespaces);
+    let error = bob_client.auth_client().claim_namespace(&namespace).await.err().unwrap();
+    assert_eq!(Some(ErrorCode::NamespaceAlreadyOwned), error.code());
+
+    assert!(alice_client.clone().namespace(&namespace)?.todo_client().get_todos(None).await?.is_empty());
+    let error = bob_client.clone().namespace(&namespace)?.todo_client().get_todos(None).await.err().unwrap();
+    assert_eq!(Some(ErrorCode::Forbidden), error.code());
+    let error = client.clone().namespace(&namespace)?.todo_client().get_todos(None).await.err().unwrap();
+    assert_eq!(Some(ErrorCode::Unauthorized), error.code());
+
+    // the streams take a single-use ticket in place of the token
+    let ticket = alice_client.auth_client().ticket().await?.token;
+    let events = |access_token: String| reqwest::Client::new()
+        .get(format!("{}/todos/events", base_url))
+        .query(&[("ns", namespace.clone()), ("access_token", access_token)])
+        .send();
+    assert_eq!(200, events(ticket.clone()).await?.status());
+    assert_eq!(401, events(ticket).await?.status());
+    assert_eq!(401, events(token.clone()).await?.status());
+
+    alice_client.auth_client().logout().await?;
+    let error = alice_client.auth_client().me().await.err().unwrap();
+    assert_eq!(Some(ErrorCode::Unauthorized), error.code());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn share_with_roles() -> anyhow::Result<()> {
+    let base_url = spawn_server();
+    let micros = Utc::now().timestamp_micros();
+    let (alice, bob) = (format!("it-sa{}", micros), format!("it-sb{}", micros));
+    let namespace = format!("it-shared-{}", micros);
+
+    let client = ScopeClient::default().endpoint(&base_url);
+    let auth_client = client.auth_client();
+    auth_client.register(&alice, "alice-password").await?;
+    auth_client.register(&bob, "bob-password").await?;
+    let alice_client = client.clone().namespace(&namespace)?.credentials(&auth_client.login(&alice, "alice-password").await?.token)?;
+    let bob_client = client.clone().namespace(&namespace)?.credentials(&auth_client.login(&bob, "bob-password").await?.token)?;
+
+    alice_client.auth_client().claim_namespace(&namespace).await?;
+    alice_client.todo_client().create_todo(CreateTodoRequest {
+        content: "shared".to_string(),
+        ..Default::default()
+    }).await?;
+
+    let invitation = alice_client.member_client().create_invitation(NamespaceRole::Viewer).await?;
+    let me = bob_client.auth_client().accept_invitation(&invitation.token).await?;
+    assert_eq!(vec![format!("@{}", bob), namespace.clone()], me.namespaces);
+    let error = bob_client.auth_client().accept_invitation(&invitation.token).await.err().unwrap();
+    assert_eq!(Some(ErrorCode::InvitationNotFound), error.code());
+
+    // a viewer reads, but does not write
+    assert_eq!(1, bob_client.todo_client().get_todos(None).await?.len());
+    let error = bob_client.todo_client().create_todo(CreateTodoRequest {
+        content: "viewed only".to_string(),
+        ..Default::default()
+    }).await.err().unwrap();
+    assert_eq!(Some(ErrorCode::Forbidden), error.code());
+    let error = bob_client.member_client().create_invitation(NamespaceRole::Owner).await.err().unwrap();
+    assert_eq!(Some(ErrorCode::Forbidden), error.code());
+
+    let members = alice_client.member_client().get_members().await?;
+    assert_eq!(NamespaceRole::Owner, members.role);
+    let bob_id = members.members.iter().find(|it| it.username == bob).unwrap().user_id;
+    assert_eq!(NamespaceRole::Editor, alice_client.member_client().update_member(bob_id, NamespaceRole::Editor).await?.role);
+    bob_client.todo_client().create_todo(CreateTodoRequest {
+        content: "edited".to_string(),
+        ..Default::default()
+    }).await?;
+
+    let alice_id = members.members.iter().find(|it| it.username == alice).unwrap().user_id;
+    let error = alice_client.member_client().remove_member(alice_id).await.err().unwrap();
+    assert_eq!(Some(ErrorCode::LastOwner), error.code());
+
+    bob_client.member_client().remove_member(bob_id).await?;
+    let error = bob_client.todo_client().get_todos(None).await.err().unwrap();
+    assert_eq!(Some(ErrorCode::Forbidden), error.code());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn api_keys() -> anyhow::Result<()> {
+    let base_url = spawn_server();
+    let micros = Utc::now().timestamp_micros();
+    let alice = format!("it-ka{}", micros);
+    let namespace = format!("it-keys-{}", micros);
+
+    let client = ScopeClient::default().endpoint(&base_url);
+    let auth_client = client.auth_client();
+    auth_client.register(&alice, "alice-password").await?;
+    let alice_client = client.clone().namespace(&namespace)?.credentials(&auth_client.login(&alice, "alice-password").await?.token)?;
+    alice_client.auth_client().claim_namespace(&namespace).await?;
+
+    let created = alice_client.key_client().create_key("nightly", NamespaceRole::Editor).await?;
+    let key = created.key.clone().unwrap();
+    assert_eq!(None, created.last_used_at);
+
+    // the namespace of the key without a `t-ns` header
+    let key_client = client.clone().api_key(&key)?;
+    let todo = key_client.todo_client().create_todo(CreateTodoRequest {
+        content: "nightly failed".to_string(),
+        ..Default::default()
+    }).await?;
+    assert_eq!(namespace, todo.namespace);
+    let error = key_client.clone().namespace(&format!("@{}", alice))?.todo_client().get_todos(None).await.err().unwrap();
+    assert_eq!(Some(ErrorCode::Forbidden), error.code());
+    let error = key_client.key_client().get_keys().await.err().unwrap();
+    assert_eq!(Some(ErrorCode::Forbidden), error.code());
+
+    let keys = alice_client.key_client().get_keys().await?;
+    assert_eq!(vec![created.id], keys.iter().map(|it| it.id).collect::<Vec<_>>());
+    assert!(keys[0].last_used_at.is_some());
+    assert_eq!(None, keys[0].key);
+
+    alice_client.key_client().revoke_key(created.id).await?;
+    let error = key_client.todo_client().get_todos(None).await.err().unwrap();
+    assert_eq!(Some(ErrorCode::Unauthorized), error.code());
+    let error = alice_client.key_client().revoke_key(created.id).await.err().unwrap();
+    assert_eq!(Some(ErrorCode::ApiKeyNotFound), error.code());
+
+    Ok(())
+}
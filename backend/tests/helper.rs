@@ -2,11 +2,13 @@ pub fn spawn_server() -> String {
     let mut config = backend::infra::config::must_get();
 
     config.server.port = 0;
+    config.purge.enabled = false;
     let listener = config.address();
     let port = listener.local_addr().unwrap().port();
     let db_pool = backend::infra::db::must_init(&config.db);
+    let pg_config = config.db.get_pg_config().unwrap();
 
-    let _ = tokio::spawn(backend::start_server(listener, db_pool));
+    tokio::spawn(backend::start_server(listener, None, db_pool, pg_config, config.purge, config.auth));
 
     format!("http://127.0.0.1:{}", port)
 }
@@ -1,6 +1,4 @@
-use common::client::ScopeClient;
-
-use common::client::ScopeClient;
+use common::client::{ClientError, ScopeClient};
 
 use crate::helper::spawn_server;
 
@@ -28,10 +26,10 @@ async fn test_ping_server_unavailable() -> anyhow::Result<()> {
         .endpoint("http://127.0.0.1:9999")
         .ping_client();
 
-    let error = client.ping().await
-        .map_err(|e| e.downcast::<reqwest::Error>().unwrap())
-        .err()
-        .unwrap();
+    let error = match client.ping().await {
+        Err(ClientError::Transport(e)) => e,
+        other => panic!("expect transport error, got {:?}", other),
+    };
 
     assert!(error.is_connect());
     assert!(error.to_string().contains("Connection refused"));
@@ -0,0 +1,87 @@
+use common::client::{ClientError, ScopeClient};
+use common::model::{CreateTodoRequest, ErrorCode, GetTodosQuery, UpdateTodoRequest};
+use crate::helper::spawn_server;
+
+mod helper;
+
+pub const NS: &str = "testing/integration/tags";
+
+#[tokio::test]
+async fn tag_crud() -> anyhow::Result<()> {
+    let base_url = spawn_server();
+
+    let client = ScopeClient::default()
+        .endpoint(&base_url)
+        .namespace(&format!("{}{}", NS, "/crud"))?;
+    let tag_client = client.tag_client();
+
+    let created = tag_client.create_tag("backend").await?;
+    assert_eq!("backend", created.name);
+
+    let error = tag_client.create_tag("backend").await.err().unwrap();
+    assert!(matches!(error, ClientError::Conflict(_)), "{:?}", error);
+    assert_eq!(Some(ErrorCode::TagAlreadyExists), error.code());
+
+    let renamed = tag_client.rename_tag(created.id, "server").await?;
+    assert_eq!("server", renamed.name);
+
+    let tags = tag_client.get_tags().await?;
+    assert_eq!(vec![renamed.clone()], tags);
+
+    tag_client.delete_tag(created.id).await?;
+    let error = tag_client.delete_tag(created.id).await.err().unwrap();
+    assert_eq!(Some(ErrorCode::TagNotFound), error.code());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn todo_tags() -> anyhow::Result<()> {
+    let base_url = spawn_server();
+
+    let client = ScopeClient::default()
+        .endpoint(&base_url)
+        .namespace(&format!("{}{}", NS, "/todos"))?;
+    let todo_client = client.todo_client();
+    let tag_client = client.tag_client();
+
+    let tagged = todo_client.create_todo(CreateTodoRequest {
+        content: "tagged".to_string(),
+        tags: vec!["release-1.2".to_string(), "backend".to_string()],
+        ..Default::default()
+    }).await?;
+    assert_eq!(vec!["backend", "release-1.2"], tagged.tags);
+
+    let untagged = todo_client.create_todo(CreateTodoRequest {
+        content: "untagged".to_string(),
+        ..Default::default()
+    }).await?;
+
+    let found = todo_client.query_todos(GetTodosQuery {
+        tag: Some("backend".to_string()),
+        ..Default::default()
+    }).await?;
+    assert_eq!(vec![tagged.id], found.iter().map(|it| it.id).collect::<Vec<i32>>());
+
+    // the tags are created on the fly, deleting one removes it from the todos
+    let tags = tag_client.get_tags().await?;
+    assert_eq!(vec!["backend", "release-1.2"], tags.iter().map(|it| it.name.as_str()).collect::<Vec<&str>>());
+    tag_client.delete_tag(tags[0].id).await?;
+
+    let updated = todo_client.get_todo_by_id(tagged.id).await?.unwrap();
+    assert_eq!(vec!["release-1.2"], updated.tags);
+
+    let updated = todo_client.update_todo(tagged.id, UpdateTodoRequest {
+        tags: Some(vec![]),
+        ..Default::default()
+    }).await?;
+    assert!(updated.tags.is_empty());
+
+    // clean data
+    todo_client.clear_todos(vec![tagged.id, untagged.id]).await?;
+    for tag in tag_client.get_tags().await? {
+        tag_client.delete_tag(tag.id).await?;
+    }
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
-use common::client::{TodoClient, ScopeClient};
-use common::model::{CreateTodoRequest, TodoResponse, TodoStatus, UpdateTodoRequest};
+use chrono::{Duration, Utc};
+use common::client::{ClientError, TodoClient, ScopeClient};
+use common::model::{BatchOperation, BatchResult, CreateTodoRequest, ErrorCode, ExportFormat, GetTodosQuery, ImportResult, ImportTodo, MoveTodoRequest, TodoPriority, TodoResponse, TodoSort, TodoStatus, UpdateTodoRequest};
 use crate::helper::spawn_server;
 
 mod helper;
@@ -12,7 +13,7 @@ async fn get_todos() -> anyhow::Result<()> {
 
     let client = ScopeClient::default()
         .endpoint(&base_url)
-        .namespace(&format!("{}{}", NS, "/get_todos"))
+        .namespace(&format!("{}{}", NS, "/get_todos"))?
         .todo_client();
 
     let data = client.get_todos(None).await?;
@@ -28,17 +29,20 @@ async fn get_todos_not_empty() -> anyhow::Result<()> {
 
     let client = ScopeClient::default()
         .endpoint(&base_url)
-        .namespace(&format!("{}{}", NS, "/not_empty"))
+        .namespace(&format!("{}{}", NS, "/not_empty"))?
         .todo_client();
 
     let todo_1 = client.create_todo(CreateTodoRequest {
         content: "create todo 1".to_string(),
+        ..Default::default()
     }).await?;
     let todo_2 = client.create_todo(CreateTodoRequest {
         content: "create todo 2".to_string(),
+        ..Default::default()
     }).await?;
     let todo_3 = client.create_todo(CreateTodoRequest {
         content: "create todo 3".to_string(),
+        ..Default::default()
     }).await?;
 
     let todos = client.get_todos(None).await?;
@@ -57,13 +61,552 @@ async fn get_todos_not_empty() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn get_todos_by_pages() -> anyhow::Result<()> {
+    let base_url = spawn_server();
+
+    let client = ScopeClient::default()
+        .endpoint(&base_url)
+        .namespace(&format!("{}{}", NS, "/pages"))?
+        .todo_client();
+
+    let mut created = vec![];
+    for i in 0..5 {
+        created.push(client.create_todo(CreateTodoRequest {
+            content: format!("create todo {}", i),
+            ..Default::default()
+        }).await?);
+    }
+    created.reverse();
+
+    let mut pages = client.pages(GetTodosQuery {
+        limit: Some(2),
+        ..Default::default()
+    });
+
+    let mut sizes = vec![];
+    let mut todos = vec![];
+    while let Some(page) = pages.next().await {
+        let page = page?;
+        sizes.push(page.items.len());
+        todos.extend(page.items);
+    }
+
+    assert_eq!(vec![2, 2, 1], sizes);
+    assert_eq!(created, todos);
+
+    // clean data
+    client.clear_todos(created.iter().map(|it| it.id).collect()).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn get_todos_search() -> anyhow::Result<()> {
+    let base_url = spawn_server();
+
+    let client = ScopeClient::default()
+        .endpoint(&base_url)
+        .namespace(&format!("{}{}", NS, "/search"))?
+        .todo_client();
+
+    let milk = client.create_todo(CreateTodoRequest {
+        content: "buy some milk".to_string(),
+        ..Default::default()
+    }).await?;
+    let notes = client.create_todo(CreateTodoRequest {
+        content: "write release notes".to_string(),
+        ..Default::default()
+    }).await?;
+
+    let hits = client.query_todos(GetTodosQuery {
+        q: Some("milk".to_string()),
+        ..Default::default()
+    }).await?;
+
+    assert_eq!(1, hits.len());
+    assert_eq!(milk.id, hits[0].id);
+    assert_eq!(Some("buy some <mark>milk</mark>".to_string()), hits[0].snippet);
+    assert!(hits[0].rank.is_some());
+
+    // clean data
+    client.clear_todos(vec![milk.id, notes.id]).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn get_todos_overdue() -> anyhow::Result<()> {
+    let base_url = spawn_server();
+
+    let client = ScopeClient::default()
+        .endpoint(&base_url)
+        .namespace(&format!("{}{}", NS, "/overdue"))?
+        .todo_client();
+
+    let yesterday = Utc::now() - Duration::days(1);
+    let overdue = client.create_todo(CreateTodoRequest {
+        content: "overdue since yesterday".to_string(),
+        due_at: Some(yesterday),
+        ..Default::default()
+    }).await?;
+    let no_due = client.create_todo(CreateTodoRequest {
+        content: "no due date".to_string(),
+        ..Default::default()
+    }).await?;
+
+    let found = client.query_todos(GetTodosQuery {
+        overdue: Some(true),
+        ..Default::default()
+    }).await?;
+    assert_eq!(vec![overdue.id], found.iter().map(|it| it.id).collect::<Vec<i32>>());
+    assert!(found[0].is_overdue(Utc::now()));
+
+    let cleared = client.update_todo(overdue.id, UpdateTodoRequest {
+        due_at: Some(None),
+        ..Default::default()
+    }).await?;
+    assert_eq!(None, cleared.due_at);
+
+    let found = client.query_todos(GetTodosQuery {
+        overdue: Some(true),
+        ..Default::default()
+    }).await?;
+    assert!(found.is_empty());
+
+    // clean data
+    client.clear_todos(vec![overdue.id, no_due.id]).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn move_todo() -> anyhow::Result<()> {
+    let base_url = spawn_server();
+
+    let client = ScopeClient::default()
+        .endpoint(&base_url)
+        .namespace(&format!("{}{}", NS, "/move"))?
+        .todo_client();
+
+    let mut ids = vec![];
+    for (content, priority) in [("a", TodoPriority::High), ("b", TodoPriority::Low), ("c", TodoPriority::Normal)] {
+        ids.push(client.create_todo(CreateTodoRequest {
+            content: content.to_string(),
+            priority: Some(priority),
+            ..Default::default()
+        }).await?.id);
+    }
+    let sorted = |sort| client.query_todos(GetTodosQuery { sort: Some(sort), ..Default::default() });
+    let contents = |todos: Vec<TodoResponse>| todos.into_iter().map(|it| it.content).collect::<Vec<String>>();
+
+    assert_eq!(vec!["a", "c", "b"], contents(sorted(TodoSort::Priority).await?));
+    assert_eq!(vec!["c", "b", "a"], contents(sorted(TodoSort::Manual).await?));
+
+    client.move_todo(ids[0], MoveTodoRequest { before: Some(ids[2]), ..Default::default() }).await?;
+    assert_eq!(vec!["a", "c", "b"], contents(sorted(TodoSort::Manual).await?));
+
+    let error = client.move_todo(ids[0], MoveTodoRequest::default()).await.err().unwrap();
+    assert!(matches!(error, ClientError::Validation(_)), "{:?}", error);
+
+    // clean data
+    client.clear_todos(ids).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn subtasks() -> anyhow::Result<()> {
+    let base_url = spawn_server();
+
+    let client = ScopeClient::default()
+        .endpoint(&base_url)
+        .namespace(&format!("{}{}", NS, "/subtasks"))?
+        .todo_client();
+
+    let parent = client.create_todo(CreateTodoRequest {
+        content: "parent".to_string(),
+        ..Default::default()
+    }).await?;
+    let child = client.create_todo(CreateTodoRequest {
+        content: "child".to_string(),
+        parent_id: Some(parent.id),
+        ..Default::default()
+    }).await?;
+    assert_eq!(Some(parent.id), child.parent_id);
+
+    let children = client.get_children(parent.id).await?;
+    assert_eq!(vec![child.clone()], children);
+
+    let error = client.update_todo(parent.id, UpdateTodoRequest {
+        status: Some(TodoStatus::Done),
+        ..Default::default()
+    }).await.err().unwrap();
+    assert!(matches!(error, ClientError::Conflict(_)), "{:?}", error);
+    assert_eq!(Some(ErrorCode::OpenSubtasks), error.code());
+
+    let done = client.update_todo(parent.id, UpdateTodoRequest {
+        status: Some(TodoStatus::Done),
+        cascade: true,
+        ..Default::default()
+    }).await?;
+    assert_eq!(TodoStatus::Done, done.status);
+    let child = client.get_todo_by_id(child.id).await?.unwrap();
+    assert_eq!(TodoStatus::Done, child.status);
+
+    let error = client.create_todo(CreateTodoRequest {
+        content: "orphan".to_string(),
+        parent_id: Some(child.id + 1000),
+        ..Default::default()
+    }).await.err().unwrap();
+    assert!(matches!(error, ClientError::Validation(_)), "{:?}", error);
+
+    // the child outlives its parent as a top-level todo
+    client.clear_todos(vec![parent.id]).await?;
+    assert_eq!(None, client.get_todo_by_id(child.id).await?.unwrap().parent_id);
+
+    // clean data
+    client.clear_todos(vec![child.id]).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn batch() -> anyhow::Result<()> {
+    let base_url = spawn_server();
+
+    let client = ScopeClient::default()
+        .endpoint(&base_url)
+        .namespace(&format!("{}{}", NS, "/batch"))?
+        .todo_client();
+
+    let first = client.create_todo(CreateTodoRequest {
+        content: "first".to_string(),
+        ..Default::default()
+    }).await?;
+    let second = client.create_todo(CreateTodoRequest {
+        content: "second".to_string(),
+        ..Default::default()
+    }).await?;
+
+    let response = client.batch(vec![
+        BatchOperation::Create(CreateTodoRequest {
+            content: "created".to_string(),
+            parent_id: Some(first.id),
+            ..Default::default()
+        }),
+        BatchOperation::Status { id: first.id, status: TodoStatus::Done, cascade: false },
+        BatchOperation::Update {
+            id: second.id,
+            body: UpdateTodoRequest { content: Some("updated".to_string()), ..Default::default() },
+        },
+        BatchOperation::Delete { id: second.id },
+        BatchOperation::Status { id: second.id, status: TodoStatus::Archived, cascade: false },
+        BatchOperation::Delete { id: second.id },
+        BatchOperation::Delete { id: second.id },
+    ]).await?;
+
+    let codes = response.results.iter()
+        .map(|it| match it {
+            BatchResult::Ok(_) => None,
+            BatchResult::Error(error) => Some(error.code),
+        })
+        .collect::<Vec<Option<ErrorCode>>>();
+    // an `Archived` todo is moved to `Deleted` first, then removed for good
+    assert_eq!(vec![None, Some(ErrorCode::OpenSubtasks), None, Some(ErrorCode::InvalidStatusTransition), None, None, None], codes);
+
+    let BatchResult::Ok(created) = &response.results[0] else { panic!("{:?}", response.results[0]) };
+    assert_eq!(Some(first.id), created.parent_id);
+    assert_eq!(TodoStatus::Todo, client.get_todo_by_id(first.id).await?.unwrap().status);
+    assert_eq!(None, client.get_todo_by_id(second.id).await?);
+
+    // clean data
+    client.clear_todos(vec![first.id]).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn get_changes() -> anyhow::Result<()> {
+    let base_url = spawn_server();
+
+    let client = ScopeClient::default()
+        .endpoint(&base_url)
+        .namespace(&format!("{}{}", NS, "/changes"))?
+        .todo_client();
+
+    let since = client.get_changes(None).await?.until;
+
+    let kept = client.create_todo(CreateTodoRequest {
+        content: "kept".to_string(),
+        ..Default::default()
+    }).await?;
+    let deleted = client.create_todo(CreateTodoRequest {
+        content: "deleted".to_string(),
+        ..Default::default()
+    }).await?;
+    client.clear_todos(vec![deleted.id]).await?;
+
+    let changes = client.get_changes(Some(since)).await?;
+
+    assert!(changes.todos.contains(&kept));
+    assert!(changes.todos.iter().all(|it| it.id != deleted.id));
+    assert!(changes.deleted.contains(&deleted.id));
+    assert!(changes.until > since);
+
+    // clean data
+    client.clear_todos(vec![kept.id]).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn import_todos() -> anyhow::Result<()> {
+    let base_url = spawn_server();
+
+    let client = ScopeClient::default()
+        .endpoint(&base_url)
+        .namespace(&format!("{}{}", NS, "/import"))?
+        .todo_client();
+
+    let created_at = Utc::now() - Duration::days(7);
+    let todos = vec![
+        ImportTodo {
+            id: 2,
+            content: "imported subtask".to_string(),
+            status: TodoStatus::Done,
+            created_at,
+            due_at: None,
+            priority: TodoPriority::High,
+            tags: vec![],
+            parent_id: Some(1),
+        },
+        ImportTodo {
+            id: 1,
+            content: "imported".to_string(),
+            status: TodoStatus::Archived,
+            created_at,
+            due_at: None,
+            priority: Default::default(),
+            tags: vec!["local".to_string()],
+            parent_id: None,
+        },
+    ];
+
+    let response = client.import_todos(todos.clone()).await?;
+
+    let [ImportResult::Created(subtask), ImportResult::Created(parent)] = response.results.as_slice() else {
+        panic!("{:?}", response.results)
+    };
+    assert_eq!(Some(parent.id), subtask.parent_id);
+    assert_eq!(TodoStatus::Archived, parent.status);
+    assert_eq!(created_at.timestamp_micros(), parent.created_at.timestamp_micros());
+    assert_eq!(vec!["local".to_string()], parent.tags);
+
+    let response = client.import_todos(todos).await?;
+
+    assert_eq!((0, 2, 0), response.counts());
+
+    // clean data
+    client.clear_todos(vec![parent.id, subtask.id]).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn export_import_todos() -> anyhow::Result<()> {
+    let base_url = spawn_server();
+    let scope = ScopeClient::default().endpoint(&base_url);
+    let source = scope.clone().namespace(&format!("{}{}", NS, "/export"))?.todo_client();
+
+    let parent = source.create_todo(CreateTodoRequest {
+        content: "a \"quoted\", comma\nand a second line \\ <!-- -->".to_string(),
+        due_at: Some(Utc::now()),
+        priority: Some(TodoPriority::High),
+        tags: vec!["export".to_string(), "backup".to_string()],
+        ..Default::default()
+    }).await?;
+    let subtask = source.create_todo(CreateTodoRequest {
+        content: "subtask".to_string(),
+        parent_id: Some(parent.id),
+        ..Default::default()
+    }).await?;
+    source.update_todo(subtask.id, UpdateTodoRequest { status: Some(TodoStatus::Done), ..Default::default() }).await?;
+
+    let exported = source.query_todos(GetTodosQuery { sort: Some(TodoSort::Manual), ..Default::default() }).await?;
+
+    for format in [ExportFormat::Json, ExportFormat::Csv, ExportFormat::Md] {
+        let document = source.export_todos(format).await?;
+        assert_eq!(
+            exported.iter().cloned().map(ImportTodo::from).collect::<Vec<ImportTodo>>(),
+            format.parse(&document)?,
+            "{:?}", format,
+        );
+
+        let target = scope.clone().namespace(&format!("{}/import/{}", NS, format.extension()))?.todo_client();
+        let response = target.import_document(format, document).await?;
+        assert_eq!((0, 0), (response.counts().1, response.counts().2), "{:?}", response);
+
+        let imported = target.query_todos(GetTodosQuery { sort: Some(TodoSort::Manual), ..Default::default() }).await?;
+        let ids = |todos: &[TodoResponse]| todos.iter().map(|it| it.id).collect::<Vec<i32>>();
+        let strip = |todos: Vec<TodoResponse>, ids: Vec<i32>| todos.into_iter()
+            .map(|it| (it.content, it.status, it.created_at, it.due_at, it.priority, it.tags, it.parent_id.map(|id| ids.iter().position(|it| *it == id))))
+            .collect::<Vec<_>>();
+        assert_eq!(strip(exported.clone(), ids(&exported)), strip(imported.clone(), ids(&imported)), "{:?}", format);
+
+        // clean data
+        target.clear_todos(ids(&imported)).await?;
+    }
+
+    // clean data
+    source.clear_todos(vec![parent.id, subtask.id]).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn import_export_todo_txt() -> anyhow::Result<()> {
+    let base_url = spawn_server();
+    let client = ScopeClient::default()
+        .endpoint(&base_url)
+        .namespace(&format!("{}{}", NS, "/todo_txt"))?
+        .todo_client();
+
+    let document = "(A) 2024-01-01 plan the trip +travel @home due:2024-02-01 id:1\n\
+        x 2024-01-03 2024-01-02 book the flights +travel id:2 parent:1\n\
+        \n\
+        (D) 2024-01-04 pack id:3 parent:1\n";
+    let response = client.import_document(ExportFormat::Txt, document.to_string()).await?;
+
+    assert_eq!((3, 0, 0), response.counts(), "{:?}", response);
+
+    let imported = client.query_todos(GetTodosQuery { sort: Some(TodoSort::Manual), ..Default::default() }).await?;
+    let trip = imported.iter().find(|it| it.content == "plan the trip").unwrap();
+    let flights = imported.iter().find(|it| it.content == "book the flights").unwrap();
+    let pack = imported.iter().find(|it| it.content == "pack").unwrap();
+
+    assert_eq!((TodoStatus::Todo, TodoPriority::High), (trip.status, trip.priority));
+    assert_eq!(vec!["@home".to_string(), "travel".to_string()], trip.tags);
+    assert_eq!("2024-02-01T00:00:00+00:00", trip.due_at.unwrap().to_rfc3339());
+    assert_eq!((TodoStatus::Done, Some(trip.id)), (flights.status, flights.parent_id));
+    assert_eq!("2024-01-02T00:00:00+00:00", flights.created_at.to_rfc3339());
+    assert_eq!((TodoPriority::Low, Some(trip.id)), (pack.priority, pack.parent_id));
+
+    let exported = client.export_todos(ExportFormat::Txt).await?;
+
+    assert_eq!(
+        imported.iter().cloned().map(ImportTodo::from).collect::<Vec<ImportTodo>>(),
+        ExportFormat::Txt.parse(&exported)?,
+        "{}", exported,
+    );
+    assert!(exported.contains(&format!("(A) 2024-01-01 plan the trip @home +travel due:2024-02-01 id:{}\n", trip.id)), "{}", exported);
+
+    // clean data
+    client.clear_todos(vec![trip.id, flights.id, pack.id]).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn todos_ics() -> anyhow::Result<()> {
+    let ns = format!("{}{}", NS, "/ics");
+    let base_url = spawn_server();
+    let client = ScopeClient::default()
+        .endpoint(&base_url)
+        .namespace(&ns)?
+        .todo_client();
+
+    let content = "a long line; with commas, a backslash \\ and ünïcödé\nthat is folded across lines of the calendar";
+    let created = client.create_todo(CreateTodoRequest {
+        content: content.to_string(),
+        tags: vec!["ics".to_string()],
+        ..Default::default()
+    }).await?;
+
+    let response = reqwest::Client::new()
+        .get(format!("{}/todos.ics", base_url))
+        .query(&[("ns", &ns)])
+        .send().await?;
+    assert_eq!("text/calendar; charset=utf-8", response.headers()["content-type"]);
+
+    let calendar = response.text().await?;
+    assert!(calendar.split("\r\n").all(|it| it.len() <= 75), "{}", calendar);
+
+    let unfolded = calendar.replace("\r\n ", "");
+    assert!(unfolded.contains(&format!("UID:{}/{}@rust-fullstack-todo\r\n", ns, created.id)), "{}", unfolded);
+    assert!(unfolded.contains("SUMMARY:a long line\\; with commas\\, a backslash \\\\ and ünïcödé\\nthat is folded across lines of the calendar\r\n"), "{}", unfolded);
+    assert!(unfolded.contains("STATUS:NEEDS-ACTION\r\nPRIORITY:5\r\nCATEGORIES:ics\r\n"), "{}", unfolded);
+
+    // clean data
+    client.clear_todos(vec![created.id]).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn todo_events() -> anyhow::Result<()> {
+    let ns = "testing/integration/events";
+    let base_url = spawn_server();
+    let client = ScopeClient::default()
+        .endpoint(&base_url)
+        .namespace(ns)?
+        .todo_client();
+
+    let mut events = reqwest::Client::new()
+        .get(format!("{}/todos/events", base_url))
+        .header("t-ns", ns)
+        .send().await?;
+    assert_eq!("text/event-stream", events.headers()["content-type"]);
+
+    // created again until the server is listening
+    let mut received = String::new();
+    let mut attempts = 0;
+    let created = loop {
+        attempts += 1;
+        assert!(attempts <= 20, "no event received, got {:?}", received);
+
+        let created = client.create_todo(CreateTodoRequest {
+            content: "Create todo for events".to_string(),
+            ..Default::default()
+        }).await?;
+
+        if receive(&mut events, &mut received, &format!(r#"{{"type":"created","id":{}}}"#, created.id)).await? {
+            break created;
+        }
+    };
+
+    client.update_todo(created.id, UpdateTodoRequest {
+        status: Some(TodoStatus::Done),
+        ..Default::default()
+    }).await?;
+    assert!(receive(&mut events, &mut received, &format!(r#"{{"type":"updated","id":{}}}"#, created.id)).await?);
+
+    client.clear_todos(vec![created.id]).await?;
+    assert!(receive(&mut events, &mut received, &format!(r#"{{"type":"deleted","id":{}}}"#, created.id)).await?);
+
+    Ok(())
+}
+
+/// read the stream into `received` until the `data` of an event, `false` when it does not come in time
+async fn receive(events: &mut reqwest::Response, received: &mut String, data: &str) -> anyhow::Result<bool> {
+    let frame = format!("data: {}\n\n", data);
+    while !received.contains(&frame) {
+        match tokio::time::timeout(std::time::Duration::from_millis(500), events.chunk()).await {
+            Ok(chunk) => received.push_str(&String::from_utf8(chunk?.unwrap_or_default().to_vec())?),
+            Err(_) => return Ok(false),
+        }
+    }
+
+    Ok(true)
+}
+
 #[tokio::test]
 async fn get_todos_query_status() -> anyhow::Result<()> {
     let base_url = spawn_server();
 
     let client = ScopeClient::default()
         .endpoint(&base_url)
-        .namespace(&format!("{}{}", NS, "/query_status"))
+        .namespace(&format!("{}{}", NS, "/query_status"))?
         .todo_client();
 
     let deleted_todo = create_todo_with_status(&client, Some(TodoStatus::Deleted)).await?;
@@ -100,12 +643,24 @@ async fn assert_todos_with_status(client: &TodoClient, status: Option<TodoStatus
 async fn create_todo_with_status(client: &TodoClient, status: Option<TodoStatus>) -> anyhow::Result<TodoResponse> {
     let created = client.create_todo(CreateTodoRequest {
         content: format!("create todo with status {:?}", status),
+        ..Default::default()
     }).await?;
 
-    let response = client.update_todo(created.id.clone(), UpdateTodoRequest {
-        content: None,
-        status,
-    }).await?;
+    // walk through the status machine: todo -> archived -> deleted
+    let path = match status {
+        Some(TodoStatus::Deleted) => vec![TodoStatus::Archived, TodoStatus::Deleted],
+        Some(status) => vec![status],
+        None => vec![],
+    };
+
+    let mut response = created;
+    for status in path {
+        response = client.update_todo(response.id, UpdateTodoRequest {
+            content: None,
+            status: Some(status),
+            ..Default::default()
+        }).await?;
+    }
 
     Ok(response)
 }
@@ -127,6 +682,7 @@ async fn create_todo() -> anyhow::Result<()> {
 
     let data = client.create_todo(CreateTodoRequest {
         content: "create a new todo".to_string(),
+        ..Default::default()
     }).await?;
 
     assert_eq!("create a new todo", data.content);
@@ -148,6 +704,7 @@ async fn update_todo() -> anyhow::Result<()> {
     // create first
     let created = client.create_todo(CreateTodoRequest {
         content: "Create todo for update".to_string(),
+        ..Default::default()
     }).await?;
 
     let id = created.id;
@@ -156,6 +713,7 @@ async fn update_todo() -> anyhow::Result<()> {
     let updated = client.update_todo(id, UpdateTodoRequest {
         content: Some("Update todo".to_string()),
         status: Some(TodoStatus::Done),
+        ..Default::default()
     }).await?;
 
     // assert
@@ -167,10 +725,104 @@ async fn update_todo() -> anyhow::Result<()> {
     Ok(())
 }
 
-// #[tokio::test]
-// async fn update_todo_notfound() -> anyhow::Result<()> {
-//     todo!()
-// }
+#[tokio::test]
+async fn update_todo_if_match() -> anyhow::Result<()> {
+    let client = client();
+
+    let created = client.create_todo(CreateTodoRequest {
+        content: "Create todo for if-match".to_string(),
+        ..Default::default()
+    }).await?;
+
+    // the first writer wins
+    let updated = client.update_todo_if_match(created.id, created.version, UpdateTodoRequest {
+        content: Some("Update todo by first".to_string()),
+        ..Default::default()
+    }).await?;
+
+    assert_eq!(created.version + 1, updated.version);
+
+    // the second one is stale
+    let error = client.update_todo_if_match(created.id, created.version, UpdateTodoRequest {
+        content: Some("Update todo by second".to_string()),
+        ..Default::default()
+    }).await
+        .err()
+        .unwrap();
+
+    assert!(matches!(error, ClientError::Conflict(_)));
+    assert_eq!(Some(ErrorCode::VersionConflict), error.code());
+    client.assert_eq(created.id, &updated).await?;
+
+    // clear data
+    client.clear_todos(vec![created.id]).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn update_todo_invalid_status_transition() -> anyhow::Result<()> {
+    let client = client();
+
+    let created = client.create_todo(CreateTodoRequest {
+        content: "Create todo for invalid transition".to_string(),
+        ..Default::default()
+    }).await?;
+
+    let error = client.update_todo(created.id, UpdateTodoRequest {
+        content: None,
+        status: Some(TodoStatus::Deleted),
+        ..Default::default()
+    }).await
+        .err()
+        .unwrap();
+
+    assert!(matches!(error, ClientError::Conflict(_)));
+    assert_eq!(Some(ErrorCode::InvalidStatusTransition), error.code());
+    client.assert_eq(created.id, &created).await?;
+
+    // clear data
+    client.clear_todos(vec![created.id]).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn update_todo_validation_failed() -> anyhow::Result<()> {
+    let client = client();
+
+    let error = client.update_todo(99999, UpdateTodoRequest {
+        content: None,
+        status: None,
+        ..Default::default()
+    }).await
+        .err()
+        .unwrap();
+
+    assert!(matches!(error, ClientError::Validation(_)));
+    assert_eq!(Some(ErrorCode::ValidationFailed), error.code());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn update_todo_notfound() -> anyhow::Result<()> {
+    let client = client();
+
+    let error = match client.update_todo(99999, UpdateTodoRequest {
+        content: Some("Update todo".to_string()),
+        status: None,
+        ..Default::default()
+    }).await {
+        Err(ClientError::NotFound(e)) => e,
+        other => panic!("expect not found, got {:?}", other),
+    };
+
+    assert_eq!(ErrorCode::TodoNotFound, error.code);
+    assert_eq!("Todo [id: 99999] not found.", error.message);
+
+    Ok(())
+}
 
 fn client() -> TodoClient {
     let base_url = spawn_server();
@@ -178,5 +830,6 @@ fn client() -> TodoClient {
     ScopeClient::default()
         .endpoint(&base_url)
         .namespace(NS)
+        .unwrap()
         .todo_client()
 }
@@ -0,0 +1,117 @@
+use std::path::PathBuf;
+
+use config::{ConfigError, File, FileFormat};
+use serde::Deserialize;
+
+pub const DEFAULT_ENDPOINT: &str = "http://localhost:3000";
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+/// the config file, every key is optional
+#[derive(Deserialize, Debug, Default, PartialEq)]
+pub struct Config {
+    pub endpoint: Option<String>,
+    pub namespace: Option<String>,
+    /// of `POST /auth/login`
+    pub token: Option<String>,
+    /// of `POST /keys`, in place of the token
+    pub api_key: Option<String>,
+}
+
+impl Config {
+    /// `path` has to exist, the default path may not
+    pub fn load(path: Option<PathBuf>) -> Result<Self, ConfigError> {
+        let (path, required) = match path {
+            Some(path) => (path, true),
+            None => match default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Self::default()),
+            },
+        };
+
+        config::Config::builder()
+            .add_source(File::from(path).format(FileFormat::Toml).required(required))
+            .build()?
+            .try_deserialize()
+    }
+
+    /// the flag or env, then the file, then the default
+    pub fn endpoint(&self, flag: Option<String>) -> String {
+        flag.or_else(|| self.endpoint.clone())
+            .unwrap_or_else(|| DEFAULT_ENDPOINT.to_string())
+            .trim_end_matches('/')
+            .to_string()
+    }
+
+    /// the flag or env, then the file, then the namespace of the API key or else the default
+    pub fn namespace(&self, flag: Option<String>, api_key: Option<&str>) -> Option<String> {
+        flag.or_else(|| self.namespace.clone())
+            .or_else(|| api_key.is_none().then(|| DEFAULT_NAMESPACE.to_string()))
+    }
+
+    /// the flag or env, then the file, anonymous without either
+    pub fn token(&self, flag: Option<String>) -> Option<String> {
+        flag.or_else(|| self.token.clone())
+    }
+
+    /// the flag or env, then the file
+    pub fn api_key(&self, flag: Option<String>) -> Option<String> {
+        flag.or_else(|| self.api_key.clone())
+    }
+}
+
+/// `$XDG_CONFIG_HOME/todo/config.toml`, or under `~/.config`
+fn default_path() -> Option<PathBuf> {
+    let dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|it| !it.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(dir.join("todo").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    #[test]
+    fn precedence() {
+        let config = Config {
+            endpoint: Some("http://todos.example.com/".to_string()),
+            namespace: None,
+            token: Some("file-token".to_string()),
+            api_key: None,
+        };
+
+        assert_eq!("http://localhost:4000", config.endpoint(Some("http://localhost:4000".to_string())));
+        assert_eq!("http://todos.example.com", config.endpoint(None));
+        assert_eq!(Some("work".to_string()), config.namespace(Some("work".to_string()), Some("tk_key")));
+        assert_eq!(Some(DEFAULT_NAMESPACE.to_string()), config.namespace(None, None));
+        assert_eq!(None, config.namespace(None, Some("tk_key")));
+        assert_eq!(DEFAULT_ENDPOINT, Config::default().endpoint(None));
+        assert_eq!(Some("flag-token".to_string()), config.token(Some("flag-token".to_string())));
+        assert_eq!(Some("file-token".to_string()), config.token(None));
+        assert_eq!(None, Config::default().token(None));
+        assert_eq!(Some("tk_flag".to_string()), config.api_key(Some("tk_flag".to_string())));
+        assert_eq!(None, config.api_key(None));
+    }
+
+    #[test]
+    fn load() {
+        let path = std::env::temp_dir().join(format!("todo-cli-{}.toml", std::process::id()));
+        std::fs::File::create(&path).unwrap()
+            .write_all(b"endpoint = \"http://todos.example.com\"\nnamespace = \"work\"\n").unwrap();
+
+        let config = Config::load(Some(path.clone())).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(Config {
+            endpoint: Some("http://todos.example.com".to_string()),
+            namespace: Some("work".to_string()),
+            token: None,
+            api_key: None,
+        }, config);
+        assert!(Config::load(Some(path)).is_err());
+    }
+}
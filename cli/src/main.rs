@@ -0,0 +1,317 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{Parser, Subcommand};
+use ::config::ConfigError;
+use serde::de::DeserializeOwned;
+
+use common::client::{ClientError, ScopeClient, TodoClient};
+use common::model::{CreateTodoRequest, ExportFormat, GetTodosQuery, TodoPriority, TodoSort, TodoStatus, UpdateTodoRequest};
+
+use crate::config::Config;
+use crate::output::Output;
+
+mod config;
+mod output;
+
+const EXIT_CODES: &str = r#"exit codes:
+    0  done
+    1  the server responded an error
+    2  invalid usage or config
+    3  the server is unreachable"#;
+
+/// manage the todos of a namespace from the command line
+#[derive(Parser, Debug)]
+#[command(name = "todo", version, after_help = EXIT_CODES)]
+struct Cli {
+    /// base url of the backend, else `endpoint` of the config file [default: http://localhost:3000]
+    #[arg(short, long, global = true, env = "TODO_ENDPOINT")]
+    endpoint: Option<String>,
+    /// else `namespace` of the config file, else the one of the API key [default: default]
+    #[arg(short, long, global = true, env = "TODO_NAMESPACE")]
+    namespace: Option<String>,
+    /// bearer token of `POST /auth/login`, else `token` of the config file
+    #[arg(long, global = true, env = "TODO_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+    /// API key of `POST /keys` for scripts and CI, in place of the token, else `api_key` of the config file
+    #[arg(long, global = true, env = "TODO_API_KEY", hide_env_values = true)]
+    api_key: Option<String>,
+    /// TOML file of `endpoint`, `namespace`, `token` and `api_key` [default: $XDG_CONFIG_HOME/todo/config.toml]
+    #[arg(long, global = true, env = "TODO_CONFIG")]
+    config: Option<PathBuf>,
+    #[arg(short, long, global = true, env = "TODO_OUTPUT", value_enum, default_value_t)]
+    output: Output,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// list the todos, newest first
+    List {
+        /// todo, done, archived or deleted
+        #[arg(long, value_parser = lowercase::<TodoStatus>)]
+        status: Option<TodoStatus>,
+        /// full-text search on the content, ordered by rank
+        #[arg(long)]
+        search: Option<String>,
+        /// labeled with the tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// newest, priority or manual
+        #[arg(long, value_parser = lowercase::<TodoSort>)]
+        sort: Option<TodoSort>,
+    },
+    /// create a todo
+    Add {
+        content: String,
+        /// RFC 3339, or a day at midnight UTC, e.g. 2024-01-01
+        #[arg(long, value_parser = parse_due)]
+        due: Option<DateTime<Utc>>,
+        /// low, normal or high
+        #[arg(long, value_parser = lowercase::<TodoPriority>)]
+        priority: Option<TodoPriority>,
+        /// repeat for more tags
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// create as a subtask of the todo
+        #[arg(long)]
+        parent: Option<i32>,
+    },
+    /// update the given fields of a todo
+    Edit {
+        id: i32,
+        #[arg(long)]
+        content: Option<String>,
+        /// RFC 3339, or a day at midnight UTC, e.g. 2024-01-01
+        #[arg(long, value_parser = parse_due, conflicts_with = "no_due")]
+        due: Option<DateTime<Utc>>,
+        /// clear the due date
+        #[arg(long)]
+        no_due: bool,
+        /// low, normal or high
+        #[arg(long, value_parser = lowercase::<TodoPriority>)]
+        priority: Option<TodoPriority>,
+        /// replaces all the tags, repeat for more tags
+        #[arg(long = "tag", conflicts_with = "no_tags")]
+        tags: Vec<String>,
+        /// remove all the tags
+        #[arg(long)]
+        no_tags: bool,
+        /// update only while the todo is still at this version
+        #[arg(long)]
+        if_match: Option<i32>,
+    },
+    /// mark a todo done
+    Done {
+        id: i32,
+        /// mark the open subtasks done along with it
+        #[arg(long)]
+        cascade: bool,
+    },
+    /// archive a todo
+    Archive {
+        id: i32,
+    },
+    /// mark an archived todo deleted
+    Delete {
+        id: i32,
+    },
+    /// hard-delete the deleted todos, all of them without ids
+    Clear {
+        ids: Vec<i32>,
+    },
+    /// print all the todos as a document of `GET /todos/export`
+    Export {
+        /// json, csv, md, txt or ics
+        #[arg(short, long, value_parser = lowercase::<ExportFormat>, default_value = "json")]
+        format: ExportFormat,
+    },
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let client = match Config::load(cli.config)
+        .and_then(|config| scope_client(&config, cli.endpoint, cli.namespace, cli.token, cli.api_key)) {
+        Ok(client) => client,
+        Err(err) => {
+            eprintln!("error: invalid config: {}", err);
+            return ExitCode::from(2);
+        }
+    };
+    let client = client.todo_client();
+
+    match run(&client, cli.command, cli.output).await {
+        Ok(output) => {
+            print!("{}", output);
+            if !output.ends_with('\n') {
+                println!();
+            }
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::from(match err {
+                ClientError::Transport(_) => 3,
+                _ => 1,
+            })
+        }
+    }
+}
+
+/// the client of the flags, env and config file, an error for what cannot be sent in a header
+fn scope_client(
+    config: &Config,
+    endpoint: Option<String>,
+    namespace: Option<String>,
+    token: Option<String>,
+    api_key: Option<String>,
+) -> Result<ScopeClient, ConfigError> {
+    let invalid = |err: ClientError| ConfigError::Message(err.to_string());
+    let mut client = ScopeClient::default()
+        .endpoint(&config.endpoint(endpoint));
+    let api_key = config.api_key(api_key);
+    if let Some(namespace) = config.namespace(namespace, api_key.as_deref()) {
+        client = client.namespace(&namespace).map_err(invalid)?;
+    }
+    match (api_key, config.token(token)) {
+        (Some(key), _) => client = client.api_key(&key).map_err(invalid)?,
+        (None, Some(token)) => client = client.credentials(&token).map_err(invalid)?,
+        (None, None) => {}
+    }
+
+    Ok(client)
+}
+
+/// what to print for the command
+async fn run(client: &TodoClient, command: Command, output: Output) -> Result<String, ClientError> {
+    match command {
+        Command::List { status, search, tag, sort } => {
+            let todos = client.query_todos(GetTodosQuery { status, q: search, tag, sort, ..Default::default() }).await?;
+            Ok(output.todos(&todos))
+        }
+        Command::Add { content, due, priority, tags, parent } => {
+            let todo = client.create_todo(CreateTodoRequest {
+                content,
+                due_at: due,
+                priority,
+                tags,
+                parent_id: parent,
+            }).await?;
+            Ok(output.todo(&todo))
+        }
+        Command::Edit { id, content, due, no_due, priority, tags, no_tags, if_match } => {
+            let body = UpdateTodoRequest {
+                content,
+                due_at: if no_due { Some(None) } else { due.map(Some) },
+                priority,
+                tags: if no_tags { Some(vec![]) } else { Some(tags).filter(|it| !it.is_empty()) },
+                ..Default::default()
+            };
+            let todo = match if_match {
+                Some(version) => client.update_todo_if_match(id, version, body).await?,
+                None => client.update_todo(id, body).await?,
+            };
+            Ok(output.todo(&todo))
+        }
+        Command::Done { id, cascade } => transit(client, id, TodoStatus::Done, cascade, output).await,
+        Command::Archive { id } => transit(client, id, TodoStatus::Archived, false, output).await,
+        Command::Delete { id } => transit(client, id, TodoStatus::Deleted, false, output).await,
+        Command::Clear { ids } => {
+            let ids = match ids.is_empty() {
+                true => client.get_todos(Some(TodoStatus::Deleted)).await?
+                    .into_iter()
+                    .map(|it| it.id)
+                    .collect(),
+                false => ids,
+            };
+            if !ids.is_empty() {
+                client.clear_todos(ids.clone()).await?;
+            }
+            Ok(output.cleared(&ids))
+        }
+        Command::Export { format } => client.export_todos(format).await,
+    }
+}
+
+async fn transit(client: &TodoClient, id: i32, status: TodoStatus, cascade: bool, output: Output) -> Result<String, ClientError> {
+    let todo = client.update_todo(id, UpdateTodoRequest {
+        status: Some(status),
+        cascade,
+        ..Default::default()
+    }).await?;
+
+    Ok(output.todo(&todo))
+}
+
+/// the lowercase name of a variant, as in the requests
+fn lowercase<T: DeserializeOwned>(value: &str) -> Result<T, String> {
+    serde_json::from_value(serde_json::Value::from(value))
+        .map_err(|_| format!("invalid value [{}]", value))
+}
+
+fn parse_due(value: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|it| it.with_timezone(&Utc))
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map(|it| it.and_hms_opt(0, 0, 0).expect("midnight").and_utc()))
+        .map_err(|_| format!("invalid date [{}], expect RFC 3339 or YYYY-MM-DD", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{CommandFactory, Parser};
+
+    use common::model::{ExportFormat, TodoPriority, TodoStatus};
+
+    use super::{Cli, Command, parse_due, scope_client};
+    use crate::config::Config;
+    use crate::output::Output;
+
+    #[test]
+    fn args() {
+        Cli::command().debug_assert();
+
+        let cli = Cli::try_parse_from(["todo", "-n", "work", "add", "write the cli", "--priority", "high", "--tag", "cli", "--tag", "rust"]).unwrap();
+        assert_eq!(Some("work".to_string()), cli.namespace);
+        match cli.command {
+            Command::Add { content, priority, tags, .. } => {
+                assert_eq!("write the cli", content);
+                assert_eq!(Some(TodoPriority::High), priority);
+                assert_eq!(vec!["cli".to_string(), "rust".to_string()], tags);
+            }
+            other => panic!("expect add, got {:?}", other),
+        }
+
+        let cli = Cli::try_parse_from(["todo", "list", "--status", "done", "-o", "json"]).unwrap();
+        assert_eq!(Output::Json, cli.output);
+        assert!(matches!(cli.command, Command::List { status: Some(TodoStatus::Done), .. }));
+
+        let cli = Cli::try_parse_from(["todo", "export"]).unwrap();
+        assert!(matches!(cli.command, Command::Export { format: ExportFormat::Json }));
+
+        assert!(Cli::try_parse_from(["todo", "list", "--status", "open"]).is_err());
+        assert!(Cli::try_parse_from(["todo", "edit", "1", "--due", "2024-01-01", "--no-due"]).is_err());
+    }
+
+    #[test]
+    fn header() {
+        let client = |namespace: &str, token: &str| scope_client(&Config::default(), None, Some(namespace.to_string()), Some(token.to_string()), None);
+
+        assert!(client("Équipe Web", "token").is_ok());
+        for invalid in ["token\n", "to\rken", "\u{7f}"] {
+            assert!(client("work", invalid).is_err(), "{:?}", invalid);
+            assert!(client(invalid, "token").is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn due() {
+        assert_eq!("2024-01-01T00:00:00+00:00", parse_due("2024-01-01").unwrap().to_rfc3339());
+        assert_eq!("2024-01-01T00:00:00+00:00", parse_due("2024-01-01T08:00:00+08:00").unwrap().to_rfc3339());
+        assert!(parse_due("tomorrow").is_err());
+    }
+}
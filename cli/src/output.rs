@@ -0,0 +1,134 @@
+use clap::ValueEnum;
+use serde::Serialize;
+
+use common::model::TodoResponse;
+
+const HEADER: [&str; 7] = ["ID", "PARENT", "STATUS", "PRIORITY", "DUE", "TAGS", "CONTENT"];
+
+#[derive(ValueEnum, Debug, Default, PartialEq, Clone, Copy)]
+pub enum Output {
+    /// aligned columns, the first line of the content
+    #[default]
+    Table,
+    /// the responses of the server as they are
+    Json,
+}
+
+impl Output {
+    pub fn todos(&self, todos: &[TodoResponse]) -> String {
+        match self {
+            Output::Table => table(todos),
+            Output::Json => json(&todos),
+        }
+    }
+
+    pub fn todo(&self, todo: &TodoResponse) -> String {
+        match self {
+            Output::Table => table(std::slice::from_ref(todo)),
+            Output::Json => json(todo),
+        }
+    }
+
+    /// the ids hard-deleted by `clear`
+    pub fn cleared(&self, ids: &[i32]) -> String {
+        match self {
+            Output::Table => format!("cleared {} todos", ids.len()),
+            Output::Json => json(&serde_json::json!({ "cleared": ids })),
+        }
+    }
+}
+
+fn json<T: Serialize + ?Sized>(value: &T) -> String {
+    serde_json::to_string_pretty(value).expect("a response is serializable")
+}
+
+/// the content is the last column, so it is not padded
+fn table(todos: &[TodoResponse]) -> String {
+    let rows = todos.iter()
+        .map(|todo| [
+            todo.id.to_string(),
+            todo.parent_id.map(|it| it.to_string()).unwrap_or_default(),
+            todo.status.to_string().to_lowercase(),
+            todo.priority.to_string().to_lowercase(),
+            todo.due_at.map(|it| it.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default(),
+            todo.tags.join(","),
+            todo.content.lines().next().unwrap_or_default().to_string(),
+        ])
+        .collect::<Vec<[String; 7]>>();
+
+    let mut widths = HEADER.map(|it| it.chars().count());
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let line = |cells: [&str; 7]| {
+        let mut line = cells.iter()
+            .zip(widths)
+            .take(cells.len() - 1)
+            .map(|(cell, width)| format!("{:<width$}  ", cell, width = width))
+            .collect::<String>();
+        line.push_str(cells[cells.len() - 1]);
+        line.trim_end().to_string()
+    };
+
+    std::iter::once(line(HEADER))
+        .chain(rows.iter().map(|row| line(row.each_ref().map(String::as_str))))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use common::model::{TodoPriority, TodoResponse, TodoStatus};
+
+    use super::Output;
+
+    fn todo(id: i32, content: &str) -> TodoResponse {
+        TodoResponse {
+            namespace: "default".to_string(),
+            id,
+            content: content.to_string(),
+            status: TodoStatus::Todo,
+            created_at: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+            updated_at: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+            due_at: None,
+            priority: TodoPriority::Normal,
+            tags: vec![],
+            parent_id: None,
+            version: 1,
+            rank: None,
+            snippet: None,
+        }
+    }
+
+    #[test]
+    fn table() {
+        let mut first = todo(1, "first thing\nwith details");
+        first.tags = vec!["backend".to_string(), "release".to_string()];
+        first.due_at = Some(Utc.with_ymd_and_hms(2024, 2, 1, 8, 30, 0).unwrap());
+        let mut subtask = todo(12, "subtask");
+        subtask.status = TodoStatus::Done;
+        subtask.priority = TodoPriority::High;
+        subtask.parent_id = Some(1);
+
+        assert_eq!(
+            "ID  PARENT  STATUS  PRIORITY  DUE               TAGS             CONTENT\n\
+             1           todo    normal    2024-02-01 08:30  backend,release  first thing\n\
+             12  1       done    high                                         subtask",
+            Output::Table.todos(&[first, subtask]),
+        );
+        assert_eq!("ID  PARENT  STATUS  PRIORITY  DUE  TAGS  CONTENT", Output::Table.todos(&[]));
+    }
+
+    #[test]
+    fn json() {
+        let output = Output::Json.todo(&todo(1, "first thing"));
+
+        assert_eq!(todo(1, "first thing"), serde_json::from_str::<TodoResponse>(&output).unwrap());
+        assert_eq!("{\n  \"cleared\": [\n    1,\n    2\n  ]\n}", Output::Json.cleared(&[1, 2]));
+    }
+}
@@ -1,16 +1,30 @@
-use reqwest::{Client, ClientBuilder};
-use reqwest::header::HeaderMap;
+use reqwest::{Client, ClientBuilder, Response};
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue};
+use crate::model::{ErrorCode, ErrorResponse};
 
 mod todo_client;
+mod tag_client;
 mod ping_client;
+mod auth_client;
+mod member_client;
+mod key_client;
+mod client_error;
 
-pub use todo_client::TodoClient;
+pub use todo_client::{TodoClient, TodoPages};
+pub use tag_client::TagClient;
 pub use ping_client::PingClient;
+pub use auth_client::AuthClient;
+pub use member_client::MemberClient;
+pub use key_client::KeyClient;
+pub use client_error::ClientError;
 
 #[derive(Clone)]
 pub struct ScopeClient {
     endpoint: String,
-    namespace: Option<String>,
+    /// the `t-ns` header of the requests
+    namespace: Option<HeaderValue>,
+    /// the `Authorization` header of the requests
+    authorization: Option<HeaderValue>,
     inner: Client,
 }
 
@@ -19,6 +33,7 @@ impl Default for ScopeClient {
         Self {
             endpoint: "".to_string(),
             namespace: None,
+            authorization: None,
             inner: Client::default(),
         }
     }
@@ -42,30 +57,82 @@ impl ScopeClient {
         }
     }
 
-    pub fn namespace(self, namespace: &str) -> Self {
-        Self {
-            namespace: Some(namespace.to_string()),
+    /// `ClientError::Validation` for what cannot be sent in a header, the setters below alike
+    pub fn namespace(self, namespace: &str) -> Result<Self, ClientError> {
+        Ok(Self {
+            namespace: Some(header_of("namespace", namespace.to_string())?),
             ..self
-        }
+        })
+    }
+
+    /// authenticate the requests with the bearer token of `POST /auth/login`
+    pub fn credentials(self, token: &str) -> Result<Self, ClientError> {
+        Ok(Self {
+            authorization: Some(header_of("token", format!("Bearer {}", token))?),
+            ..self
+        })
+    }
+
+    /// authenticate the requests with the API key of `POST /keys`, for scripts and CI.
+    /// the key is bound to its namespace, which is the default one of the requests
+    pub fn api_key(self, key: &str) -> Result<Self, ClientError> {
+        Ok(Self {
+            authorization: Some(header_of("api_key", format!("ApiKey {}", key))?),
+            ..self
+        })
     }
 
     pub fn todo_client(&self) -> TodoClient {
         let mut c = self.clone();
-        c.inner = client(self.namespace.clone());
+        c.inner = client(self.namespace.clone(), self.authorization.clone());
         TodoClient::from(c)
     }
 
+    pub fn tag_client(&self) -> TagClient {
+        let mut c = self.clone();
+        c.inner = client(self.namespace.clone(), self.authorization.clone());
+        TagClient::from(c)
+    }
+
+    pub fn member_client(&self) -> MemberClient {
+        let mut c = self.clone();
+        c.inner = client(self.namespace.clone(), self.authorization.clone());
+        MemberClient::from(c)
+    }
+
+    pub fn key_client(&self) -> KeyClient {
+        let mut c = self.clone();
+        c.inner = client(self.namespace.clone(), self.authorization.clone());
+        KeyClient::from(c)
+    }
+
+    pub fn auth_client(&self) -> AuthClient {
+        let mut c = self.clone();
+        c.inner = client(None, self.authorization.clone());
+        AuthClient::from(c)
+    }
+
     pub fn ping_client(&self) -> PingClient {
         PingClient::from(self.clone())
     }
 }
 
-pub(crate) fn client(namespace: Option<String>) -> Client {
+/// a header value has no control characters, such as the trailing newline of a pasted token
+fn header_of(key: &str, value: String) -> Result<HeaderValue, ClientError> {
+    HeaderValue::try_from(value).map_err(|_| ClientError::Validation(ErrorResponse {
+        code: ErrorCode::ValidationFailed,
+        message: format!("`{}` contains a control character", key),
+    }))
+}
+
+fn client(namespace: Option<HeaderValue>, authorization: Option<HeaderValue>) -> Client {
     let mut default_headers = HeaderMap::new();
-    if namespace.is_some() {
-        let namespace = namespace.unwrap().parse().unwrap();
+    if let Some(namespace) = namespace {
         default_headers.insert("t-ns", namespace);
     }
+    if let Some(authorization) = authorization {
+        default_headers.insert(AUTHORIZATION, authorization);
+    }
 
     Client::builder()
         .default_headers(default_headers)
@@ -73,3 +140,18 @@ pub(crate) fn client(namespace: Option<String>) -> Client {
         .expect("Failed to create reqwest Client")
 }
 
+
+/// decode the `ErrorResponse` of a failed request
+pub(crate) async fn error_of(response: Response) -> ClientError {
+    let status = response.status();
+
+    let error = match response.json::<ErrorResponse>().await {
+        Ok(error) => error,
+        Err(_) => ErrorResponse {
+            code: ErrorCode::InternalError,
+            message: format!("unexpected response <{}>", status),
+        },
+    };
+
+    ClientError::from_status(status, error)
+}
@@ -0,0 +1,122 @@
+use std::ops::Deref;
+use crate::model::{CredentialsRequest, InvitationRequest, NamespaceRequest, TokenResponse, UserResponse};
+use super::{error_of, ClientError, ScopeClient};
+
+pub struct AuthClient(ScopeClient);
+
+impl From<ScopeClient> for AuthClient {
+    fn from(c: ScopeClient) -> Self {
+        Self(c)
+    }
+}
+
+impl Deref for AuthClient {
+    type Target = ScopeClient;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AuthClient {
+    /// the user owns the namespace `@{username}` from now on
+    pub async fn register(&self, username: &str, password: &str) -> Result<UserResponse, ClientError> {
+        let response = self.inner.post(format!("{}/auth/register", self.endpoint))
+            .json(&CredentialsRequest { username: username.to_string(), password: password.to_string() })
+            .send().await?;
+
+        if response.status() != 201 {
+            return Err(error_of(response).await);
+        }
+
+        let data = response.json::<UserResponse>().await?;
+
+        Ok(data)
+    }
+
+    /// a new token, pass it to `ScopeClient::credentials`
+    pub async fn login(&self, username: &str, password: &str) -> Result<TokenResponse, ClientError> {
+        let response = self.inner.post(format!("{}/auth/login", self.endpoint))
+            .json(&CredentialsRequest { username: username.to_string(), password: password.to_string() })
+            .send().await?;
+
+        if response.status() != 200 {
+            return Err(error_of(response).await);
+        }
+
+        let data = response.json::<TokenResponse>().await?;
+
+        Ok(data)
+    }
+
+    /// revoke the token of the credentials
+    pub async fn logout(&self) -> Result<(), ClientError> {
+        let response = self.inner.post(format!("{}/auth/logout", self.endpoint))
+            .send().await?;
+
+        if response.status() != 200 {
+            return Err(error_of(response).await);
+        }
+
+        Ok(())
+    }
+
+    /// a single-use ticket of the user for `?access_token=` of the event stream and the sync WebSocket,
+    /// which cannot send the token of the credentials
+    pub async fn ticket(&self) -> Result<TokenResponse, ClientError> {
+        let response = self.inner.post(format!("{}/auth/tickets", self.endpoint))
+            .send().await?;
+
+        if response.status() != 201 {
+            return Err(error_of(response).await);
+        }
+
+        let data = response.json::<TokenResponse>().await?;
+
+        Ok(data)
+    }
+
+    /// the user of the credentials
+    pub async fn me(&self) -> Result<UserResponse, ClientError> {
+        let response = self.inner.get(format!("{}/auth/me", self.endpoint))
+            .send().await?;
+
+        if response.status() != 200 {
+            return Err(error_of(response).await);
+        }
+
+        let data = response.json::<UserResponse>().await?;
+
+        Ok(data)
+    }
+
+    /// own a namespace nobody owns yet, the todos in it are kept
+    pub async fn claim_namespace(&self, namespace: &str) -> Result<UserResponse, ClientError> {
+        let response = self.inner.post(format!("{}/auth/namespaces", self.endpoint))
+            .json(&NamespaceRequest { namespace: namespace.to_string() })
+            .send().await?;
+
+        if response.status() != 200 {
+            return Err(error_of(response).await);
+        }
+
+        let data = response.json::<UserResponse>().await?;
+
+        Ok(data)
+    }
+
+    /// join the namespace of the invitation with its role
+    pub async fn accept_invitation(&self, token: &str) -> Result<UserResponse, ClientError> {
+        let response = self.inner.post(format!("{}/auth/invitations", self.endpoint))
+            .json(&InvitationRequest { token: token.to_string() })
+            .send().await?;
+
+        if response.status() != 200 {
+            return Err(error_of(response).await);
+        }
+
+        let data = response.json::<UserResponse>().await?;
+
+        Ok(data)
+    }
+}
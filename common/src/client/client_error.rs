@@ -0,0 +1,45 @@
+use reqwest::StatusCode;
+use thiserror::Error;
+
+use crate::model::{ErrorCode, ErrorResponse};
+
+/// error of the clients, the server side errors carry the decoded `ErrorResponse`
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error("transport error: {0}")]
+    Transport(#[from] reqwest::Error),
+    #[error("not found: {0}")]
+    NotFound(ErrorResponse),
+    #[error("conflict: {0}")]
+    Conflict(ErrorResponse),
+    #[error("unauthorized: {0}")]
+    Unauthorized(ErrorResponse),
+    #[error("validation failed: {0}")]
+    Validation(ErrorResponse),
+    #[error("server error: {0}")]
+    Server(ErrorResponse),
+}
+
+impl ClientError {
+    pub fn from_status(status: StatusCode, error: ErrorResponse) -> Self {
+        match status {
+            StatusCode::NOT_FOUND => Self::NotFound(error),
+            StatusCode::CONFLICT | StatusCode::PRECONDITION_FAILED => Self::Conflict(error),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Self::Unauthorized(error),
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => Self::Validation(error),
+            _ => Self::Server(error),
+        }
+    }
+
+    /// the `ErrorResponse` from server, `None` for transport errors
+    pub fn response(&self) -> Option<&ErrorResponse> {
+        match self {
+            Self::Transport(_) => None,
+            Self::NotFound(e) | Self::Conflict(e) | Self::Unauthorized(e) | Self::Validation(e) | Self::Server(e) => Some(e),
+        }
+    }
+
+    pub fn code(&self) -> Option<ErrorCode> {
+        self.response().map(|e| e.code)
+    }
+}
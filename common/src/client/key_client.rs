@@ -0,0 +1,62 @@
+use std::ops::Deref;
+use crate::model::{ApiKeyRequest, ApiKeyResponse, NamespaceRole};
+use super::{error_of, ClientError, ScopeClient};
+
+pub struct KeyClient(ScopeClient);
+
+impl From<ScopeClient> for KeyClient {
+    fn from(c: ScopeClient) -> Self {
+        Self(c)
+    }
+}
+
+impl Deref for KeyClient {
+    type Target = ScopeClient;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl KeyClient {
+    /// owners only, newest first
+    pub async fn get_keys(&self) -> Result<Vec<ApiKeyResponse>, ClientError> {
+        let response = self.inner.get(format!("{}/keys", self.endpoint))
+            .send().await?;
+
+        if response.status() != 200 {
+            return Err(error_of(response).await);
+        }
+
+        let data = response.json::<Vec<ApiKeyResponse>>().await?;
+
+        Ok(data)
+    }
+
+    /// owners only, pass the key to `ScopeClient::api_key`, it is not shown again
+    pub async fn create_key(&self, name: &str, role: NamespaceRole) -> Result<ApiKeyResponse, ClientError> {
+        let response = self.inner.post(format!("{}/keys", self.endpoint))
+            .json(&ApiKeyRequest { name: name.to_string(), role })
+            .send().await?;
+
+        if response.status() != 201 {
+            return Err(error_of(response).await);
+        }
+
+        let data = response.json::<ApiKeyResponse>().await?;
+
+        Ok(data)
+    }
+
+    /// owners only
+    pub async fn revoke_key(&self, id: i32) -> Result<(), ClientError> {
+        let response = self.inner.delete(format!("{}/keys/{}", self.endpoint, id))
+            .send().await?;
+
+        if response.status() != 200 {
+            return Err(error_of(response).await);
+        }
+
+        Ok(())
+    }
+}
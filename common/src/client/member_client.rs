@@ -0,0 +1,77 @@
+use std::ops::Deref;
+use crate::model::{InvitationResponse, MemberResponse, MembersResponse, NamespaceRole, RoleRequest};
+use super::{error_of, ClientError, ScopeClient};
+
+pub struct MemberClient(ScopeClient);
+
+impl From<ScopeClient> for MemberClient {
+    fn from(c: ScopeClient) -> Self {
+        Self(c)
+    }
+}
+
+impl Deref for MemberClient {
+    type Target = ScopeClient;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl MemberClient {
+    /// the members of the namespace along with the role of the caller
+    pub async fn get_members(&self) -> Result<MembersResponse, ClientError> {
+        let response = self.inner.get(format!("{}/members", self.endpoint))
+            .send().await?;
+
+        if response.status() != 200 {
+            return Err(error_of(response).await);
+        }
+
+        let data = response.json::<MembersResponse>().await?;
+
+        Ok(data)
+    }
+
+    /// owners only
+    pub async fn update_member(&self, user_id: i32, role: NamespaceRole) -> Result<MemberResponse, ClientError> {
+        let response = self.inner.patch(format!("{}/members/{}", self.endpoint, user_id))
+            .json(&RoleRequest { role })
+            .send().await?;
+
+        if response.status() != 200 {
+            return Err(error_of(response).await);
+        }
+
+        let data = response.json::<MemberResponse>().await?;
+
+        Ok(data)
+    }
+
+    /// owners only, or the member leaving
+    pub async fn remove_member(&self, user_id: i32) -> Result<(), ClientError> {
+        let response = self.inner.delete(format!("{}/members/{}", self.endpoint, user_id))
+            .send().await?;
+
+        if response.status() != 200 {
+            return Err(error_of(response).await);
+        }
+
+        Ok(())
+    }
+
+    /// owners only, pass the token to `AuthClient::accept_invitation` of the invitee
+    pub async fn create_invitation(&self, role: NamespaceRole) -> Result<InvitationResponse, ClientError> {
+        let response = self.inner.post(format!("{}/members/invitations", self.endpoint))
+            .json(&RoleRequest { role })
+            .send().await?;
+
+        if response.status() != 201 {
+            return Err(error_of(response).await);
+        }
+
+        let data = response.json::<InvitationResponse>().await?;
+
+        Ok(data)
+    }
+}
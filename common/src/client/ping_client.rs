@@ -1,6 +1,5 @@
 use std::ops::Deref;
-use anyhow::bail;
-use crate::client::ScopeClient;
+use crate::client::{error_of, ClientError, ScopeClient};
 
 pub struct PingClient(ScopeClient);
 
@@ -19,13 +18,13 @@ impl Deref for PingClient {
 }
 
 impl PingClient {
-    pub async fn ping(&self) -> anyhow::Result<String> {
-        let request = self.inner.get(&format!("{}/ping", self.endpoint));
+    pub async fn ping(&self) -> Result<String, ClientError> {
+        let request = self.inner.get(format!("{}/ping", self.endpoint));
 
         let response = request.send().await?;
 
         if response.status() != 200 {
-            bail!("server is unavailable <{}>", response.status());
+            return Err(error_of(response).await);
         }
 
         let data = response.text().await?;
@@ -35,7 +34,7 @@ impl PingClient {
 
     pub async fn health(&self) -> bool {
         self.ping().await
-            .map(|pong| pong == "pong".to_string())
+            .map(|pong| pong == "pong")
             .unwrap_or_else(|_| false)
     }
 }
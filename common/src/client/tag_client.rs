@@ -0,0 +1,75 @@
+use std::ops::Deref;
+use crate::model::{TagRequest, TagResponse};
+use super::{error_of, ClientError, ScopeClient};
+
+pub struct TagClient(ScopeClient);
+
+impl From<ScopeClient> for TagClient {
+    fn from(c: ScopeClient) -> Self {
+        Self(c)
+    }
+}
+
+impl Deref for TagClient {
+    type Target = ScopeClient;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl TagClient {
+    /// all the tags of the namespace, ordered by name
+    pub async fn get_tags(&self) -> Result<Vec<TagResponse>, ClientError> {
+        let response = self.inner.get(format!("{}/tags", self.endpoint))
+            .send().await?;
+
+        if response.status() != 200 {
+            return Err(error_of(response).await);
+        }
+
+        let data = response.json::<Vec<TagResponse>>().await?;
+
+        Ok(data)
+    }
+
+    pub async fn create_tag(&self, name: &str) -> Result<TagResponse, ClientError> {
+        let response = self.inner.post(format!("{}/tags", self.endpoint))
+            .json(&TagRequest { name: name.to_string() })
+            .send().await?;
+
+        if response.status() != 201 {
+            return Err(error_of(response).await);
+        }
+
+        let data = response.json::<TagResponse>().await?;
+
+        Ok(data)
+    }
+
+    pub async fn rename_tag(&self, id: i32, name: &str) -> Result<TagResponse, ClientError> {
+        let response = self.inner.patch(format!("{}/tags/{}", self.endpoint, id))
+            .json(&TagRequest { name: name.to_string() })
+            .send().await?;
+
+        if response.status() != 200 {
+            return Err(error_of(response).await);
+        }
+
+        let data = response.json::<TagResponse>().await?;
+
+        Ok(data)
+    }
+
+    /// the tag is removed from all of its todos
+    pub async fn delete_tag(&self, id: i32) -> Result<(), ClientError> {
+        let response = self.inner.delete(format!("{}/tags/{}", self.endpoint, id))
+            .send().await?;
+
+        if response.status() != 200 {
+            return Err(error_of(response).await);
+        }
+
+        Ok(())
+    }
+}
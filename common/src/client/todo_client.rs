@@ -1,6 +1,7 @@
 use std::ops::Deref;
-use crate::model::{CreateTodoRequest, IdsRequest, TodoResponse, TodoStatus, UpdateTodoRequest};
-use super::ScopeClient;
+use chrono::{DateTime, Utc};
+use crate::model::{BatchOperation, BatchRequest, BatchResponse, CreateTodoRequest, ExportFormat, FormatQuery, GetChangesQuery, GetTodosQuery, IdsRequest, ImportRequest, ImportResponse, ImportTodo, MoveTodoRequest, TodoChanges, TodoPage, TodoResponse, TodoStatus, UpdateTodoRequest};
+use super::{error_of, ClientError, ScopeClient};
 
 pub struct TodoClient(ScopeClient);
 
@@ -19,46 +20,126 @@ impl Deref for TodoClient {
 }
 
 impl TodoClient {
-    pub async fn get_todos(&self, status: Option<TodoStatus>) -> anyhow::Result<Vec<TodoResponse>> {
-        let mut request = self.inner.get(&format!("{}/todos", self.endpoint));
+    /// all the todos, fetched page by page
+    pub async fn get_todos(&self, status: Option<TodoStatus>) -> Result<Vec<TodoResponse>, ClientError> {
+        self.query_todos(GetTodosQuery { status, ..Default::default() }).await
+    }
+
+    /// all the todos matching the `query`, fetched page by page
+    pub async fn query_todos(&self, query: GetTodosQuery) -> Result<Vec<TodoResponse>, ClientError> {
+        let mut pages = self.pages(query);
 
-        if let Some(status) = status {
-            request = request.query(&[("status", &status)]);
+        let mut data = vec![];
+        while let Some(page) = pages.next().await {
+            data.extend(page?.items);
         }
 
-        let response = request.send().await?;
+        Ok(data)
+    }
+
+    pub async fn get_todos_page(&self, query: &GetTodosQuery) -> Result<TodoPage, ClientError> {
+        let response = self.inner.get(format!("{}/todos", self.endpoint))
+            .query(query)
+            .send().await?;
 
         if response.status() != 200 {
-            println!("{:?}", response.bytes().await.unwrap());
-            unreachable!();
+            return Err(error_of(response).await);
         }
 
-        let data = response.json::<Vec<TodoResponse>>().await?;
+        let data = response.json::<TodoPage>().await?;
+
+        Ok(data)
+    }
+
+    /// iterate the pages from `query.cursor` until the last page
+    pub fn pages(&self, query: GetTodosQuery) -> TodoPages<'_> {
+        TodoPages { client: self, query, done: false }
+    }
+
+    /// the changes since the `until` of the previous ones, every todo without `since`
+    pub async fn get_changes(&self, since: Option<DateTime<Utc>>) -> Result<TodoChanges, ClientError> {
+        let response = self.inner.get(format!("{}/todos/changes", self.endpoint))
+            .query(&GetChangesQuery { since })
+            .send().await?;
+
+        if response.status() != 200 {
+            return Err(error_of(response).await);
+        }
+
+        let data = response.json::<TodoChanges>().await?;
 
         Ok(data)
     }
 
-    pub async fn get_todo_by_id(&self, id: i32) -> anyhow::Result<Option<TodoResponse>> {
-        let response = self.inner.get(&format!("{}/todos/{}", self.endpoint, id))
+    pub async fn get_todo_by_id(&self, id: i32) -> Result<Option<TodoResponse>, ClientError> {
+        let response = self.inner.get(format!("{}/todos/{}", self.endpoint, id))
             .send().await?;
 
         if response.status().as_u16() == 404 {
             return Ok(None);
         }
 
+        if response.status() != 200 {
+            return Err(error_of(response).await);
+        }
+
         let data = response.json::<TodoResponse>().await?;
 
         Ok(Some(data))
     }
 
-    pub async fn create_todo(&self, body: CreateTodoRequest) -> anyhow::Result<TodoResponse> {
-        let response = self.inner.post(&format!("{}/todos", self.endpoint))
+    /// the direct subtasks of the todo in the manual order
+    pub async fn get_children(&self, id: i32) -> Result<Vec<TodoResponse>, ClientError> {
+        let response = self.inner.get(format!("{}/todos/{}/children", self.endpoint, id))
+            .send().await?;
+
+        if response.status() != 200 {
+            return Err(error_of(response).await);
+        }
+
+        let data = response.json::<Vec<TodoResponse>>().await?;
+
+        Ok(data)
+    }
+
+    pub async fn create_todo(&self, body: CreateTodoRequest) -> Result<TodoResponse, ClientError> {
+        let response = self.inner.post(format!("{}/todos", self.endpoint))
             .json(&body)
             .send().await?;
 
         if response.status() != 201 {
-            println!("{:?}", response.bytes().await.unwrap());
-            unreachable!();
+            return Err(error_of(response).await);
+        }
+
+        let data = response.json::<TodoResponse>().await?;
+
+        Ok(data)
+    }
+
+    pub async fn update_todo(&self, id: i32, body: UpdateTodoRequest) -> Result<TodoResponse, ClientError> {
+        let response = self.inner.patch(format!("{}/todos/{}", self.endpoint, id))
+            .json(&body)
+            .send().await?;
+
+        if response.status() != 200 {
+            return Err(error_of(response).await);
+        }
+
+        let data = response.json::<TodoResponse>().await?;
+
+        Ok(data)
+    }
+
+    /// update the `version` of the todo only, sent as `If-Match`,
+    /// `ErrorCode::VersionConflict` when it has been changed since
+    pub async fn update_todo_if_match(&self, id: i32, version: i32, body: UpdateTodoRequest) -> Result<TodoResponse, ClientError> {
+        let response = self.inner.patch(format!("{}/todos/{}", self.endpoint, id))
+            .header("If-Match", format!("\"{}\"", version))
+            .json(&body)
+            .send().await?;
+
+        if response.status() != 200 {
+            return Err(error_of(response).await);
         }
 
         let data = response.json::<TodoResponse>().await?;
@@ -66,14 +147,14 @@ impl TodoClient {
         Ok(data)
     }
 
-    pub async fn update_todo(&self, id: i32, body: UpdateTodoRequest) -> anyhow::Result<TodoResponse> {
-        let response = self.inner.patch(&format!("{}/todos/{}", self.endpoint, id))
+    /// place the todo before or after another one in the `TodoSort::Manual` order
+    pub async fn move_todo(&self, id: i32, body: MoveTodoRequest) -> Result<TodoResponse, ClientError> {
+        let response = self.inner.post(format!("{}/todos/{}/move", self.endpoint, id))
             .json(&body)
             .send().await?;
 
         if response.status() != 200 {
-            println!("{:?}", response.bytes().await.unwrap());
-            unreachable!();
+            return Err(error_of(response).await);
         }
 
         let data = response.json::<TodoResponse>().await?;
@@ -81,14 +162,81 @@ impl TodoClient {
         Ok(data)
     }
 
-    pub async fn clear_todos(&self, ids: Vec<i32>) -> anyhow::Result<()> {
-        let _ = self.inner.delete(&format!("{}/todos", self.endpoint))
+    /// apply the operations in one transaction, a failed operation does not stop the others
+    pub async fn batch(&self, operations: Vec<BatchOperation>) -> Result<BatchResponse, ClientError> {
+        let response = self.inner.post(format!("{}/todos/batch", self.endpoint))
+            .json(&BatchRequest { operations })
+            .send().await?;
+
+        if response.status() != 200 {
+            return Err(error_of(response).await);
+        }
+
+        let data = response.json::<BatchResponse>().await?;
+
+        Ok(data)
+    }
+
+    /// import the todos of another source, e.g. the local ones of the frontend
+    pub async fn import_todos(&self, todos: Vec<ImportTodo>) -> Result<ImportResponse, ClientError> {
+        let response = self.inner.post(format!("{}/todos/import", self.endpoint))
+            .json(&ImportRequest { todos })
+            .send().await?;
+
+        if response.status() != 200 {
+            return Err(error_of(response).await);
+        }
+
+        let data = response.json::<ImportResponse>().await?;
+
+        Ok(data)
+    }
+
+    /// the document of the whole namespace in the format
+    pub async fn export_todos(&self, format: ExportFormat) -> Result<String, ClientError> {
+        let response = self.inner.get(format!("{}/todos/export", self.endpoint))
+            .query(&FormatQuery { format })
+            .send().await?;
+
+        if response.status() != 200 {
+            return Err(error_of(response).await);
+        }
+
+        let data = response.text().await?;
+
+        Ok(data)
+    }
+
+    /// import the todos of a document of `export_todos`
+    pub async fn import_document(&self, format: ExportFormat, document: String) -> Result<ImportResponse, ClientError> {
+        let response = self.inner.post(format!("{}/todos/import", self.endpoint))
+            .query(&FormatQuery { format })
+            .header("content-type", format.content_type())
+            .body(document)
+            .send().await?;
+
+        if response.status() != 200 {
+            return Err(error_of(response).await);
+        }
+
+        let data = response.json::<ImportResponse>().await?;
+
+        Ok(data)
+    }
+
+    pub async fn clear_todos(&self, ids: Vec<i32>) -> Result<(), ClientError> {
+        let response = self.inner.delete(format!("{}/todos", self.endpoint))
             .json(&IdsRequest { ids })
             .send().await?;
+
+        if response.status() != 200 {
+            return Err(error_of(response).await);
+        }
+
         Ok(())
     }
 
-    pub async fn assert_eq(&self, id: i32, actual: &TodoResponse) -> anyhow::Result<()> {
+    pub async fn assert_eq(&self, id: i32, actual: &TodoResponse) -> Result<(), ClientError> {
         let created = self.get_todo_by_id(id).await?;
         assert!(created.is_some());
         let created = created.unwrap();
@@ -98,3 +246,25 @@ impl TodoClient {
         Ok(())
     }
 }
+
+pub struct TodoPages<'a> {
+    client: &'a TodoClient,
+    query: GetTodosQuery,
+    done: bool,
+}
+
+impl TodoPages<'_> {
+    pub async fn next(&mut self) -> Option<Result<TodoPage, ClientError>> {
+        if self.done {
+            return None;
+        }
+
+        let page = self.client.get_todos_page(&self.query).await;
+        match &page {
+            Ok(TodoPage { next_cursor: Some(cursor), .. }) => self.query.cursor = Some(cursor.clone()),
+            _ => self.done = true,
+        }
+
+        Some(page)
+    }
+}
@@ -1,7 +1,7 @@
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 use anyhow::{bail, Error};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize)]
@@ -38,17 +38,879 @@ impl FromStr for TodoStatus {
     }
 }
 
-#[derive(Deserialize, Serialize)]
+/// stored as a number, so the higher priority sorts first
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum TodoPriority {
+    Low = 0,
+    #[default]
+    Normal = 1,
+    High = 2,
+}
+
+impl Display for TodoPriority {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl TryFrom<i16> for TodoPriority {
+    type Error = Error;
+
+    fn try_from(value: i16) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(TodoPriority::Low),
+            1 => Ok(TodoPriority::Normal),
+            2 => Ok(TodoPriority::High),
+            _ => bail!("invalid todo priority [{}] from db", value)
+        }
+    }
+}
+
+/// order of `GET /todos`, ignored by the search of `q` which orders by rank
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum TodoSort {
+    /// newest first
+    #[default]
+    Newest,
+    /// higher priority first, then newest first
+    Priority,
+    /// as arranged by `POST /todos/{id}/move`, new todos are on top
+    Manual,
+}
+
+/// query of `GET /todos`, paged by the `cursor` of `TodoPage`
+#[derive(Deserialize, Serialize, Default, Debug, PartialEq, Clone)]
+pub struct GetTodosQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<TodoStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
+    /// full-text search on content, hits are ordered by rank
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub q: Option<String>,
+    /// todos due strictly before, e.g. `2024-01-01T00:00:00Z`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_before: Option<DateTime<Utc>>,
+    /// `true` for the overdue todos only, `false` to exclude them, see `TodoResponse::is_overdue`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overdue: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<TodoSort>,
+    /// todos labeled with the tag name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+/// timestamps are RFC 3339 with any offset, e.g. `2024-01-01T08:00:00+08:00`, and stored in UTC
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
 pub struct CreateTodoRequest {
     pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_at: Option<DateTime<Utc>>,
+    /// `normal` by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<TodoPriority>,
+    /// tag names, the missing tags are created
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// create as a subtask of the todo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<i32>,
 }
 
-#[derive(Deserialize, Serialize, Clone)]
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
 pub struct UpdateTodoRequest {
     pub content: Option<String>,
     pub status: Option<TodoStatus>,
+    /// `null` clears the due date, absent keeps it
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "nullable")]
+    pub due_at: Option<Option<DateTime<Utc>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<TodoPriority>,
+    /// replaces all the tags, the missing tags are created
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    /// marking `done`, `archived` or `deleted` moves the open subtasks along, instead of failing with `OpenSubtasks`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cascade: bool,
+}
+
+/// body of `POST /todos/{id}/move`, expects exactly one of `before` or `after`,
+/// the id of the todo to be placed next to in the manual order
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone)]
+pub struct MoveTodoRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<i32>,
+}
+
+/// one operation of `POST /todos/batch`, tagged by `op`
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum BatchOperation {
+    Create(CreateTodoRequest),
+    Update {
+        id: i32,
+        #[serde(flatten)]
+        body: UpdateTodoRequest,
+    },
+    Status {
+        id: i32,
+        status: TodoStatus,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        cascade: bool,
+    },
+    /// hard-delete a `Deleted` todo, the subtasks are left as top-level todos, like `DELETE /todos`,
+    /// any other status is moved to `Deleted` like `DELETE /todos/{id}`
+    Delete {
+        id: i32,
+    },
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct BatchRequest {
+    pub operations: Vec<BatchOperation>,
+}
+
+/// the outcome of a `BatchOperation`, the deleted todo for a `delete`
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchResult {
+    Ok(TodoResponse),
+    Error(ErrorResponse),
+}
+
+/// the results in the order of `BatchRequest.operations`
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct BatchResponse {
+    pub results: Vec<BatchResult>,
+}
+
+/// a todo of `POST /todos/import`, the fields of a `TodoResponse` which are carried over
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct ImportTodo {
+    /// the id in the source, referred to by the `parent_id` of its subtasks
+    pub id: i32,
+    pub content: String,
+    pub status: TodoStatus,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub priority: TodoPriority,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// the source id of the parent, which is imported along
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<i32>,
+}
+
+impl From<TodoResponse> for ImportTodo {
+    fn from(todo: TodoResponse) -> Self {
+        Self {
+            id: todo.id,
+            content: todo.content,
+            status: todo.status,
+            created_at: todo.created_at,
+            due_at: todo.due_at,
+            priority: todo.priority,
+            tags: todo.tags,
+            parent_id: todo.parent_id,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ImportRequest {
+    pub todos: Vec<ImportTodo>,
+}
+
+/// the outcome of an `ImportTodo`
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportResult {
+    Created(TodoResponse),
+    /// the todo with the same content and `created_at` already in the namespace
+    Duplicate(TodoResponse),
+    Failed(ErrorResponse),
+}
+
+/// the results in the order of `ImportRequest.todos`
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct ImportResponse {
+    pub results: Vec<ImportResult>,
+}
+
+impl ImportResponse {
+    /// the number of created, duplicate and failed todos
+    pub fn counts(&self) -> (usize, usize, usize) {
+        self.results.iter().fold((0, 0, 0), |(created, duplicate, failed), it| match it {
+            ImportResult::Created(_) => (created + 1, duplicate, failed),
+            ImportResult::Duplicate(_) => (created, duplicate + 1, failed),
+            ImportResult::Failed(_) => (created, duplicate, failed + 1),
+        })
+    }
+}
+
+/// the documents of `GET /todos/export` and `POST /todos/import`, written todo by todo
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// an `ImportRequest` of `TodoResponse`s
+    #[default]
+    Json,
+    /// a row per todo under a header, the tags separated by spaces
+    Csv,
+    /// a task list, the fields which do not show go in a trailing `<!-- {json} -->`
+    Md,
+    /// a todo.txt line per todo, the tags are `+project`s and the ones starting with `@` contexts
+    Txt,
+    /// an RFC 5545 calendar of a `VTODO` per todo, export only
+    Ics,
+}
+
+/// query of `GET /todos/export` and `POST /todos/import`
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone)]
+pub struct FormatQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+const CSV_HEADER: [&str; 10] = ["id", "parent_id", "content", "status", "priority", "tags", "due_at", "created_at", "updated_at", "version"];
+
+/// a row of `ExportFormat::Csv`, in the order of `CSV_HEADER`
+#[derive(Deserialize, Serialize)]
+struct CsvRow {
+    id: i32,
+    parent_id: Option<i32>,
+    content: String,
+    status: TodoStatus,
+    priority: TodoPriority,
+    tags: String,
+    due_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    #[serde(default)]
+    updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    version: Option<i32>,
+}
+
+/// the trailing comment of an `ExportFormat::Md` task
+#[derive(Deserialize, Serialize)]
+struct MarkdownMeta {
+    id: i32,
+    status: TodoStatus,
+    #[serde(default)]
+    priority: TodoPriority,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    parent_id: Option<i32>,
+    created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    updated_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<i32>,
+}
+
+const MD_META_START: &str = " <!-- ";
+const MD_META_END: &str = " -->";
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "application/json",
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Md => "text/markdown; charset=utf-8",
+            ExportFormat::Txt => "text/plain; charset=utf-8",
+            ExportFormat::Ics => "text/calendar; charset=utf-8",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Md => "md",
+            ExportFormat::Txt => "txt",
+            ExportFormat::Ics => "ics",
+        }
+    }
+
+    /// the start of a document of the namespace
+    pub fn begin(&self, namespace: &str) -> String {
+        match self {
+            ExportFormat::Json => r#"{"todos":["#.to_string(),
+            ExportFormat::Csv => csv_line(&CSV_HEADER),
+            ExportFormat::Md => format!("# {}\n\n", namespace),
+            ExportFormat::Txt => String::new(),
+            ExportFormat::Ics => [
+                ics_line("BEGIN", "VCALENDAR"),
+                ics_line("VERSION", "2.0"),
+                ics_line("PRODID", "-//rust-fullstack-todo//todos//EN"),
+                ics_line("X-WR-CALNAME", &ics_text(namespace)),
+            ].concat(),
+        }
+    }
+
+    /// the todo at `index` of the document
+    pub fn todo(&self, todo: &TodoResponse, index: usize) -> String {
+        match self {
+            ExportFormat::Json => {
+                let json = serde_json::to_string(todo).expect("a todo is serializable");
+                if index == 0 { json } else { format!(",{}", json) }
+            }
+            ExportFormat::Csv => {
+                let mut writer = csv::WriterBuilder::new().has_headers(false).from_writer(vec![]);
+                writer.serialize(CsvRow {
+                    id: todo.id,
+                    parent_id: todo.parent_id,
+                    content: todo.content.clone(),
+                    status: todo.status,
+                    priority: todo.priority,
+                    tags: todo.tags.join(" "),
+                    due_at: todo.due_at,
+                    created_at: todo.created_at,
+                    updated_at: Some(todo.updated_at),
+                    version: Some(todo.version),
+                }).expect("a todo is serializable");
+                String::from_utf8(writer.into_inner().expect("written in memory")).expect("utf-8 fields")
+            }
+            ExportFormat::Md => {
+                let meta = serde_json::to_string(&MarkdownMeta {
+                    id: todo.id,
+                    status: todo.status,
+                    priority: todo.priority,
+                    tags: todo.tags.clone(),
+                    due_at: todo.due_at,
+                    parent_id: todo.parent_id,
+                    created_at: todo.created_at,
+                    updated_at: Some(todo.updated_at),
+                    version: Some(todo.version),
+                }).expect("a todo is serializable");
+                let checked = if todo.status == TodoStatus::Done { "x" } else { " " };
+                let content = todo.content.replace('\\', "\\\\").replace('\n', "\\n");
+                format!("- [{}] {}{}{}{}\n", checked, content, MD_META_START, meta, MD_META_END)
+            }
+            ExportFormat::Txt => txt_line(todo),
+            ExportFormat::Ics => vtodo(todo),
+        }
+    }
+
+    /// the end of a document
+    pub fn end(&self) -> String {
+        match self {
+            ExportFormat::Json => "]}".to_string(),
+            ExportFormat::Csv | ExportFormat::Md | ExportFormat::Txt => String::new(),
+            ExportFormat::Ics => ics_line("END", "VCALENDAR"),
+        }
+    }
+
+    /// the todos of a document, a Markdown task without the comment is a new `todo` or `done` one
+    pub fn parse(&self, document: &str) -> anyhow::Result<Vec<ImportTodo>> {
+        match self {
+            ExportFormat::Json => Ok(serde_json::from_str::<ImportRequest>(document)?.todos),
+            ExportFormat::Csv => csv::Reader::from_reader(document.as_bytes())
+                .deserialize::<CsvRow>()
+                .map(|row| {
+                    let row = row?;
+                    Ok(ImportTodo {
+                        id: row.id,
+                        content: row.content,
+                        status: row.status,
+                        created_at: row.created_at,
+                        due_at: row.due_at,
+                        priority: row.priority,
+                        tags: row.tags.split_whitespace().map(str::to_string).collect(),
+                        parent_id: row.parent_id,
+                    })
+                })
+                .collect(),
+            ExportFormat::Md => document.lines()
+                .map(str::trim_start)
+                .filter_map(|line| line.strip_prefix("- [").or_else(|| line.strip_prefix("* [")))
+                .enumerate()
+                .filter_map(|(index, task)| parse_task(index, task))
+                .map(Ok)
+                .collect(),
+            ExportFormat::Txt => document.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .enumerate()
+                .map(|(index, line)| parse_txt(index, line))
+                .collect(),
+            ExportFormat::Ics => bail!("iCalendar is export only"),
+        }
+    }
+}
+
+fn csv_line(fields: &[&str]) -> String {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer.write_record(fields).expect("written in memory");
+    String::from_utf8(writer.into_inner().expect("written in memory")).expect("utf-8 fields")
+}
+
+/// a task after its `- [`, `None` when it is not one.
+/// a trailing comment which is not the meta, as in a hand-written task, is part of the content
+fn parse_task(index: usize, task: &str) -> Option<ImportTodo> {
+    let (done, rest) = match task.split_at_checked(3) {
+        Some(("x] " | "X] ", rest)) => (true, rest),
+        Some((" ] ", rest)) => (false, rest),
+        _ => return None,
+    };
+
+    let meta = rest.rfind(MD_META_START)
+        .filter(|_| rest.ends_with(MD_META_END))
+        .and_then(|start| {
+            let json = rest.get(start + MD_META_START.len()..rest.len() - MD_META_END.len())?;
+            serde_json::from_str::<MarkdownMeta>(json).ok().map(|meta| (start, meta))
+        });
+    let (content, meta) = match meta {
+        Some((start, meta)) => (&rest[..start], Some(meta)),
+        None => (rest, None),
+    };
+    let content = unescape(content);
+
+    Some(match meta {
+        Some(meta) => ImportTodo {
+            id: meta.id,
+            content,
+            status: meta.status,
+            created_at: meta.created_at,
+            due_at: meta.due_at,
+            priority: meta.priority,
+            tags: meta.tags,
+            parent_id: meta.parent_id,
+        },
+        None => ImportTodo {
+            id: index as i32 + 1,
+            content,
+            status: if done { TodoStatus::Done } else { TodoStatus::Todo },
+            created_at: Utc::now(),
+            due_at: None,
+            priority: Default::default(),
+            tags: vec![],
+            parent_id: None,
+        },
+    })
+}
+
+const TXT_DATE: &str = "%Y-%m-%d";
+
+/// `x (A) completed created content +project @context key:value`,
+/// `(A)` is `High` and `(C)` `Low`, a done todo keeps its priority as `pri:`.
+/// The words of the content which read as metadata are escaped with a `\`, see `is_txt_meta`.
+/// The dates and `due:` are days in UTC, `id:` and `parent:` keep the subtasks
+fn txt_line(todo: &TodoResponse) -> String {
+    let priority = match todo.priority {
+        TodoPriority::High => Some('A'),
+        TodoPriority::Normal => None,
+        TodoPriority::Low => Some('C'),
+    };
+
+    let mut fields = vec![];
+    match todo.status {
+        TodoStatus::Done => {
+            fields.push("x".to_string());
+            fields.push(todo.updated_at.format(TXT_DATE).to_string());
+        }
+        _ => fields.extend(priority.map(|it| format!("({})", it))),
+    }
+    fields.push(todo.created_at.format(TXT_DATE).to_string());
+    let content = todo.content.replace('\\', "\\\\").replace('\n', "\\n");
+    fields.extend(content.split_whitespace().map(|it| if is_txt_meta(it) { format!("\\{}", it) } else { it.to_string() }));
+    fields.extend(todo.tags.iter().map(|it| if it.starts_with('@') { it.clone() } else { format!("+{}", it) }));
+
+    if let Some(due_at) = todo.due_at {
+        fields.push(format!("due:{}", due_at.format(TXT_DATE)));
+    }
+    fields.push(format!("id:{}", todo.id));
+    if let Some(parent_id) = todo.parent_id {
+        fields.push(format!("parent:{}", parent_id));
+    }
+    if let (TodoStatus::Done, Some(priority)) = (todo.status, priority) {
+        fields.push(format!("pri:{}", priority));
+    }
+    if matches!(todo.status, TodoStatus::Archived | TodoStatus::Deleted) {
+        fields.push(format!("status:{}", todo.status.to_string().to_lowercase()));
+    }
+
+    fields.join(" ") + "\n"
+}
+
+/// a word which todo.txt reads as `key:value`, `+project` or `@context`, a `\` before it keeps it in the content
+fn is_txt_meta(word: &str) -> bool {
+    (word.len() > 1 && (word.starts_with('+') || word.starts_with('@'))) ||
+        word.split_once(':').is_some_and(|(key, _)| !key.is_empty())
+}
+
+/// a todo.txt line, the words of the content starting with `+` or `@` are tags.
+/// `id` is the line number without `id:`, `created_at` now without a creation date
+fn parse_txt(index: usize, line: &str) -> anyhow::Result<ImportTodo> {
+    let mut words = line.split_whitespace().peekable();
+    let date = |word: Option<&&str>| word.and_then(|it| NaiveDate::parse_from_str(it, TXT_DATE).ok());
+
+    let done = words.next_if_eq(&"x").is_some();
+    let mut priority = words.next_if(|it| it.len() == 3 && it.starts_with('(') && it.ends_with(')'))
+        .map(|it| priority_of(&it[1..2]))
+        .transpose()?;
+    if done && date(words.peek()).is_some() {
+        // the completion date, `updated_at` is the time of the import
+        words.next();
+    }
+    let created_at = date(words.peek()).map(|it| {
+        words.next();
+        it.and_hms_opt(0, 0, 0).expect("midnight").and_utc()
+    });
+
+    let mut todo = ImportTodo {
+        id: index as i32 + 1,
+        content: String::new(),
+        status: if done { TodoStatus::Done } else { TodoStatus::Todo },
+        created_at: created_at.unwrap_or_else(Utc::now),
+        due_at: None,
+        priority: Default::default(),
+        tags: vec![],
+        parent_id: None,
+    };
+    let mut content = vec![];
+    for word in words {
+        if let Some(word) = word.strip_prefix('\\').filter(|it| is_txt_meta(it)) {
+            content.push(word);
+            continue;
+        }
+        match word.split_once(':') {
+            Some(("due", value)) => todo.due_at = Some(NaiveDate::parse_from_str(value, TXT_DATE)?
+                .and_hms_opt(0, 0, 0).expect("midnight").and_utc()),
+            Some(("id", value)) => todo.id = value.parse()?,
+            Some(("parent", value)) => todo.parent_id = Some(value.parse()?),
+            Some(("pri", value)) => priority = Some(priority_of(value)?),
+            Some(("status", value)) => todo.status = serde_json::from_value(serde_json::Value::from(value))?,
+            _ => match word.strip_prefix('+') {
+                Some(project) if !project.is_empty() => todo.tags.push(project.to_string()),
+                _ if word.len() > 1 && word.starts_with('@') => todo.tags.push(word.to_string()),
+                _ => content.push(word),
+            },
+        }
+    }
+    todo.content = unescape(&content.join(" "));
+    todo.priority = priority.unwrap_or_default();
+
+    Ok(todo)
+}
+
+/// `A` is `High`, `B` `Normal` and the rest down to `Z` `Low`
+fn priority_of(letter: &str) -> anyhow::Result<TodoPriority> {
+    match letter {
+        "A" => Ok(TodoPriority::High),
+        "B" => Ok(TodoPriority::Normal),
+        _ if letter.len() == 1 && letter.chars().all(|it| it.is_ascii_uppercase()) => Ok(TodoPriority::Low),
+        _ => bail!("invalid todo.txt priority [{}]", letter),
+    }
+}
+
+const ICS_DATE_TIME: &str = "%Y%m%dT%H%M%SZ";
+
+/// the todo as a `VTODO`, its `UID` is `{namespace}/{id}@rust-fullstack-todo`.
+/// `Archived` is `CANCELLED` like `Deleted`, and `SEQUENCE` counts the writes from 0
+fn vtodo(todo: &TodoResponse) -> String {
+    let uid = |id: i32| ics_text(&format!("{}/{}@rust-fullstack-todo", todo.namespace, id));
+    let status = match todo.status {
+        TodoStatus::Todo => "NEEDS-ACTION",
+        TodoStatus::Done => "COMPLETED",
+        TodoStatus::Archived | TodoStatus::Deleted => "CANCELLED",
+    };
+    let priority = match todo.priority {
+        TodoPriority::High => 1,
+        TodoPriority::Normal => 5,
+        TodoPriority::Low => 9,
+    };
+
+    let mut lines = vec![
+        ics_line("BEGIN", "VTODO"),
+        ics_line("UID", &uid(todo.id)),
+        ics_line("DTSTAMP", &todo.updated_at.format(ICS_DATE_TIME).to_string()),
+        ics_line("CREATED", &todo.created_at.format(ICS_DATE_TIME).to_string()),
+        ics_line("LAST-MODIFIED", &todo.updated_at.format(ICS_DATE_TIME).to_string()),
+        ics_line("SEQUENCE", &(todo.version - 1).max(0).to_string()),
+        ics_line("SUMMARY", &ics_text(&todo.content)),
+        ics_line("STATUS", status),
+        ics_line("PRIORITY", &priority.to_string()),
+    ];
+    if todo.status == TodoStatus::Done {
+        lines.push(ics_line("COMPLETED", &todo.updated_at.format(ICS_DATE_TIME).to_string()));
+    }
+    if let Some(due_at) = todo.due_at {
+        lines.push(ics_line("DUE", &due_at.format(ICS_DATE_TIME).to_string()));
+    }
+    if !todo.tags.is_empty() {
+        lines.push(ics_line("CATEGORIES", &todo.tags.iter().map(|it| ics_text(it)).collect::<Vec<String>>().join(",")));
+    }
+    if let Some(parent_id) = todo.parent_id {
+        lines.push(ics_line("RELATED-TO", &uid(parent_id)));
+    }
+    lines.push(ics_line("END", "VTODO"));
+
+    lines.concat()
+}
+
+/// a content line ending with CRLF, folded into lines of 75 octets at most
+fn ics_line(name: &str, value: &str) -> String {
+    let mut line = String::with_capacity(name.len() + value.len() + 8);
+    let mut octets = 0;
+    for c in name.chars().chain([':']).chain(value.chars()) {
+        if octets + c.len_utf8() > 75 {
+            line.push_str("\r\n ");
+            octets = 1;
+        }
+        line.push(c);
+        octets += c.len_utf8();
+    }
+    line.push_str("\r\n");
+    line
+}
+
+/// a TEXT value, escaping `\`, `;`, `,` and line breaks
+fn ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// the content of a task, `\n` is a line break and `\\` a backslash
+fn unescape(content: &str) -> String {
+    let mut unescaped = String::with_capacity(content.len());
+    let mut chars = content.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                unescaped.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                unescaped.push('\\');
+                chars.next();
+            }
+            _ => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+/// tells an explicit `null` (`Some(None)`) apart from an absent field (`None`)
+mod nullable {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<T: Serialize, S: Serializer>(value: &Option<Option<T>>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => value.serialize(serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Option<T>>, D::Error> {
+        Option::<T>::deserialize(deserializer).map(Some)
+    }
+}
+
+/// body of `POST /tags` and `PATCH /tags/{id}`
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone)]
+pub struct TagRequest {
+    pub name: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct TagResponse {
+    pub namespace: String,
+    pub id: i32,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// body of `POST /auth/register` and `POST /auth/login`
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone)]
+pub struct CredentialsRequest {
+    pub username: String,
+    pub password: String,
+}
+
+/// a bearer token of `POST /auth/login`, sent as `Authorization: Bearer {token}`
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct TokenResponse {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// body of `POST /auth/namespaces`
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone)]
+pub struct NamespaceRequest {
+    pub namespace: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct UserResponse {
+    pub id: i32,
+    pub username: String,
+    /// the namespaces the user is a member of, `@{username}` first
+    pub namespaces: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// what a member may do in an owned namespace, stored as a number so the greater role allows more
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum NamespaceRole {
+    /// read the todos and tags
+    Viewer = 0,
+    /// read and write them, as anyone may in a namespace without owners
+    #[default]
+    Editor = 1,
+    /// manage the members and invitations too
+    Owner = 2,
+}
+
+impl Display for NamespaceRole {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl TryFrom<i16> for NamespaceRole {
+    type Error = Error;
+
+    fn try_from(value: i16) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(NamespaceRole::Viewer),
+            1 => Ok(NamespaceRole::Editor),
+            2 => Ok(NamespaceRole::Owner),
+            _ => bail!("invalid namespace role [{}] from db", value)
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct MemberResponse {
+    pub user_id: i32,
+    pub username: String,
+    pub role: NamespaceRole,
+    pub created_at: DateTime<Utc>,
+}
+
+/// response of `GET /members`
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct MembersResponse {
+    pub namespace: String,
+    /// of the caller, `Editor` for anyone in a namespace without owners
+    pub role: NamespaceRole,
+    /// owners first, no one in a namespace without owners
+    pub members: Vec<MemberResponse>,
+}
+
+/// body of `PATCH /members/{user_id}` and `POST /members/invitations`
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone)]
+pub struct RoleRequest {
+    pub role: NamespaceRole,
+}
+
+/// a single-use token to join the namespace with the role, see `POST /auth/invitations`
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct InvitationResponse {
+    pub token: String,
+    pub namespace: String,
+    pub role: NamespaceRole,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// body of `POST /auth/invitations`
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone)]
+pub struct InvitationRequest {
+    pub token: String,
+}
+
+/// body of `POST /keys`
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq, Clone)]
+pub struct ApiKeyRequest {
+    pub name: String,
+    /// `Viewer` or `Editor`, the key acts with no more than the role of its creator
+    #[serde(default)]
+    pub role: NamespaceRole,
+}
+
+/// an API key of the namespace, see `ScopeClient::api_key`
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct ApiKeyResponse {
+    pub id: i32,
+    pub namespace: String,
+    pub name: String,
+    pub role: NamespaceRole,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// the secret, only in the response of `POST /keys`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+}
+
+/// stable error codes of the api, see `ErrorResponse`
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ErrorCode {
+    TodoNotFound,
+    TagNotFound,
+    TagAlreadyExists,
+    InvalidStatusTransition,
+    /// a parent marked `Done`, `Archived` or `Deleted` while some of its subtasks are still open
+    OpenSubtasks,
+    /// a subtask reopened or added under a parent which is no longer `Todo`
+    ClosedParent,
+    /// the todo has been changed since the version of `If-Match`
+    VersionConflict,
+    /// missing or invalid credentials
+    Unauthorized,
+    /// the namespace is owned by others, or the role of the caller is not enough
+    Forbidden,
+    UserAlreadyExists,
+    NamespaceAlreadyOwned,
+    /// a namespace holding todos cannot be claimed, unless `[auth] claim_used`
+    NamespaceNotEmpty,
+    MemberNotFound,
+    /// unknown, used or expired
+    InvitationNotFound,
+    /// an owned namespace keeps at least one owner
+    LastOwner,
+    ApiKeyNotFound,
+    ValidationFailed,
+    DbUnavailable,
+    InternalError,
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct ErrorResponse {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl Display for ErrorResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for ErrorResponse {}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct TodoResponse {
     pub namespace: String,
@@ -57,4 +919,102 @@ pub struct TodoResponse {
     pub status: TodoStatus,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub due_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub priority: TodoPriority,
+    /// tag names in alphabetical order
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// the todo this one is a subtask of
+    #[serde(default)]
+    pub parent_id: Option<i32>,
+    /// counts the writes, sent back as `If-Match` to update this version only
+    #[serde(default)]
+    pub version: i32,
+    /// search rank, only for the hits of `GetTodosQuery.q`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rank: Option<f32>,
+    /// content with the matched terms wrapped in `<mark></mark>`, only for the hits of `GetTodosQuery.q`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+}
+
+impl TodoResponse {
+    /// a `Todo` passed its due date, done or archived ones are never overdue
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        is_overdue(self.status, self.due_at, now)
+    }
+}
+
+pub fn is_overdue(status: TodoStatus, due_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    status == TodoStatus::Todo && due_at.is_some_and(|due_at| due_at < now)
+}
+
+pub const HIGHLIGHT_START: &str = "<mark>";
+pub const HIGHLIGHT_STOP: &str = "</mark>";
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct TodoPage {
+    pub items: Vec<TodoResponse>,
+    /// pass as `cursor` to fetch the next page, `None` for the last page
+    pub next_cursor: Option<String>,
+}
+
+/// query of `GET /todos/changes`
+#[derive(Deserialize, Serialize, Default, Debug, PartialEq, Clone)]
+pub struct GetChangesQuery {
+    /// the `until` of the previous changes, all the todos without it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<DateTime<Utc>>,
+}
+
+/// the todos written and the ids hard-deleted since `GetChangesQuery.since`
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct TodoChanges {
+    pub todos: Vec<TodoResponse>,
+    pub deleted: Vec<i32>,
+    /// pass as `since` to fetch the next changes
+    pub until: DateTime<Utc>,
+    /// `todos` are all of them, the ones missing have been deleted
+    #[serde(default)]
+    pub full: bool,
+}
+
+/// `data` of a `GET /todos/events` message, tagged by `type`
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum TodoEvent {
+    Created { id: i32 },
+    Updated { id: i32 },
+    /// hard-deleted, a todo moved to `deleted` is `Updated`
+    Deleted { id: i32 },
+    /// some events were missed, reload the todos
+    Lagged,
+}
+
+/// sent by the client over `GET /todos/sync`, tagged by `type`
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SyncRequest {
+    /// applied like an operation of `POST /todos/batch`, acknowledged with the same `seq`
+    Mutate {
+        seq: u64,
+        op: BatchOperation,
+        /// the version an `update` or `status` applies to, like `If-Match`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        if_match: Option<i32>,
+    },
+}
+
+/// sent by the server over `GET /todos/sync`, tagged by `type`
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SyncResponse {
+    /// the outcome of the `SyncRequest::Mutate` of `seq`
+    Ack { seq: u64, result: BatchResult },
+    /// a change in the namespace, made by this connection or any other
+    Change { event: TodoEvent },
+    /// a message which is not a `SyncRequest`
+    Error { error: ErrorResponse },
 }
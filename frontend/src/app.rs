@@ -1,56 +1,132 @@
-use yew::{Callback, ContextProvider, function_component, Html, html, use_effect_with, use_reducer, use_state};
-use yew_hooks::{use_async_with_options, UseAsyncOptions};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 
-use common::client::{ScopeClient, TodoClient};
-use common::model::{TodoResponse, TodoStatus};
+use web_sys::DragEvent;
+use wasm_bindgen_futures::spawn_local;
+use yew::{Callback, ContextProvider, function_component, Html, html, use_effect_with, use_reducer, use_state, use_state_eq};
+use yew_hooks::{use_async_with_options, use_interval, UseAsyncOptions};
 
-use crate::{icons, namespace};
+use common::client::{AuthClient, MemberClient, ScopeClient, TagClient, TodoClient};
+use common::model::{GetTodosQuery, MoveTodoRequest, TodoResponse, TodoSort, TodoStatus};
+
+use crate::{icons, namespace, session};
 use crate::components::*;
-use crate::states::{TodoAction, TodoContext, TodoState};
+use crate::offline::Offline;
+use crate::states::{tags_of, TodoAction, TodoContext, TodoState};
+use crate::sync::SyncChannel;
 
-pub fn todo_client() -> TodoClient {
-    ScopeClient::default()
+/// how often the remote is checked while remote
+const HEALTH_INTERVAL_MILLIS: u32 = 5000;
+
+fn endpoint() -> &'static str {
+    option_env!("APP_REMOTE_ENDPOINT")
+        .unwrap_or_else(|| "http://localhost:3000")
+}
+
+/// a token which cannot be sent in a header is left out, as signed out
+fn scope_client() -> ScopeClient {
+    let client = ScopeClient::default()
         .namespace(&namespace::get())
-        .endpoint(
-            option_env!("APP_REMOTE_ENDPOINT")
-                .unwrap_or_else(|| "http://localhost:3000")
-        )
-        .todo_client()
+        .expect("namespace::get has no control characters")
+        .endpoint(endpoint());
+    match session::get().map(|token| client.clone().credentials(&token)) {
+        Some(Ok(client)) => client,
+        _ => client,
+    }
+}
+
+pub fn todo_client() -> TodoClient {
+    scope_client().todo_client()
+}
+
+pub fn tag_client() -> TagClient {
+    scope_client().tag_client()
+}
+
+pub fn member_client() -> MemberClient {
+    scope_client().member_client()
+}
+
+pub fn auth_client() -> AuthClient {
+    scope_client().auth_client()
 }
 
 #[function_component(App)]
 pub fn app() -> Html {
     let state = use_reducer(TodoState::default);
     let status_tab = use_state(|| Option::<TodoStatus>::None);
+    let search = use_state(String::new);
+    let tag = use_state(|| Option::<String>::None);
 
-    let context = use_state(|| TodoContext {
-        enable_remote: false,
-    });
+    let context = {
+        let d = state.dispatcher();
+        use_state(move || TodoContext {
+            enable_remote: false,
+            online: true,
+            sync: None,
+            offline: Offline::new(namespace::get(), Callback::from(move |_| d.dispatch(TodoAction::Refresh))),
+        })
+    };
+    let online = use_state_eq(|| true);
 
     let on_enable_remote = {
         let context = context.clone();
+        let online = online.clone();
         Callback::from(move |value: bool| {
-            context.set(TodoContext { enable_remote: value })
+            // the remote button is enabled once the remote is healthy
+            online.set(true);
+            context.set(TodoContext { enable_remote: value, sync: None, ..(*context).clone() })
         })
     };
 
+    {
+        let online = online.clone();
+        let enable_remote = context.enable_remote;
+        use_interval(move || {
+            if !enable_remote {
+                return;
+            }
+            let online = online.clone();
+            spawn_local(async move {
+                online.set(todo_client().ping_client().health().await);
+            });
+        }, HEALTH_INTERVAL_MILLIS);
+    }
+
+    let remote_tags = use_async_with_options(async move {
+        tag_client()
+            .get_tags().await
+            .map(|tags| tags.into_iter().map(|it| it.name).collect::<Vec<String>>())
+            .map_err(|e| e.to_string())
+    }, UseAsyncOptions { auto: false });
+
     let remotes = {
         let status_tab = status_tab.clone();
+        let search = search.clone();
+        let tag = tag.clone();
         use_async_with_options(async move {
             todo_client()
-                .get_todos(*status_tab).await
+                .query_todos(GetTodosQuery {
+                    status: *status_tab,
+                    q: Some(search.to_string()).filter(|q| !q.is_empty()),
+                    sort: Some(TodoSort::Manual),
+                    tag: (*tag).clone(),
+                    ..Default::default()
+                }).await
                 .map_err(|e| e.to_string())
         }, UseAsyncOptions { auto: false })
     };
 
     {
         let remotes = remotes.clone();
+        let remote_tags = remote_tags.clone();
         let state = state.clone();
         let refresh = state.refresh;
-        let enable_remote = context.clone().enable_remote;
+        let enable_remote = context.enable_remote && *online;
         use_effect_with((enable_remote, refresh), move |_| {
             if enable_remote {
                 remotes.run();
+                remote_tags.run();
             } else {
                 state.save_to_local();
             }
@@ -58,25 +134,191 @@ pub fn app() -> Html {
         });
     }
 
+    // the mutations go over the sync channel while remote and online, any change in the namespace reloads the todos,
+    // the offline ones are replayed once online, and the cache catches up with the changes
+    {
+        let d = state.dispatcher();
+        let context = context.clone();
+        let enable_remote = context.enable_remote;
+        let online = *online;
+        use_effect_with((enable_remote, online), move |_| {
+            let offline = context.offline.clone();
+            let sync_offline = move || {
+                let offline = offline.clone();
+                spawn_local(async move {
+                    if let Err(err) = offline.sync(&todo_client()).await {
+                        gloo::console::warn!(format!("failed to sync: {}", err));
+                    }
+                });
+            };
+
+            // opened once its ticket is issued, which may be after the effect is cleaned up
+            let opened = Rc::new(RefCell::new(Option::<SyncChannel>::None));
+            let closed = Rc::new(Cell::new(false));
+            context.set(TodoContext { enable_remote, online, sync: None, ..(*context).clone() });
+            if enable_remote && online {
+                sync_offline();
+                let (opened, closed) = (opened.clone(), closed.clone());
+                spawn_local(async move {
+                    let mut url = format!("{}/todos/sync?ns={}", endpoint().replacen("http", "ws", 1), namespace::get());
+                    // a WebSocket cannot send the bearer token, a single-use ticket stands for it
+                    if session::get().is_some() {
+                        match auth_client().ticket().await {
+                            Ok(ticket) => url.push_str(&format!("&access_token={}", ticket.token)),
+                            Err(err) => {
+                                gloo::console::warn!(format!("failed to open the sync channel: {}", err));
+                                return;
+                            }
+                        }
+                    }
+                    if closed.get() {
+                        return;
+                    }
+                    let sync = SyncChannel::open(&url, Callback::from(move |_| {
+                        d.dispatch(TodoAction::Refresh);
+                        sync_offline();
+                    }));
+                    context.set(TodoContext { enable_remote, online, sync: sync.clone(), ..(*context).clone() });
+                    *opened.borrow_mut() = sync;
+                });
+            }
+            move || {
+                closed.set(true);
+                if let Some(sync) = opened.borrow_mut().take() {
+                    sync.close();
+                }
+            }
+        });
+    }
+
     let data = {
-        let enable_remote = context.clone().enable_remote;
-        if enable_remote {
+        if context.enable_remote && context.online {
             remotes.data.clone().unwrap_or_default()
         } else {
-            state.locals
+            let todos = if context.enable_remote { context.offline.todos() } else { state.locals.clone() };
+            todos
                 .iter()
-                .cloned()
                 .filter(|todo| {
                     status_tab.is_none() ||
                         (status_tab.is_some() && todo.status == status_tab.unwrap())
                 })
+                .filter(|todo| matches_search(todo, &search))
+                .filter(|todo| tag.as_ref().is_none_or(|tag| todo.tags.contains(tag)))
+                .cloned()
                 .collect::<Vec<TodoResponse>>()
         }
     };
 
-    let todo_elements = data.iter()
-        .map(|todo| html! {
-            <TodoDetails todo={(*todo).clone()} dispatcher={state.dispatcher()}/>
+    // subtasks are nested under their parent, or listed on their own when the parent is filtered out
+    let roots = data.iter()
+        .filter(|todo| todo.parent_id.is_none_or(|parent_id| data.iter().all(|it| it.id != parent_id)))
+        .cloned()
+        .collect::<Vec<TodoResponse>>();
+    let data = Rc::new(data);
+
+    let selected = use_state(Vec::<i32>::new);
+    let on_select = {
+        let selected = selected.clone();
+        Callback::from(move |id: i32| {
+            let mut ids = (*selected).clone();
+            match ids.iter().position(|it| *it == id) {
+                Some(index) => { ids.remove(index); }
+                None => ids.push(id),
+            }
+            selected.set(ids);
+        })
+    };
+    let on_batch_select = {
+        let selected = selected.clone();
+        Callback::from(move |ids: Vec<i32>| selected.set(ids))
+    };
+    let selected_ids = Rc::new((*selected).clone());
+
+    let dragging = use_state(|| Option::<i32>::None);
+    let move_todo_param = use_state(|| Option::<(i32, MoveTodoRequest)>::None);
+    let move_todo = {
+        let param = move_todo_param.clone();
+        let d = state.dispatcher();
+        use_async_with_options(async move {
+            let result = match (*param).clone() {
+                Some((id, to)) => todo_client().move_todo(id, to).await
+                    .map(|_| ())
+                    .map_err(|e| e.to_string()),
+                None => Ok(()),
+            };
+
+            d.dispatch(TodoAction::Refresh);
+
+            result
+        }, UseAsyncOptions { auto: false })
+    };
+
+    let on_drop = {
+        let dragging = dragging.clone();
+        let d = state.dispatcher();
+        let enable_remote = context.enable_remote;
+        let offline = context.is_offline();
+        let ids = roots.iter().map(|it| it.id).collect::<Vec<i32>>();
+        move |target: i32| {
+            let dragging = dragging.clone();
+            let d = d.clone();
+            let ids = ids.clone();
+            let param = move_todo_param.clone();
+            let move_todo = move_todo.clone();
+            Callback::from(move |e: DragEvent| {
+                e.prevent_default();
+                let Some(id) = *dragging else { return; };
+                // the manual order is not kept offline
+                if offline {
+                    dragging.set(None);
+                    return;
+                }
+                dragging.set(None);
+                if id == target {
+                    return;
+                }
+
+                // dropped on a todo below moves after it, above moves before it
+                let index_of = |id: i32| ids.iter().position(|it| *it == id);
+                let to = if index_of(id) < index_of(target) {
+                    MoveTodoRequest { after: Some(target), ..Default::default() }
+                } else {
+                    MoveTodoRequest { before: Some(target), ..Default::default() }
+                };
+
+                if enable_remote {
+                    param.set(Some((id, to)));
+                    move_todo.run();
+                } else {
+                    d.dispatch(TodoAction::Move(id, to));
+                }
+            })
+        }
+    };
+
+    let todo_elements = roots.iter()
+        .map(|todo| {
+            let id = todo.id;
+            let on_drag_start = {
+                let dragging = dragging.clone();
+                Callback::from(move |e: DragEvent| {
+                    if let Some(transfer) = e.data_transfer() {
+                        let _ = transfer.set_data("text/plain", &id.to_string());
+                    }
+                    dragging.set(Some(id));
+                })
+            };
+            let on_drag_over = Callback::from(|e: DragEvent| e.prevent_default());
+
+            html! {
+                <div draggable="true"
+                    ondragstart={on_drag_start}
+                    ondragover={on_drag_over}
+                    ondrop={on_drop(id)}>
+                    <TodoDetails todo={(*todo).clone()} dispatcher={state.dispatcher()} todos={data.clone()}
+                        selected={selected_ids.clone()} on_select={on_select.clone()}/>
+                </div>
+            }
         }).collect::<Html>();
 
     let on_tab_select = {
@@ -91,6 +333,35 @@ pub fn app() -> Html {
         })
     };
 
+    let tags = if context.is_offline() {
+        tags_of(&context.offline.todos())
+    } else if context.enable_remote {
+        remote_tags.data.clone().unwrap_or_default()
+    } else {
+        state.local_tags()
+    };
+
+    let on_tag_select = {
+        let tag = tag.clone();
+        let state = state.clone();
+        Callback::from(move |value: Option<String>| {
+            tag.set(value);
+            state.dispatch(TodoAction::Refresh);
+        })
+    };
+
+    let on_search = {
+        let search = search.clone();
+        let state = state.clone();
+        Callback::from(move |value: String| {
+            if value == *search {
+                return;
+            }
+            search.set(value);
+            state.dispatch(TodoAction::Refresh);
+        })
+    };
+
     let empty_todos = data.is_empty();
     let show_clear_deleted_button = *status_tab == Some(TodoStatus::Deleted) && !empty_todos;
 
@@ -111,16 +382,30 @@ pub fn app() -> Html {
                 </div>
                 <div class="hero-foot">
                     <Tabs on_select={on_tab_select} selected={*status_tab} />
+                    <TagFilter {tags} selected={(*tag).clone()} on_select={on_tag_select} />
                 </div>
             </section>
             <section class="container p-4 is-max-desktop">
                 <div class="is-flex is-justify-content-space-between">
                     <DataSourceSwitcher {on_enable_remote}
-                            enable_remote={context.enable_remote}/>
+                            enable_remote={context.enable_remote}
+                            locals={state.locals.clone()}/>
                     <div class="is-flex-grow-1 pl-4">
                         <AddTodo dispatcher={state.dispatcher()} />
                     </div>
                 </div>
+                <SearchBox {on_search} />
+                <div class="is-flex is-justify-content-space-between">
+                    if context.enable_remote && context.online {
+                        <MembersButton />
+                    }
+                    <div class="is-flex-grow-1">
+                        <ExportButtons locals={state.locals.clone()} />
+                    </div>
+                </div>
+                if !selected.is_empty() {
+                    <BatchActions selected={(*selected).clone()} on_select={on_batch_select} dispatcher={state.dispatcher()} />
+                }
                 if show_clear_deleted_button {
                     <ClearDeletedButton dispatcher={state.dispatcher()} />
                 }
@@ -139,6 +424,14 @@ pub fn app() -> Html {
     }
 }
 
+/// every whitespace separated term of `search` is in the content, ignoring case
+fn matches_search(todo: &TodoResponse, search: &str) -> bool {
+    let content = todo.content.to_lowercase();
+    search.to_lowercase()
+        .split_whitespace()
+        .all(|term| content.contains(term))
+}
+
 #[function_component(Header)]
 pub fn header() -> Html {
     html! {
@@ -1,60 +1,43 @@
 use std::rc::Rc;
 
 use web_sys::{HtmlInputElement, KeyboardEvent, MouseEvent};
-use yew::{Callback, function_component, Html, html, Properties, use_context, use_node_ref, use_state, UseReducerDispatcher};
-use yew_hooks::{use_async_with_options, UseAsyncOptions};
+use yew::{Callback, function_component, Html, html, Properties, use_context, use_node_ref, UseReducerDispatcher};
 
-use common::model::CreateTodoRequest;
+use common::model::{BatchOperation, CreateTodoRequest};
 
 use crate::states::{TodoAction, TodoContext, TodoState};
-use crate::app::todo_client;
 
 #[derive(Properties, PartialEq, Clone)]
 pub struct AddTodoProps {
     pub dispatcher: UseReducerDispatcher<TodoState>,
+    /// adds subtasks of the todo instead
+    #[prop_or_default]
+    pub parent_id: Option<i32>,
 }
 
 #[function_component(AddTodo)]
-pub fn add_todo(AddTodoProps { dispatcher }: &AddTodoProps) -> Html {
+pub fn add_todo(AddTodoProps { dispatcher, parent_id }: &AddTodoProps) -> Html {
     let input_ref = use_node_ref();
     let dispatcher = dispatcher.clone();
-
-    let create_todo_param = use_state(|| String::new());
-    let create_todo = {
-        let d = dispatcher.clone();
-        let param = create_todo_param.clone();
-        use_async_with_options(async move {
-            let content = param.to_string();
-            let result = todo_client()
-                .create_todo(CreateTodoRequest { content }).await
-                .map_err(|e| e.to_string());
-
-            d.dispatch(TodoAction::Refresh);
-
-            result
-        }, UseAsyncOptions { auto: false })
-    };
+    let parent_id = *parent_id;
 
     let context = use_context::<TodoContext>().expect("no ctx found");
     let handle_input = Rc::new({
         let input_ref = input_ref.clone();
-        let param = create_todo_param.clone();
         let d = dispatcher.clone();
         let context = context.clone();
-        let create_todo = create_todo.clone();
 
         move || {
             if let Some(input) = input_ref.cast::<HtmlInputElement>() {
-                let value = input.value();
-                let value = value.trim();
-                if value.len() == 0 {
+                let (content, tags) = split_tags(&input.value());
+                if content.is_empty() {
                     return;
                 }
                 if context.enable_remote {
-                    param.set(value.to_string());
-                    create_todo.run();
+                    let to_create = CreateTodoRequest { content, tags, parent_id, ..Default::default() };
+                    context.mutate(BatchOperation::Create(to_create), None, Callback::noop());
                 } else {
-                    d.dispatch(TodoAction::Add(value.to_string()));
+                    d.dispatch(TodoAction::Add(content, tags, parent_id));
                 }
                 input.set_value("");
             }
@@ -75,6 +58,12 @@ pub fn add_todo(AddTodoProps { dispatcher }: &AddTodoProps) -> Html {
         })
     };
 
+    let placeholder = if parent_id.is_some() {
+        "Add a subtask, #tag to label"
+    } else {
+        "Press 'enter' to submit, #tag to label"
+    };
+
     html! {
         <div class="field has-addons">
             <div class="control is-expanded">
@@ -82,7 +71,7 @@ pub fn add_todo(AddTodoProps { dispatcher }: &AddTodoProps) -> Html {
                         type="text"
                         ref={input_ref}
                         onkeypress={on_enter_press}
-                        placeholder={ "Press 'enter' to submit" }/>
+                        {placeholder}/>
             </div>
             <div class="control">
                 <button class="button is-rounded is-info" onclick={on_submit}>{ "Add" }</button>
@@ -91,3 +80,15 @@ pub fn add_todo(AddTodoProps { dispatcher }: &AddTodoProps) -> Html {
     }
 }
 
+
+/// the `#tag` words are taken out of the content as tags
+fn split_tags(input: &str) -> (String, Vec<String>) {
+    let (tags, words): (Vec<&str>, Vec<&str>) = input.split_whitespace()
+        .partition(|word| word.len() > 1 && word.starts_with('#'));
+
+    let mut tags = tags.iter().map(|it| it[1..].to_string()).collect::<Vec<String>>();
+    tags.sort();
+    tags.dedup();
+
+    (words.join(" "), tags)
+}
@@ -0,0 +1,105 @@
+use yew::{Callback, function_component, html, Html, Properties, use_context, use_state, UseReducerDispatcher};
+use yew_hooks::use_async;
+
+use common::model::{BatchOperation, BatchResult, TodoStatus, UpdateTodoRequest};
+
+use crate::app::todo_client;
+use crate::states::{TodoAction, TodoContext, TodoState};
+
+#[derive(Properties, PartialEq, Clone)]
+pub struct BatchActionsProps {
+    pub selected: Vec<i32>,
+    /// the new selection, the failed todos stay selected
+    pub on_select: Callback<Vec<i32>>,
+    pub dispatcher: UseReducerDispatcher<TodoState>,
+}
+
+/// change the status of the selected todos at once, with a single `POST /todos/batch` when remote and online
+#[function_component(BatchActions)]
+pub fn batch_actions(BatchActionsProps { selected, on_select, dispatcher }: &BatchActionsProps) -> Html {
+    let batch_param = use_state(Vec::<BatchOperation>::new);
+    let batch = {
+        let param = batch_param.clone();
+        let d = dispatcher.clone();
+        let on_select = on_select.clone();
+        use_async(async move {
+            let ids = param.iter()
+                .map(|it| match it {
+                    BatchOperation::Status { id, .. } | BatchOperation::Update { id, .. } | BatchOperation::Delete { id } => Some(*id),
+                    BatchOperation::Create(_) => None,
+                })
+                .collect::<Vec<Option<i32>>>();
+            let result = todo_client().batch((*param).clone()).await
+                .map(|response| ids.into_iter()
+                    .zip(response.results)
+                    .filter_map(|(id, result)| id.filter(|_| matches!(result, BatchResult::Error(_))))
+                    .collect::<Vec<i32>>())
+                .map_err(|e| e.to_string());
+
+            if let Ok(failed) = &result {
+                on_select.emit(failed.clone());
+            }
+            d.dispatch(TodoAction::Refresh);
+
+            result
+        })
+    };
+
+    let context = use_context::<TodoContext>().expect("no ctx found");
+    let update_status = |status: TodoStatus| {
+        let ids = selected.clone();
+        let d = dispatcher.clone();
+        let param = batch_param.clone();
+        let batch = batch.clone();
+        let on_select = on_select.clone();
+        let context = context.clone();
+        Callback::from(move |_| {
+            if context.is_offline() {
+                let failed = ids.iter()
+                    .filter(|id| matches!(
+                        context.offline.queue(BatchOperation::Status { id: **id, status, cascade: false }),
+                        BatchResult::Error(_)
+                    ))
+                    .copied()
+                    .collect();
+                on_select.emit(failed);
+            } else if context.enable_remote {
+                param.set(ids.iter()
+                    .map(|id| BatchOperation::Status { id: *id, status, cascade: false })
+                    .collect());
+                batch.run();
+            } else {
+                d.dispatch(TodoAction::UpdateAll(ids.clone(), UpdateTodoRequest {
+                    status: Some(status),
+                    ..Default::default()
+                }));
+                on_select.emit(vec![]);
+            }
+        })
+    };
+
+    let failed = batch.data.as_ref().map_or(0, |failed| failed.iter().filter(|it| selected.contains(it)).count());
+
+    html! {
+        <div class="field is-grouped is-align-items-center">
+            <p class="control is-expanded">
+                { format!("{} selected", selected.len()) }
+                if failed > 0 {
+                    <span class="tag is-danger is-light is-rounded ml-2">{ format!("{} failed", failed) }</span>
+                }
+            </p>
+            <p class="control">
+                <button class="button is-small is-success is-light" onclick={update_status(TodoStatus::Done)}>{ "Done" }</button>
+            </p>
+            <p class="control">
+                <button class="button is-small is-warning is-light" onclick={update_status(TodoStatus::Archived)}>{ "Archive" }</button>
+            </p>
+            <p class="control">
+                <button class="button is-small is-light" onclick={update_status(TodoStatus::Deleted)}>{ "Delete" }</button>
+            </p>
+            <p class="control">
+                <button class="button is-small is-text" onclick={on_select.reform(|_| vec![])}>{ "Cancel" }</button>
+            </p>
+        </div>
+    }
+}
@@ -1,6 +1,6 @@
 use yew::{Html, Properties, function_component, use_context, use_state, UseReducerDispatcher, html, Callback};
 use yew_hooks::use_async;
-use common::model::TodoStatus;
+use common::model::{BatchOperation, TodoStatus};
 use crate::states::{TodoAction, TodoContext, TodoState};
 use crate::app::todo_client;
 
@@ -39,7 +39,11 @@ pub fn clear_deleted_button(ClearDeletedButtonProps { dispatcher }: &ClearDelete
         let d = dispatcher.clone();
         Callback::from(move |_| {
             if *confirm_status {
-                if context.enable_remote {
+                if context.is_offline() {
+                    for id in context.offline.ids_with(TodoStatus::Deleted) {
+                        context.offline.queue(BatchOperation::Delete { id });
+                    }
+                } else if context.enable_remote {
                     clear_deleted.run();
                 } else {
                     d.dispatch(TodoAction::ClearDeleted);
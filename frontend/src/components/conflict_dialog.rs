@@ -0,0 +1,48 @@
+use web_sys::MouseEvent;
+use yew::{Callback, function_component, html, Html, Properties};
+
+use common::model::TodoResponse;
+
+#[derive(Properties, PartialEq, Clone)]
+pub struct ConflictDialogProps {
+    /// the content being saved
+    pub yours: String,
+    /// the todo as it is on the server now
+    pub theirs: TodoResponse,
+    /// save `yours` over `theirs`
+    pub on_overwrite: Callback<MouseEvent>,
+    /// drop `yours` and reload
+    pub on_discard: Callback<MouseEvent>,
+}
+
+/// shown when a save is rejected with `ErrorCode::VersionConflict`
+#[function_component(ConflictDialog)]
+pub fn conflict_dialog(ConflictDialogProps { yours, theirs, on_overwrite, on_discard }: &ConflictDialogProps) -> Html {
+    html! {
+        <div class="modal is-active">
+            <div class="modal-background" onclick={on_discard.clone()}></div>
+            <div class="modal-card">
+                <header class="modal-card-head">
+                    <p class="modal-card-title">{ "This todo has been changed" }</p>
+                </header>
+                <section class="modal-card-body">
+                    <div class="field">
+                        <label class="label">{ "Yours" }</label>
+                        <p>{ yours }</p>
+                    </div>
+                    <div class="field">
+                        <label class="label">{ "Theirs" }</label>
+                        <p>
+                            { &theirs.content }
+                            <span class="tag is-light is-rounded ml-2">{ theirs.status.to_string() }</span>
+                        </p>
+                    </div>
+                </section>
+                <footer class="modal-card-foot">
+                    <button class="button is-danger" onclick={on_overwrite.clone()}>{ "Overwrite" }</button>
+                    <button class="button" onclick={on_discard.clone()}>{ "Discard mine" }</button>
+                </footer>
+            </div>
+        </div>
+    }
+}
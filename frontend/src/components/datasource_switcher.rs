@@ -2,6 +2,8 @@ use web_sys::MouseEvent;
 use yew::{Callback, function_component, Html, html, Properties};
 use yew_hooks::use_async;
 
+use common::model::{ImportTodo, TodoResponse};
+
 use crate::icons;
 use crate::app::todo_client;
 use crate::hooks::use_retry;
@@ -71,14 +73,66 @@ pub fn remote_button(RemoteButtonProps { enable_remote, on_enable_remote }: &Rem
 }
 
 
+#[derive(Properties, PartialEq, Clone)]
+pub struct UploadButtonProps {
+    pub locals: Vec<TodoResponse>,
+    /// switches to remote once uploaded
+    pub on_enable_remote: Callback<bool>,
+}
+
+/// import the local todos into the remote namespace, the local ones are kept
+#[function_component(UploadButton)]
+pub fn upload_button(UploadButtonProps { locals, on_enable_remote }: &UploadButtonProps) -> Html {
+    let upload = {
+        let locals = locals.clone();
+        let on_enable_remote = on_enable_remote.clone();
+        use_async(async move {
+            let response = todo_client()
+                .import_todos(locals.into_iter().map(ImportTodo::from).collect()).await
+                .map_err(|e| e.to_string())?;
+
+            on_enable_remote.emit(true);
+
+            Ok::<_, String>(response.counts())
+        })
+    };
+
+    let on_upload = {
+        let upload = upload.clone();
+        Callback::from(move |_: MouseEvent| upload.run())
+    };
+
+    let summary = match (&upload.data, &upload.error) {
+        (Some((created, duplicate, failed)), _) => Some(("is-success", format!(
+            "{} uploaded, {} duplicate, {} failed", created, duplicate, failed
+        ))),
+        (None, Some(error)) => Some(("is-danger", error.clone())),
+        (None, None) => None,
+    };
+    let class = if upload.loading { "button is-rounded is-loading" } else { "button is-rounded" };
+
+    html! {
+        <div class="control" data-tooltip="upload the local todos to remote server">
+            <button {class} onclick={on_upload}>
+                <span class="icon"><icons::CloudUpload /></span>
+            </button>
+            if let Some((color, text)) = summary {
+                <span class={format!("tag is-light is-rounded ml-2 {}", color)}>{ text }</span>
+            }
+        </div>
+    }
+}
+
 #[derive(Properties, PartialEq, Clone)]
 pub struct DataSourceSwitcherProps {
     pub enable_remote: bool,
     pub on_enable_remote: Callback<bool>,
+    /// the todos of the local mode, offered to upload
+    pub locals: Vec<TodoResponse>,
 }
 
 #[function_component(DataSourceSwitcher)]
-pub fn data_source_switcher(DataSourceSwitcherProps { on_enable_remote, enable_remote }: &DataSourceSwitcherProps) -> Html {
+pub fn data_source_switcher(DataSourceSwitcherProps { on_enable_remote, enable_remote, locals }: &DataSourceSwitcherProps) -> Html {
     let on_local = {
         let on_enable_remote = on_enable_remote.clone();
         Callback::from(move |_: MouseEvent| {
@@ -97,7 +151,10 @@ pub fn data_source_switcher(DataSourceSwitcherProps { on_enable_remote, enable_r
                     <span class="icon"><icons::CloudOffline /></span>
                 </button>
             </div>
-            <RemoteButton {on_enable_remote} enable_remote={*enable_remote} />
+            <RemoteButton on_enable_remote={on_enable_remote.clone()} enable_remote={*enable_remote} />
+            if !locals.is_empty() {
+                <UploadButton locals={locals.clone()} on_enable_remote={on_enable_remote.clone()} />
+            }
         </div>
     }
 }
@@ -0,0 +1,75 @@
+use gloo::file::{Blob, ObjectUrl};
+use gloo::timers::callback::Timeout;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlElement;
+use web_sys::wasm_bindgen::JsCast;
+use yew::{Callback, function_component, html, Html, Properties, use_context};
+
+use common::model::{ExportFormat, TodoResponse};
+
+use crate::app::todo_client;
+use crate::states::TodoContext;
+
+#[derive(Properties, PartialEq, Clone)]
+pub struct ExportButtonsProps {
+    pub locals: Vec<TodoResponse>,
+}
+
+/// download the todos as a document, from the remote while remote and online
+#[function_component(ExportButtons)]
+pub fn export_buttons(ExportButtonsProps { locals }: &ExportButtonsProps) -> Html {
+    let context = use_context::<TodoContext>().expect("no ctx found");
+
+    let on_export = |format: ExportFormat| {
+        let context = context.clone();
+        let locals = locals.clone();
+        Callback::from(move |_| {
+            if context.enable_remote && context.online {
+                spawn_local(async move {
+                    match todo_client().export_todos(format).await {
+                        Ok(document) => download(format, document),
+                        Err(err) => gloo::console::error!(err.to_string()),
+                    }
+                });
+            } else {
+                let todos = if context.enable_remote { context.offline.todos() } else { locals.clone() };
+                download(format, document_of(format, &todos));
+            }
+        })
+    };
+
+    html! {
+        <div class="field is-grouped is-grouped-right">
+            <p class="control">{ "Download" }</p>
+            { for [ExportFormat::Json, ExportFormat::Csv, ExportFormat::Md, ExportFormat::Txt].into_iter().map(|format| html! {
+                <p class="control">
+                    <button class="button is-small is-light" onclick={on_export(format)}>
+                        { format.extension() }
+                    </button>
+                </p>
+            }) }
+        </div>
+    }
+}
+
+/// the document `GET /todos/export` would respond for the todos
+fn document_of(format: ExportFormat, todos: &[TodoResponse]) -> String {
+    let mut document = format.begin("local");
+    for (index, todo) in todos.iter().enumerate() {
+        document.push_str(&format.todo(todo, index));
+    }
+    document.push_str(&format.end());
+    document
+}
+
+/// save the document with the browser
+fn download(format: ExportFormat, document: String) {
+    let url = ObjectUrl::from(Blob::new_with_options(document.as_str(), Some(format.content_type())));
+    let anchor = gloo::utils::document().create_element("a").expect("an anchor");
+    let _ = anchor.set_attribute("href", &url);
+    let _ = anchor.set_attribute("download", &format!("todos.{}", format.extension()));
+    anchor.unchecked_ref::<HtmlElement>().click();
+
+    // revoked once the download has started
+    Timeout::new(1000, move || drop(url)).forget();
+}
@@ -0,0 +1,347 @@
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement, MouseEvent};
+use yew::{Callback, Event, function_component, html, Html, NodeRef, Properties, TargetCast, use_effect_with, use_node_ref, use_state, UseStateHandle};
+
+use common::client::ClientError;
+use common::model::{ErrorCode, InvitationResponse, MembersResponse, NamespaceRole, UserResponse};
+
+use crate::app::{auth_client, member_client};
+use crate::{namespace, session};
+
+const ROLES: [NamespaceRole; 3] = [NamespaceRole::Viewer, NamespaceRole::Editor, NamespaceRole::Owner];
+
+/// opens the [`MembersDialog`] of the namespace
+#[function_component(MembersButton)]
+pub fn members_button() -> Html {
+    let open = use_state(|| false);
+
+    let on_open = {
+        let open = open.clone();
+        Callback::from(move |_: MouseEvent| open.set(true))
+    };
+    let on_close = {
+        let open = open.clone();
+        Callback::from(move |_: MouseEvent| open.set(false))
+    };
+
+    html! {
+        <>
+            <div class="field">
+                <p class="control">
+                    <button class="button is-small is-light" onclick={on_open}>{ "Members" }</button>
+                </p>
+            </div>
+            if *open {
+                <MembersDialog {on_close} />
+            }
+        </>
+    }
+}
+
+#[derive(Properties, PartialEq, Clone)]
+pub struct MembersDialogProps {
+    pub on_close: Callback<MouseEvent>,
+}
+
+/// log in, switch between the namespaces of the user, and share the current one with roles
+#[function_component(MembersDialog)]
+pub fn members_dialog(MembersDialogProps { on_close }: &MembersDialogProps) -> Html {
+    let refresh = use_state(|| 0u32);
+    let me = use_state(|| Option::<UserResponse>::None);
+    let members = use_state(|| Option::<MembersResponse>::None);
+    let invitation = use_state(|| Option::<InvitationResponse>::None);
+    let error = use_state(|| Option::<String>::None);
+
+    let username_ref = use_node_ref();
+    let password_ref = use_node_ref();
+    let invite_role_ref = use_node_ref();
+    let join_ref = use_node_ref();
+
+    {
+        let me = me.clone();
+        let members = members.clone();
+        let error = error.clone();
+        use_effect_with(*refresh, move |_| {
+            spawn_local(async move {
+                if session::get().is_some() {
+                    match auth_client().me().await {
+                        Ok(user) => me.set(Some(user)),
+                        Err(err) => {
+                            // the token expired, the namespace may still be open to anyone
+                            if err.code() == Some(ErrorCode::Unauthorized) {
+                                session::clear();
+                            }
+                            me.set(None);
+                            error.set(Some(err.to_string()));
+                        }
+                    }
+                }
+                match member_client().get_members().await {
+                    Ok(response) => members.set(Some(response)),
+                    Err(err) => {
+                        members.set(None);
+                        error.set(Some(err.to_string()));
+                    }
+                }
+            });
+            || ()
+        });
+    }
+
+    let on_login = |register: bool| {
+        let username_ref = username_ref.clone();
+        let password_ref = password_ref.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let (username, password) = (value_of(&username_ref), value_of(&password_ref));
+            let error = error.clone();
+            spawn_local(async move {
+                let client = auth_client();
+                let result = async {
+                    if register {
+                        client.register(&username, &password).await?;
+                    }
+                    session::set(&client.login(&username, &password).await?.token);
+                    Ok(())
+                }.await;
+                // the todos and the sync channel are of the user from now on
+                reload_or_show(result, &error);
+            });
+        })
+    };
+
+    let on_logout = {
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let error = error.clone();
+            spawn_local(async move {
+                let result = auth_client().logout().await;
+                session::clear();
+                reload_or_show(result, &error);
+            });
+        })
+    };
+
+    let on_switch = |to: String| {
+        Callback::from(move |_: MouseEvent| {
+            namespace::set(&to);
+            gloo::utils::window().location().reload().expect("reload");
+        })
+    };
+
+    let on_claim = {
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let error = error.clone();
+            spawn_local(async move {
+                let result = auth_client().claim_namespace(&namespace::get()).await.map(|_| ());
+                reload_or_show(result, &error);
+            });
+        })
+    };
+
+    let on_update = |user_id: i32| {
+        let refresh = refresh.clone();
+        let error = error.clone();
+        Callback::from(move |e: Event| {
+            let Some(role) = e.target_dyn_into::<HtmlSelectElement>().and_then(|it| role_of(&it.value())) else { return; };
+            let (refresh, error) = (refresh.clone(), error.clone());
+            spawn_local(async move {
+                let result = member_client().update_member(user_id, role).await.map(|_| ());
+                refresh_or_show(result, &refresh, &error);
+            });
+        })
+    };
+
+    let on_remove = |user_id: i32| {
+        let refresh = refresh.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let (refresh, error) = (refresh.clone(), error.clone());
+            spawn_local(async move {
+                let result = member_client().remove_member(user_id).await;
+                refresh_or_show(result, &refresh, &error);
+            });
+        })
+    };
+
+    let on_invite = {
+        let invite_role_ref = invite_role_ref.clone();
+        let invitation = invitation.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let Some(role) = invite_role_ref.cast::<HtmlSelectElement>().and_then(|it| role_of(&it.value())) else { return; };
+            let (invitation, error) = (invitation.clone(), error.clone());
+            spawn_local(async move {
+                match member_client().create_invitation(role).await {
+                    Ok(created) => invitation.set(Some(created)),
+                    Err(err) => error.set(Some(err.to_string())),
+                }
+            });
+        })
+    };
+
+    let on_join = {
+        let join_ref = join_ref.clone();
+        let refresh = refresh.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let token = value_of(&join_ref);
+            let (refresh, error) = (refresh.clone(), error.clone());
+            spawn_local(async move {
+                let result = auth_client().accept_invitation(&token).await.map(|_| ());
+                refresh_or_show(result, &refresh, &error);
+            });
+        })
+    };
+
+    let current = namespace::get();
+    let role = members.as_ref().map(|it| it.role);
+    let is_owner = role == Some(NamespaceRole::Owner);
+
+    let account = match &*me {
+        None => html! {
+            <div class="field has-addons">
+                <p class="control"><input class="input is-small" ref={username_ref.clone()} placeholder="Username"/></p>
+                <p class="control"><input class="input is-small" type="password" ref={password_ref.clone()} placeholder="Password"/></p>
+                <p class="control"><button class="button is-small is-link" onclick={on_login(false)}>{ "Log in" }</button></p>
+                <p class="control"><button class="button is-small" onclick={on_login(true)}>{ "Register" }</button></p>
+            </div>
+        },
+        Some(user) => html! {
+            <>
+                <div class="field is-grouped">
+                    <p class="control">{ format!("Logged in as {}", user.username) }</p>
+                    <p class="control"><button class="button is-small" onclick={on_logout}>{ "Log out" }</button></p>
+                </div>
+                <div class="tags">
+                    { for user.namespaces.iter().map(|it| {
+                        let class = if *it == current { "tag is-link" } else { "tag is-light is-clickable" };
+                        html! { <span {class} onclick={on_switch(it.clone())}>{ it }</span> }
+                    }) }
+                </div>
+                <div class="field has-addons">
+                    <p class="control is-expanded"><input class="input is-small" ref={join_ref} placeholder="Invitation token"/></p>
+                    <p class="control"><button class="button is-small" onclick={on_join}>{ "Join" }</button></p>
+                </div>
+            </>
+        },
+    };
+
+    let members_table = match &*members {
+        None => html! {},
+        Some(response) if response.members.is_empty() => html! {
+            <div class="field is-grouped">
+                <p class="control">{ "Anyone may edit this namespace." }</p>
+                if me.is_some() {
+                    <p class="control"><button class="button is-small is-link" onclick={on_claim}>{ "Own it" }</button></p>
+                }
+            </div>
+        },
+        Some(response) => html! {
+            <table class="table is-fullwidth is-narrow">
+                <tbody>
+                { for response.members.iter().map(|member| {
+                    let myself = me.as_ref().is_some_and(|it| it.id == member.user_id);
+                    html! {
+                        <tr>
+                            <td>{ &member.username }</td>
+                            <td>
+                                if is_owner {
+                                    <div class="select is-small">
+                                        <select onchange={on_update(member.user_id)}>
+                                            { for ROLES.iter().map(|it| html! {
+                                                <option value={it.to_string()} selected={*it == member.role}>{ it.to_string() }</option>
+                                            }) }
+                                        </select>
+                                    </div>
+                                } else {
+                                    { member.role.to_string() }
+                                }
+                            </td>
+                            <td class="has-text-right">
+                                if is_owner || myself {
+                                    <button class="button is-small is-danger is-light" onclick={on_remove(member.user_id)}>
+                                        { if myself { "Leave" } else { "Remove" } }
+                                    </button>
+                                }
+                            </td>
+                        </tr>
+                    }
+                }) }
+                </tbody>
+            </table>
+        },
+    };
+
+    html! {
+        <div class="modal is-active">
+            <div class="modal-background" onclick={on_close.clone()}></div>
+            <div class="modal-card">
+                <header class="modal-card-head">
+                    <p class="modal-card-title">{ "Members" }</p>
+                    <button class="delete" aria-label="close" onclick={on_close.clone()}></button>
+                </header>
+                <section class="modal-card-body">
+                    { account }
+                    <hr/>
+                    <p class="heading">
+                        { current.clone() }
+                        if let Some(role) = role {
+                            <span class="tag is-light is-rounded ml-2">{ role.to_string() }</span>
+                        }
+                    </p>
+                    { members_table }
+                    if is_owner {
+                        <div class="field has-addons">
+                            <p class="control">
+                                <span class="select is-small">
+                                    <select ref={invite_role_ref}>
+                                        { for ROLES.iter().map(|it| html! {
+                                            <option value={it.to_string()} selected={*it == NamespaceRole::Viewer}>{ it.to_string() }</option>
+                                        }) }
+                                    </select>
+                                </span>
+                            </p>
+                            <p class="control"><button class="button is-small is-link" onclick={on_invite}>{ "Invite" }</button></p>
+                        </div>
+                        if let Some(invitation) = &*invitation {
+                            <p class="help">
+                                { format!("Share this token with the new {} until {}: ", invitation.role, invitation.expires_at.format("%Y-%m-%d")) }
+                                <code>{ &invitation.token }</code>
+                            </p>
+                        }
+                    }
+                    if let Some(error) = &*error {
+                        <p class="help is-danger">{ error }</p>
+                    }
+                </section>
+            </div>
+        </div>
+    }
+}
+
+fn value_of(input: &NodeRef) -> String {
+    input.cast::<HtmlInputElement>().map(|it| it.value().trim().to_string()).unwrap_or_default()
+}
+
+fn role_of(value: &str) -> Option<NamespaceRole> {
+    ROLES.into_iter().find(|it| it.to_string() == value)
+}
+
+fn refresh_or_show(result: Result<(), ClientError>, refresh: &UseStateHandle<u32>, error: &UseStateHandle<Option<String>>) {
+    match result {
+        Ok(()) => {
+            error.set(None);
+            refresh.set(**refresh + 1);
+        }
+        Err(err) => error.set(Some(err.to_string())),
+    }
+}
+
+fn reload_or_show(result: Result<(), ClientError>, error: &UseStateHandle<Option<String>>) {
+    match result {
+        Ok(()) => gloo::utils::window().location().reload().expect("reload"),
+        Err(err) => error.set(Some(err.to_string())),
+    }
+}
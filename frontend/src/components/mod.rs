@@ -4,10 +4,20 @@ mod add_todo;
 mod clear_deleted;
 mod datasource_switcher;
 mod tabs;
+mod search_box;
+mod tag_filter;
+mod batch_actions;
+mod conflict_dialog;
+mod export_todos;
+mod members;
 
-pub use todo_detail::*;
 pub use todo_detail::*;
 pub use add_todo::*;
 pub use clear_deleted::*;
 pub use datasource_switcher::*;
 pub use tabs::*;
+pub use search_box::*;
+pub use tag_filter::*;
+pub use batch_actions::*;
+pub use export_todos::*;
+pub use members::*;
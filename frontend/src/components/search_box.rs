@@ -0,0 +1,40 @@
+use web_sys::{HtmlInputElement, InputEvent};
+use yew::{Callback, function_component, Html, html, Properties, use_node_ref};
+use yew_hooks::use_debounce;
+
+use crate::icons;
+
+#[derive(Properties, PartialEq, Clone)]
+pub struct SearchBoxProps {
+    pub on_search: Callback<String>,
+}
+
+#[function_component(SearchBox)]
+pub fn search_box(SearchBoxProps { on_search }: &SearchBoxProps) -> Html {
+    let input_ref = use_node_ref();
+
+    let search = {
+        let input_ref = input_ref.clone();
+        let on_search = on_search.clone();
+        use_debounce(move || {
+            if let Some(input) = input_ref.cast::<HtmlInputElement>() {
+                on_search.emit(input.value().trim().to_string());
+            }
+        }, 300)
+    };
+
+    let on_input = Callback::from(move |_: InputEvent| search.run());
+
+    html! {
+        <div class="field">
+            <div class="control has-icons-left">
+                <input class="input is-rounded"
+                        type="search"
+                        ref={input_ref}
+                        oninput={on_input}
+                        placeholder={ "Search todos" }/>
+                <span class="icon is-left"><icons::Search /></span>
+            </div>
+        </div>
+    }
+}
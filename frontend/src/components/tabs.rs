@@ -25,7 +25,7 @@ pub fn tabs(TabsProps { on_select, selected }: &TabsProps) -> Html {
         };
         let on_tab_click = {
             let on_select = on_select.clone();
-            let tab = status.clone();
+            let tab = *status;
             Callback::from(move |_| on_select.emit(tab))
         };
 
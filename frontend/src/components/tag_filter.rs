@@ -0,0 +1,38 @@
+use yew::{Callback, function_component, Html, html, Properties};
+
+#[derive(Properties, PartialEq, Clone)]
+pub struct TagFilterProps {
+    pub tags: Vec<String>,
+    pub selected: Option<String>,
+    pub on_select: Callback<Option<String>>,
+}
+
+/// tag chips to filter the todos, a click on the selected one clears the filter
+#[function_component(TagFilter)]
+pub fn tag_filter(TagFilterProps { tags, selected, on_select }: &TagFilterProps) -> Html {
+    if tags.is_empty() {
+        return html! {};
+    }
+
+    let chips = tags.iter().map(|tag| {
+        let active = selected.as_ref() == Some(tag);
+        let on_click = {
+            let on_select = on_select.clone();
+            let tag = Some(tag.clone()).filter(|_| !active);
+            Callback::from(move |_| on_select.emit(tag.clone()))
+        };
+        let color = if active { "is-link" } else { "is-light" };
+
+        html! {
+            <a class={format!("tag is-rounded {}", color)} onclick={on_click}>{ format!("#{}", tag) }</a>
+        }
+    }).collect::<Html>();
+
+    html! {
+        <div class="container is-max-desktop px-4 pb-2">
+            <div class="tags">
+                { chips }
+            </div>
+        </div>
+    }
+}
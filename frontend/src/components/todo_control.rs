@@ -1,11 +1,10 @@
+use gloo::dialogs::confirm;
 use web_sys::MouseEvent;
-use yew::{Callback, function_component, html, Html, Properties, use_context, use_state, UseReducerDispatcher};
-use yew_hooks::use_async;
+use yew::{Callback, function_component, html, Html, Properties, use_context, UseReducerDispatcher};
 
-use common::model::{TodoStatus, UpdateTodoRequest};
+use common::model::{BatchOperation, TodoStatus, UpdateTodoRequest};
 
 use crate::states::{TodoAction, TodoContext, TodoState};
-use crate::app::todo_client;
 use crate::icons;
 
 #[derive(Properties, PartialEq, Clone)]
@@ -16,48 +15,38 @@ pub struct TodoControlProps {
     pub on_edit: Callback<MouseEvent>,
     pub on_save_editing: Callback<MouseEvent>,
     pub dispatcher: UseReducerDispatcher<TodoState>,
+    /// subtasks still `todo`, marking done asks to close them too
+    #[prop_or_default]
+    pub open_subtasks: usize,
 }
 
 #[function_component(TodoControl)]
 pub fn todo_control(
-    TodoControlProps { id, status, editing, on_edit, dispatcher, on_save_editing }: &TodoControlProps
+    TodoControlProps { id, status, editing, on_edit, dispatcher, on_save_editing, open_subtasks }: &TodoControlProps
 ) -> Html {
-    let status = status.clone();
+    let status = *status;
     let on_save_editing = on_save_editing.clone();
     let editing = *editing;
 
-    let update_todo_param = use_state(|| Option::<TodoStatus>::None);
-    let update_todo_status = {
-        let id = id.clone();
-        let status_to_update = update_todo_param.clone().clone();
-        let d = dispatcher.clone();
-        use_async(async move {
-            let result = todo_client().update_todo(id, UpdateTodoRequest {
-                content: None,
-                status: *status_to_update,
-            }).await;
-
-            d.dispatch(TodoAction::Refresh);
-
-            result.map_err(|e| e.to_string())
-        })
-    };
-
     let context = use_context::<TodoContext>().expect("no ctx found");
+    let open_subtasks = *open_subtasks;
     let update_status = {
         |status: TodoStatus| {
-            let id = id.clone();
+            let id = *id;
             let d = dispatcher.clone();
-            let param = update_todo_param.clone();
-            let update_todo_status = update_todo_status.clone();
+            let context = context.clone();
             Callback::from(move |_| {
+                let cascade = status == TodoStatus::Done && open_subtasks > 0;
+                if cascade && !confirm(&format!("Mark the {} open subtasks as done too?", open_subtasks)) {
+                    return;
+                }
                 if context.enable_remote {
-                    param.set(Some(status));
-                    update_todo_status.run();
+                    context.mutate(BatchOperation::Status { id, status, cascade }, None, Callback::noop());
                 } else {
                     d.dispatch(TodoAction::Update(id, UpdateTodoRequest {
-                        content: None,
                         status: Some(status),
+                        cascade,
+                        ..Default::default()
                     }))
                 }
             })
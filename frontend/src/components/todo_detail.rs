@@ -1,19 +1,31 @@
 use std::rc::Rc;
 
+use chrono::{Local, Utc};
 use web_sys::{HtmlInputElement, KeyboardEvent, MouseEvent};
 use yew::{Callback, function_component, Html, html, Properties, ToHtml, use_context, use_node_ref, use_state, UseReducerDispatcher};
-use yew_hooks::use_async;
+use wasm_bindgen_futures::spawn_local;
 
-use common::model::{TodoResponse, TodoStatus, UpdateTodoRequest};
+use common::model::{BatchOperation, BatchResult, ErrorCode, HIGHLIGHT_START, HIGHLIGHT_STOP, TodoPriority, TodoResponse, TodoStatus, UpdateTodoRequest};
 use crate::app::todo_client;
 
+use crate::components::add_todo::*;
+use crate::components::conflict_dialog::*;
 use crate::components::todo_control::*;
-use crate::states::{TodoAction, TodoContext, TodoState};
+use crate::states::{subtask_ids, TodoAction, TodoContext, TodoState};
 
 #[derive(Properties, PartialEq, Clone)]
 pub struct TodoDetailsProps {
     pub todo: TodoResponse,
     pub dispatcher: UseReducerDispatcher<TodoState>,
+    /// the listed todos, the subtasks of `todo` are nested under it
+    #[prop_or_default]
+    pub todos: Rc<Vec<TodoResponse>>,
+    /// ids of the todos selected for the `BatchActions`
+    #[prop_or_default]
+    pub selected: Rc<Vec<i32>>,
+    /// toggle the selection of a todo
+    #[prop_or_default]
+    pub on_select: Callback<i32>,
 }
 
 struct TodoStatusHtml(TodoStatus);
@@ -24,55 +36,116 @@ impl ToHtml for TodoStatusHtml {
     }
 }
 
+/// render the search snippet as text, with the matched terms in `<mark>`
+fn highlight(snippet: &str) -> Html {
+    snippet.split(HIGHLIGHT_START)
+        .enumerate()
+        .map(|(i, part)| match part.split_once(HIGHLIGHT_STOP) {
+            Some((marked, rest)) if i > 0 => html! { <><mark>{ marked }</mark>{ rest }</> },
+            _ => html! { part },
+        })
+        .collect::<Html>()
+}
+
 #[function_component(TodoDetails)]
-pub fn todo_details(TodoDetailsProps { todo, dispatcher }: &TodoDetailsProps) -> Html {
+pub fn todo_details(TodoDetailsProps { todo, dispatcher, todos, selected, on_select }: &TodoDetailsProps) -> Html {
     let editing = use_state(|| false);
+    let expanded = use_state(|| true);
+    let adding = use_state(|| false);
+
+    let toggle_expanded = {
+        let expanded = expanded.clone();
+        Callback::from(move |_: MouseEvent| expanded.set(!*expanded))
+    };
+
+    let toggle_adding = {
+        let expanded = expanded.clone();
+        let adding = adding.clone();
+        Callback::from(move |_: MouseEvent| {
+            expanded.set(true);
+            adding.set(!*adding);
+        })
+    };
 
     let toggle_edit = {
         let editing = editing.clone();
         Callback::from(move |_| editing.set(!*editing))
     };
 
-    let update_todo_param = use_state(|| String::new());
-    let update_todo_content = {
-        let id = todo.id.clone();
-        let param = update_todo_param.clone();
-        let d = dispatcher.clone();
-        use_async(async move {
-            let result = todo_client().update_todo(id, UpdateTodoRequest {
-                content: Some(param.to_string()),
-                status: None,
-            }).await.map_err(|e| e.to_string());
+    // the content being saved and the todo on the server, when the save is stale
+    let conflict = use_state(|| None::<(String, TodoResponse)>);
+    let context = use_context::<TodoContext>().expect("no ctx found");
 
-            d.dispatch(TodoAction::Refresh);
+    // save the content over the version it was edited from
+    let save_content = Rc::new({
+        let id = todo.id;
+        let conflict = conflict.clone();
+        let context = context.clone();
+        move |content: String, version: i32| {
+            let on_ack = {
+                let conflict = conflict.clone();
+                let yours = content.clone();
+                Callback::from(move |result: BatchResult| {
+                    if !matches!(&result, BatchResult::Error(e) if e.code == ErrorCode::VersionConflict) {
+                        return;
+                    }
+                    let conflict = conflict.clone();
+                    let yours = yours.clone();
+                    spawn_local(async move {
+                        if let Ok(Some(theirs)) = todo_client().get_todo_by_id(id).await {
+                            conflict.set(Some((yours, theirs)));
+                        }
+                    });
+                })
+            };
+            let body = UpdateTodoRequest {
+                content: Some(content),
+                ..Default::default()
+            };
+            context.mutate(BatchOperation::Update { id, body }, Some(version), on_ack);
+        }
+    });
 
-            result
+    let on_overwrite = {
+        let conflict = conflict.clone();
+        let save_content = save_content.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some((yours, theirs)) = (*conflict).clone() {
+                save_content(yours, theirs.version);
+            }
+            conflict.set(None);
+        })
+    };
+
+    let on_discard = {
+        let conflict = conflict.clone();
+        let d = dispatcher.clone();
+        Callback::from(move |_: MouseEvent| {
+            conflict.set(None);
+            d.dispatch(TodoAction::Refresh);
         })
     };
 
     let input_ref = use_node_ref();
-    let context = use_context::<TodoContext>().expect("no ctx found");
     let handle_input = Rc::new({
         let d = dispatcher.clone();
-        let id = todo.id.clone();
+        let id = todo.id;
+        let version = todo.version;
         let editing = editing.clone();
-        let update_todo_content = update_todo_content.clone();
-        let param = update_todo_param.clone();
         let input_ref = input_ref.clone();
         move || {
             if let Some(input) = input_ref.cast::<HtmlInputElement>() {
                 let value = input.value();
                 let value = value.trim();
-                if value.len() == 0 {
+                if value.is_empty() {
                     return;
                 }
                 if context.enable_remote {
-                    param.set(value.to_string());
-                    update_todo_content.run();
+                    save_content(value.to_string(), version);
                 } else {
                     d.dispatch(TodoAction::Update(id, UpdateTodoRequest {
                         content: Some(value.to_string()),
-                        status: None,
+                        ..Default::default()
                     }));
                 }
                 editing.set(false);
@@ -101,10 +174,33 @@ pub fn todo_details(TodoDetailsProps { todo, dispatcher }: &TodoDetailsProps) ->
         TodoStatus::Deleted => { "is-grey" }
     };
 
+    let priority_tag_color = match todo.priority {
+        TodoPriority::High => "is-primary",
+        _ => "is-white",
+    };
+
+    let overdue = todo.is_overdue(Utc::now());
+
+    let children = todos.iter()
+        .filter(|it| it.parent_id == Some(todo.id))
+        .collect::<Vec<&TodoResponse>>();
+    let done_children = children.iter().filter(|it| it.status == TodoStatus::Done).count();
+    let subtasks = subtask_ids(todos, todo.id);
+    let open_subtasks = todos.iter()
+        .filter(|it| subtasks.contains(&it.id) && it.status == TodoStatus::Todo)
+        .count();
+
     html! {
+        <>
         <div class="media is-align-items-center">
             <div class="media-left">
+                <input type="checkbox" class="mr-2"
+                    checked={selected.contains(&todo.id)}
+                    onclick={on_select.reform({ let id = todo.id; move |_| id })} />
                 <span class={format!("is-light is-rounded is-normal tag {}", status_tag_color)}>{ TodoStatusHtml(todo.status) }</span>
+                if todo.priority != TodoPriority::Normal {
+                    <span class={format!("is-rounded is-normal tag ml-1 {}", priority_tag_color)}>{ todo.priority.to_string().to_lowercase() }</span>
+                }
             </div>
             <div class="media-content">
                 <div class="control">
@@ -118,6 +214,32 @@ pub fn todo_details(TodoDetailsProps { todo, dispatcher }: &TodoDetailsProps) ->
                         readonly=true type="text" ref={input_ref} value={todo.content.clone()}/>
                 }
                 </div>
+                if !todo.tags.is_empty() {
+                    <div class="tags mb-0">
+                        { for todo.tags.iter().map(|tag| html! { <span class="tag is-rounded is-light">{ format!("#{}", tag) }</span> }) }
+                    </div>
+                }
+                if let Some(due_at) = todo.due_at {
+                    <p class="help">
+                        { format!("due {}", due_at.with_timezone(&Local).format("%Y-%m-%d %H:%M")) }
+                        if overdue {
+                            <span class="tag is-danger is-light is-rounded ml-2">{ "overdue" }</span>
+                        }
+                    </p>
+                }
+                if let Some(snippet) = &todo.snippet {
+                    <p class="help">{ highlight(snippet) }</p>
+                }
+                <p class="help">
+                    if !children.is_empty() {
+                        <a class="has-text-grey mr-3" onclick={toggle_expanded}>
+                            { format!("{} {}/{} subtasks", if *expanded { "▾" } else { "▸" }, done_children, children.len()) }
+                        </a>
+                    }
+                    if todo.status == TodoStatus::Todo {
+                        <a class="has-text-grey" onclick={toggle_adding}>{ if *adding { "cancel" } else { "+ subtask" } }</a>
+                    }
+                </p>
             </div>
             <div class="media-right">
                 <TodoControl id={todo.id}
@@ -125,8 +247,24 @@ pub fn todo_details(TodoDetailsProps { todo, dispatcher }: &TodoDetailsProps) ->
                     on_edit={toggle_edit}
                     on_save_editing={on_save_editing}
                     status={todo.status}
+                    {open_subtasks}
                     dispatcher={dispatcher.clone()} />
             </div>
         </div>
+        if let Some((yours, theirs)) = (*conflict).clone() {
+            <ConflictDialog {yours} {theirs} {on_overwrite} {on_discard} />
+        }
+        if *expanded && (!children.is_empty() || *adding) {
+            <div class="ml-5 pl-4" style="border-left: 2px solid #ededed">
+                { for children.into_iter().map(|child| html! {
+                    <TodoDetails todo={child.clone()} dispatcher={dispatcher.clone()} todos={todos.clone()}
+                        selected={selected.clone()} on_select={on_select.clone()} />
+                }) }
+                if *adding {
+                    <AddTodo dispatcher={dispatcher.clone()} parent_id={todo.id} />
+                }
+            </div>
+        }
+        </>
     }
 }
@@ -99,6 +99,15 @@ pub fn cloud_online() -> Html {
     }
 }
 
+#[function_component(CloudUpload)]
+pub fn cloud_upload() -> Html {
+    html! {
+        <svg style="width:24px;height:24px" viewBox="0 0 24 24">
+            <path fill="currentColor" d="M14,13V17H10V13H7L12,8L17,13M19.35,10.03C18.67,6.59 15.64,4 12,4C9.11,4 6.6,5.64 5.35,8.03C2.34,8.36 0,10.9 0,14A6,6 0 0,0 6,20H19A5,5 0 0,0 24,15C24,12.36 21.95,10.22 19.35,10.03Z" />
+        </svg>
+    }
+}
+
 #[function_component(CloudAlert)]
 pub fn cloud_alert() -> Html {
     html! {
@@ -107,3 +116,12 @@ pub fn cloud_alert() -> Html {
         </svg>
     }
 }
+
+#[function_component(Search)]
+pub fn search() -> Html {
+    html! {
+        <svg style="width:24px;height:24px" viewBox="0 0 24 24">
+            <path fill="currentColor" d="M9.5,3A6.5,6.5 0 0,1 16,9.5C16,11.11 15.41,12.59 14.44,13.73L14.71,14H15.5L20.5,19L19,20.5L14,15.5V14.71L13.73,14.44C12.59,15.41 11.11,16 9.5,16A6.5,6.5 0 0,1 3,9.5A6.5,6.5 0 0,1 9.5,3M9.5,5C7,5 5,7 5,9.5C5,12 7,14 9.5,14C12,14 14,12 14,9.5C14,7 12,5 9.5,5Z" />
+        </svg>
+    }
+}
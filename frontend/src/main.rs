@@ -4,6 +4,9 @@ mod icons;
 mod components;
 mod hooks;
 mod namespace;
+mod session;
+mod sync;
+mod offline;
 
 fn main() {
     yew::Renderer::<app::App>::new().render();
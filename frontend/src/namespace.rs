@@ -2,15 +2,22 @@ use gloo::storage::{LocalStorage, Storage};
 
 const KEY: &str = "rust_fullstack_todo.namespace";
 
+/// a new one in place of a stored one which cannot be sent in a header
 pub fn get() -> String {
     LocalStorage::get(KEY)
-        .unwrap_or_else(|_| {
+        .ok()
+        .filter(|it: &String| !it.chars().any(char::is_control))
+        .unwrap_or_else(|| {
             let value = generate();
             LocalStorage::set(KEY, value.clone()).unwrap();
             value
         })
 }
 
+pub fn set(namespace: &str) {
+    LocalStorage::set(KEY, namespace).unwrap();
+}
+
 fn generate() -> String {
     uuid::Uuid::new_v4().to_string()
 }
\ No newline at end of file
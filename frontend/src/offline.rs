@@ -0,0 +1,263 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use chrono::{DateTime, Utc};
+use gloo::console;
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use yew::Callback;
+
+use common::client::{ClientError, TodoClient};
+use common::model::{BatchOperation, BatchResult, ErrorCode, ErrorResponse, TodoChanges, TodoResponse, TodoStatus, UpdateTodoRequest};
+
+use crate::states::{create_todo, is_open, orphan, update_local};
+
+/// the remote todos as of the last sync, with the operations made since while offline
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+struct RemoteCache {
+    todos: Vec<TodoResponse>,
+    /// the `until` of the last changes pulled
+    synced_at: Option<DateTime<Utc>>,
+    outbox: Vec<PendingChange>,
+}
+
+/// an operation queued while offline, the ids of the todos created offline are negative
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct PendingChange {
+    op: BatchOperation,
+    /// the id in the cache of a `Create`
+    local_id: Option<i32>,
+    at: DateTime<Utc>,
+}
+
+impl RemoteCache {
+    fn key(namespace: &str) -> String {
+        format!("rust_fullstack_todo.remote.{}", namespace)
+    }
+
+    fn load(namespace: &str) -> Self {
+        LocalStorage::get(Self::key(namespace)).unwrap_or_default()
+    }
+
+    fn save(&self, namespace: &str) {
+        LocalStorage::set(Self::key(namespace), self)
+            .expect("failed to save");
+    }
+
+    /// apply the operation to the cached todos, the result is what the remote would answer
+    fn apply(&mut self, namespace: &str, op: &BatchOperation) -> (Option<i32>, BatchResult) {
+        let (local_id, id) = match op {
+            BatchOperation::Create(to_create) => {
+                if let Some(parent_id) = to_create.parent_id.filter(|&it| !is_open(&self.todos, it)) {
+                    return (None, BatchResult::Error(ErrorResponse {
+                        code: ErrorCode::ClosedParent,
+                        message: format!("parent todo {} is closed", parent_id),
+                    }));
+                }
+                let id = self.todos.iter().map(|it| it.id).min().unwrap_or_default().min(0) - 1;
+                let mut todo = create_todo(id, &to_create.content);
+                todo.namespace = namespace.to_string();
+                todo.due_at = to_create.due_at;
+                todo.priority = to_create.priority.unwrap_or_default();
+                todo.tags = to_create.tags.clone();
+                todo.parent_id = to_create.parent_id;
+                self.todos.insert(0, todo);
+
+                (Some(id), id)
+            }
+            BatchOperation::Update { id, body } => {
+                update_local(&mut self.todos, *id, body.clone());
+                (None, *id)
+            }
+            BatchOperation::Status { id, status, cascade } => {
+                update_local(&mut self.todos, *id, UpdateTodoRequest {
+                    status: Some(*status),
+                    cascade: *cascade,
+                    ..Default::default()
+                });
+                (None, *id)
+            }
+            BatchOperation::Delete { id } => {
+                let deleted = self.todos.iter().find(|it| it.id == *id).cloned();
+                self.todos.retain(|it| it.id != *id);
+                orphan(&mut self.todos, &[*id]);
+
+                return (None, deleted.map_or_else(|| not_found(*id), BatchResult::Ok));
+            }
+        };
+
+        let result = self.todos.iter()
+            .find(|it| it.id == id)
+            .cloned()
+            .map_or_else(|| not_found(id), BatchResult::Ok);
+        (local_id, result)
+    }
+
+    /// the todo created offline as `local_id` is `id` on the remote from now on
+    fn remap(&mut self, local_id: i32, id: i32) {
+        let remap = |it: &mut i32| if *it == local_id { *it = id };
+
+        for todo in self.todos.iter_mut() {
+            remap(&mut todo.id);
+            if let Some(parent_id) = todo.parent_id.as_mut() {
+                remap(parent_id);
+            }
+        }
+        for pending in self.outbox.iter_mut() {
+            match &mut pending.op {
+                BatchOperation::Create(to_create) => {
+                    if let Some(parent_id) = to_create.parent_id.as_mut() {
+                        remap(parent_id);
+                    }
+                }
+                BatchOperation::Update { id, .. } | BatchOperation::Status { id, .. } | BatchOperation::Delete { id } => remap(id),
+            }
+        }
+    }
+
+    /// the changes since `synced_at` over the cached todos, all of them replaced by full changes
+    fn merge(&mut self, changes: TodoChanges) {
+        if changes.full {
+            self.todos.clear();
+        }
+
+        self.todos.retain(|it| !changes.deleted.contains(&it.id));
+        for todo in changes.todos.into_iter().rev() {
+            match self.todos.iter().position(|it| it.id == todo.id) {
+                Some(index) => self.todos[index] = todo,
+                None => self.todos.insert(0, todo),
+            }
+        }
+
+        // a todo created offline is left only while its `Create` is queued
+        let queued = self.outbox.iter().filter_map(|it| it.local_id).collect::<Vec<i32>>();
+        self.todos.retain(|it| it.id > 0 || queued.contains(&it.id));
+        self.synced_at = Some(changes.until);
+    }
+}
+
+fn not_found(id: i32) -> BatchResult {
+    BatchResult::Error(ErrorResponse {
+        code: ErrorCode::TodoNotFound,
+        message: format!("todo {} not found", id),
+    })
+}
+
+/// the operation to replay, `None` when it is dropped:
+/// the todo has been deleted, or written on the remote after the operation was made offline
+fn resolve(pending: &PendingChange, theirs: &TodoChanges) -> Option<BatchOperation> {
+    // a negative id is left when the `Create` of its todo failed,
+    // full changes leave out the deleted ids, a todo missing from them is deleted
+    let deleted = |id: &i32| theirs.deleted.contains(id) || (theirs.full && theirs.todos.iter().all(|it| it.id != *id));
+    let kept = match &pending.op {
+        BatchOperation::Create(to_create) => to_create.parent_id
+            .is_none_or(|id| id > 0 && !deleted(&id)),
+        BatchOperation::Update { id, .. } | BatchOperation::Status { id, .. } | BatchOperation::Delete { id } => {
+            *id > 0 && !deleted(id) &&
+                theirs.todos.iter().all(|it| it.id != *id || it.updated_at <= pending.at)
+        }
+    };
+
+    kept.then(|| pending.op.clone())
+}
+
+/// the remote todos kept in the browser, written while the remote is unreachable and synced once it is back
+#[derive(Clone)]
+pub struct Offline {
+    namespace: String,
+    cache: Rc<RefCell<RemoteCache>>,
+    syncing: Rc<Cell<bool>>,
+    /// the cached todos have changed
+    on_change: Callback<()>,
+}
+
+impl PartialEq for Offline {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.cache, &other.cache)
+    }
+}
+
+impl Offline {
+    pub fn new(namespace: String, on_change: Callback<()>) -> Self {
+        let cache = RemoteCache::load(&namespace);
+        Self {
+            namespace,
+            cache: Rc::new(RefCell::new(cache)),
+            syncing: Rc::new(Cell::new(false)),
+            on_change,
+        }
+    }
+
+    pub fn todos(&self) -> Vec<TodoResponse> {
+        self.cache.borrow().todos.clone()
+    }
+
+    /// the ids of the cached todos with the status
+    pub fn ids_with(&self, status: TodoStatus) -> Vec<i32> {
+        self.cache.borrow().todos.iter()
+            .filter(|it| it.status == status)
+            .map(|it| it.id)
+            .collect()
+    }
+
+    /// apply the operation to the cache and queue it until the remote is back
+    pub fn queue(&self, op: BatchOperation) -> BatchResult {
+        let result = {
+            let mut cache = self.cache.borrow_mut();
+            let (local_id, result) = cache.apply(&self.namespace, &op);
+            if matches!(result, BatchResult::Ok(_)) {
+                cache.outbox.push(PendingChange { op, local_id, at: Utc::now() });
+            }
+            cache.save(&self.namespace);
+            result
+        };
+
+        self.on_change.emit(());
+        result
+    }
+
+    /// replay the queued operations in order, then pull the changes since the last sync,
+    /// the queued operations left are replayed by the next sync after a transport error
+    pub async fn sync(&self, client: &TodoClient) -> Result<(), ClientError> {
+        if self.syncing.replace(true) {
+            return Ok(());
+        }
+
+        let result = self.replay(client).await;
+        self.syncing.set(false);
+        self.on_change.emit(());
+
+        result
+    }
+
+    async fn replay(&self, client: &TodoClient) -> Result<(), ClientError> {
+        let since = self.cache.borrow().synced_at;
+        let theirs = client.get_changes(since).await?;
+
+        loop {
+            let Some(pending) = self.cache.borrow().outbox.first().cloned() else { break; };
+
+            if let Some(op) = resolve(&pending, &theirs) {
+                let response = client.batch(vec![op]).await?;
+                match (response.results.into_iter().next(), pending.local_id) {
+                    (Some(BatchResult::Ok(todo)), Some(local_id)) => self.cache.borrow_mut().remap(local_id, todo.id),
+                    (Some(BatchResult::Error(error)), _) => console::warn!(format!("dropped {:?}: {}", pending.op, error)),
+                    _ => {}
+                }
+            } else {
+                console::warn!(format!("dropped {:?}: the todo has been changed on the remote", pending.op));
+            }
+
+            let mut cache = self.cache.borrow_mut();
+            cache.outbox.remove(0);
+            cache.save(&self.namespace);
+        }
+
+        let changes = client.get_changes(since).await?;
+        let mut cache = self.cache.borrow_mut();
+        cache.merge(changes);
+        cache.save(&self.namespace);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,16 @@
+use gloo::storage::{LocalStorage, Storage};
+
+const KEY: &str = "rust_fullstack_todo.token";
+
+/// the bearer token of `POST /auth/login`, `None` while logged out
+pub fn get() -> Option<String> {
+    LocalStorage::get(KEY).ok()
+}
+
+pub fn set(token: &str) {
+    LocalStorage::set(KEY, token).unwrap();
+}
+
+pub fn clear() {
+    LocalStorage::delete(KEY);
+}
@@ -2,14 +2,21 @@ use std::rc::Rc;
 
 use chrono::Utc;
 use gloo::storage::{LocalStorage, Storage};
-use yew::Reducible;
+use yew::{Callback, Reducible};
 
-use common::model::{TodoResponse, TodoStatus, UpdateTodoRequest};
+use common::model::{BatchOperation, BatchResult, MoveTodoRequest, TodoResponse, TodoStatus, UpdateTodoRequest};
+
+use crate::offline::Offline;
+use crate::sync::SyncChannel;
 
 #[derive(Clone)]
 pub enum TodoAction {
-    Add(String),
+    /// content, tags and the parent of a subtask
+    Add(String, Vec<String>, Option<i32>),
     Update(i32, UpdateTodoRequest),
+    /// the same update of the selected todos
+    UpdateAll(Vec<i32>, UpdateTodoRequest),
+    Move(i32, MoveTodoRequest),
     ClearDeleted,
     Refresh,
 }
@@ -20,12 +27,40 @@ pub struct TodoState {
     pub refresh: bool,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub struct TodoContext {
     pub enable_remote: bool,
+    /// `PingClient::health` of the remote, polled while remote
+    pub online: bool,
+    /// open while remote and online
+    pub sync: Option<SyncChannel>,
+    /// the remote todos kept in the browser for the time offline
+    pub offline: Offline,
+}
+
+impl TodoContext {
+    /// remote, but the remote is not reachable
+    pub fn is_offline(&self) -> bool {
+        self.enable_remote && !self.online
+    }
+
+    /// apply the operation remotely, the todos are reloaded by the change it makes,
+    /// or else queue it until the remote is back
+    pub fn mutate(&self, op: BatchOperation, if_match: Option<i32>, on_ack: Callback<BatchResult>) {
+        match &self.sync {
+            Some(sync) if self.online => sync.mutate(op, if_match, on_ack),
+            // replayed without `If-Match`, the latest `updated_at` wins instead
+            _ => on_ack.emit(self.offline.queue(op)),
+        }
+    }
 }
 
 impl TodoState {
+    /// tag names of the local todos in alphabetical order
+    pub fn local_tags(&self) -> Vec<String> {
+        tags_of(&self.locals)
+    }
+
     pub fn save_to_local(&self) {
         LocalStorage::set(KEY, self.locals.clone())
             .expect("failed to save");
@@ -48,32 +83,55 @@ impl Reducible for TodoState {
 
     fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
         let next = match action {
-            TodoAction::Add(content) => {
+            TodoAction::Add(content, tags, parent_id) => {
                 let mut locals = self.locals.clone();
-                let todo = create_todo(&locals.len(), &content);
+                // ids stay unique after clearing, the subtasks refer to them
+                let id = locals.iter().map(|it| it.id + 1).max().unwrap_or_default();
+                let mut todo = create_todo(id, &content);
+                todo.tags = tags;
+                todo.parent_id = parent_id;
                 locals.insert(0, todo);
 
                 locals
             }
             TodoAction::Update(id, update) => {
+                let mut locals = self.locals.clone();
+                update_local(&mut locals, id, update);
+
+                locals
+            }
+            TodoAction::UpdateAll(ids, update) => {
+                let mut locals = self.locals.clone();
+                for id in ids {
+                    update_local(&mut locals, id, update.clone());
+                }
+
+                locals
+            }
+            TodoAction::Move(id, MoveTodoRequest { before, after }) => {
                 let mut locals = self.locals.clone();
                 if let Some(index) = locals.iter().position(|it| it.id == id) {
-                    if let Some(content) = update.content {
-                        locals[index].content = content;
-                    }
-                    if let Some(status) = update.status {
-                        locals[index].status = status;
+                    let moving = locals.remove(index);
+                    let target = before.or(after)
+                        .and_then(|target| locals.iter().position(|it| it.id == target));
+                    match target {
+                        Some(target) if after.is_some() => locals.insert(target + 1, moving),
+                        Some(target) => locals.insert(target, moving),
+                        None => locals.insert(index, moving),
                     }
-                    locals[index].updated_at = Utc::now();
                 }
 
                 locals
             }
             TodoAction::ClearDeleted => {
-                self.locals.clone()
-                    .into_iter()
-                    .filter(|it| it.status != TodoStatus::Deleted)
-                    .collect()
+                let mut locals = self.locals.clone();
+                let cleared = locals.iter()
+                    .filter(|it| it.status == TodoStatus::Deleted)
+                    .map(|it| it.id)
+                    .collect::<Vec<i32>>();
+                locals.retain(|it| !cleared.contains(&it.id));
+                orphan(&mut locals, &cleared);
+                locals
             }
             _ => { self.locals.clone() }
         };
@@ -85,13 +143,93 @@ impl Reducible for TodoState {
     }
 }
 
-fn create_todo(id: &usize, content: &str) -> TodoResponse {
+pub fn create_todo(id: i32, content: &str) -> TodoResponse {
     TodoResponse {
         namespace: "local".to_string(),
-        id: *id as i32,
+        id,
         status: TodoStatus::Todo,
         content: content.to_string(),
         created_at: Utc::now(),
         updated_at: Utc::now(),
+        due_at: None,
+        priority: Default::default(),
+        tags: vec![],
+        parent_id: None,
+        version: 1,
+        rank: None,
+        snippet: None,
+    }
+}
+
+/// apply the update to the local todo, like the backend a parent leaves `todo` once all of its subtasks have,
+/// and a subtask is back to `todo` only under a `todo` parent
+pub fn update_local(locals: &mut [TodoResponse], id: i32, update: UpdateTodoRequest) {
+    let Some(index) = locals.iter().position(|it| it.id == id) else { return; };
+
+    if update.status == Some(TodoStatus::Todo) && locals[index].status != TodoStatus::Todo
+        && locals[index].parent_id.is_some_and(|parent_id| !is_open(locals, parent_id)) {
+        return;
+    }
+
+    if let Some(status) = update.status.filter(|&it| it != locals[index].status && it != TodoStatus::Todo) {
+        let subtasks = subtask_ids(locals, id);
+        let mut open = locals.iter_mut()
+            .filter(|it| subtasks.contains(&it.id) && it.status == TodoStatus::Todo)
+            .peekable();
+        if open.peek().is_some() && !update.cascade {
+            return;
+        }
+        open.for_each(|it| it.status = status);
+    }
+    if let Some(content) = update.content {
+        locals[index].content = content;
+    }
+    if let Some(status) = update.status {
+        locals[index].status = status;
+    }
+    if let Some(due_at) = update.due_at {
+        locals[index].due_at = due_at;
+    }
+    if let Some(priority) = update.priority {
+        locals[index].priority = priority;
+    }
+    if let Some(tags) = update.tags {
+        locals[index].tags = tags;
+    }
+    locals[index].updated_at = Utc::now();
+}
+
+/// tag names of the todos in alphabetical order
+pub fn tags_of(todos: &[TodoResponse]) -> Vec<String> {
+    let mut tags = todos.iter()
+        .flat_map(|it| it.tags.iter().cloned())
+        .collect::<Vec<String>>();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// the subtasks of the hard-deleted todos are top-level from now on, like on the backend
+pub fn orphan(todos: &mut [TodoResponse], deleted: &[i32]) {
+    todos.iter_mut()
+        .filter(|it| it.parent_id.is_some_and(|parent_id| deleted.contains(&parent_id)))
+        .for_each(|it| it.parent_id = None);
+}
+
+/// a missing parent has been hard-deleted, its subtasks are top-level
+pub fn is_open(todos: &[TodoResponse], id: i32) -> bool {
+    todos.iter().find(|it| it.id == id).is_none_or(|it| it.status == TodoStatus::Todo)
+}
+
+/// ids of the subtasks of the todo at any depth
+pub fn subtask_ids(todos: &[TodoResponse], id: i32) -> Vec<i32> {
+    let mut found = vec![];
+    let mut parents = vec![id];
+    while let Some(parent) = parents.pop() {
+        for todo in todos.iter().filter(|it| it.parent_id == Some(parent)) {
+            found.push(todo.id);
+            parents.push(todo.id);
+        }
     }
+    found
 }
@@ -0,0 +1,92 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use futures::{SinkExt, StreamExt};
+use futures::channel::mpsc::{unbounded, UnboundedSender};
+use gloo::console;
+use gloo::net::websocket::futures::WebSocket;
+use gloo::net::websocket::Message;
+use wasm_bindgen_futures::spawn_local;
+use yew::Callback;
+
+use common::model::{BatchOperation, BatchResult, SyncRequest, SyncResponse, TodoEvent};
+
+/// the WebSocket of `GET /todos/sync`, sending the mutations of the components while remote
+#[derive(Clone, Debug)]
+pub struct SyncChannel {
+    socket: web_sys::WebSocket,
+    sender: UnboundedSender<SyncRequest>,
+    pending: Rc<RefCell<Pending>>,
+}
+
+/// the acknowledgements to come, by `seq`
+#[derive(Default, Debug)]
+struct Pending {
+    seq: u64,
+    acks: HashMap<u64, Callback<BatchResult>>,
+}
+
+impl PartialEq for SyncChannel {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.pending, &other.pending)
+    }
+}
+
+impl SyncChannel {
+    /// `on_change` gets the changes in the namespace, `None` when the url is invalid
+    pub fn open(url: &str, on_change: Callback<TodoEvent>) -> Option<Self> {
+        let socket = web_sys::WebSocket::new(url).ok()?;
+        let (mut write, mut read) = WebSocket::try_from(socket.clone()).ok()?.split();
+        let (sender, mut requests) = unbounded::<SyncRequest>();
+        let pending = Rc::new(RefCell::new(Pending::default()));
+
+        // sent once the socket is open
+        spawn_local(async move {
+            while let Some(request) = requests.next().await {
+                let text = serde_json::to_string(&request).unwrap();
+                if write.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let acks = pending.clone();
+        spawn_local(async move {
+            while let Some(Ok(message)) = read.next().await {
+                let Message::Text(text) = message else { continue; };
+                match serde_json::from_str::<SyncResponse>(&text) {
+                    Ok(SyncResponse::Ack { seq, result }) => {
+                        let on_ack = acks.borrow_mut().acks.remove(&seq);
+                        if let Some(on_ack) = on_ack {
+                            on_ack.emit(result);
+                        }
+                    }
+                    Ok(SyncResponse::Change { event }) => on_change.emit(event),
+                    Ok(SyncResponse::Error { error }) => console::error!(error.to_string()),
+                    Err(err) => console::error!(err.to_string()),
+                }
+            }
+        });
+
+        Some(Self { socket, sender, pending })
+    }
+
+    /// apply the operation, `on_ack` gets its result
+    pub fn mutate(&self, op: BatchOperation, if_match: Option<i32>, on_ack: Callback<BatchResult>) {
+        let seq = {
+            let mut pending = self.pending.borrow_mut();
+            pending.seq += 1;
+            let seq = pending.seq;
+            pending.acks.insert(seq, on_ack);
+            seq
+        };
+
+        let _ = self.sender.unbounded_send(SyncRequest::Mutate { seq, op, if_match });
+    }
+
+    pub fn close(&self) {
+        self.sender.close_channel();
+        let _ = self.socket.close();
+    }
+}